@@ -1,14 +1,32 @@
+pub mod bigint;
 pub mod builtins;
+pub mod enums;
+pub mod gc;
+pub mod generator;
+pub mod macro_expansion;
+pub mod methods;
+pub mod module;
 pub mod object;
+pub mod quote_unquote;
+pub mod structs;
 
-use std::collections::HashMap;
+use std::{collections::HashMap, rc::Rc};
 
-use object::{new_error, Env, Environment, Object, FALSE, TRUE};
+use bigint::BigInt;
+use enums::EnumType;
+use generator::{Generator, Iter};
+use object::{
+    new_error, new_error_with_kind, Env, Environment, ErrorKind, Object, RuntimeError, FALSE, TRUE,
+};
+use structs::{StructInstance, StructType};
 
 use crate::lexer::token::TokenKind;
-use crate::parser::ast::{Expression, Identifier, Literal, Program, Statement};
+use crate::parser::ast::{
+    CatchClause, EnumVariant, Expression, Identifier, Literal, MatchArm, Pattern, Program,
+    Statement,
+};
 
-use self::builtins::{first, last, len, push, puts, rest};
+use self::builtins::{collect, done, first, float, int, last, len, next, push, puts, rest, take};
 
 pub trait Eval {
     fn eval(&self, env: Env) -> Option<Object>;
@@ -18,6 +36,7 @@ impl Eval for Program {
     fn eval(&self, env: Env) -> Option<Object> {
         let mut result = None;
         for statement in &self.statements {
+            gc::collect_if_due();
             result = statement.eval(env.clone());
 
             match result {
@@ -34,7 +53,15 @@ impl Eval for Statement {
     fn eval(&self, env: Env) -> Option<Object> {
         match self {
             Statement::Expression(expr) => expr.eval(env),
-            Statement::Block(statements) => eval_block_statement(statements, env),
+            Statement::Block(statements) => {
+                let block_env = Environment::new_enclosed(env).into_env();
+                eval_block_statement(statements, block_env)
+            }
+            Statement::Import(path, name) => match module::import_module(path, &env) {
+                Ok(module) => env.borrow_mut().set(name.clone(), module).err(),
+                Err(e) => Some(e),
+            },
+            Statement::Export(statement) => statement.eval(env),
             Statement::Return(expr) => {
                 let value = expr.eval(env)?;
                 if value.is_error() {
@@ -42,24 +69,66 @@ impl Eval for Statement {
                 }
                 Some(Object::ReturnValue(Box::new(value)))
             }
-            Statement::Let(ident, expr) => {
+            Statement::Throw(expr) => {
+                let value = expr.eval(env)?;
+                if value.is_error() {
+                    return Some(value);
+                }
+                Some(thrown_error(value))
+            }
+            Statement::Let(pattern, expr) => {
                 let value = expr.eval(env.clone())?;
                 if value.is_error() {
                     return Some(value);
                 }
-                env.borrow_mut().set(ident.clone(), value);
-                None
+                bind_pattern(pattern, value, &mut env.borrow_mut()).err()
+            }
+            Statement::Const(name, expr, position) => {
+                let value = expr.eval(env.clone())?;
+                if value.is_error() {
+                    return Some(value);
+                }
+                env.borrow_mut()
+                    .set_const(name.clone(), value, *position)
+                    .err()
+            }
+            Statement::Struct(name, fields) => {
+                let ty = StructType::new(name.clone(), fields.clone());
+                env.borrow_mut()
+                    .set(name.clone(), Object::StructType(gc::track(ty)))
+                    .err()
+            }
+            Statement::Impl(name, methods) => eval_impl_statement(name, methods, env).err(),
+            Statement::Enum(name, variants) => {
+                eval_enum_statement(name, variants, &mut env.borrow_mut()).err()
+            }
+            Statement::For(pattern, iterable, body) => {
+                eval_for_statement(pattern, iterable, body, env)
             }
+            // Generators run the yields they can pause at themselves, so one
+            // reaching here is somewhere a generator can't resume from.
+            Statement::Yield(_) => Some(new_error(
+                "yield can only be used as a statement of a generator, \
+                 outside of expressions and try blocks"
+                    .to_string(),
+            )),
         }
     }
 }
 
 impl Eval for Expression {
+    // Hash keys never hash the environment of a function, so the interior
+    // mutability of `Env` can't change a key's hash.
+    #[allow(clippy::mutable_key_type)]
     fn eval(&self, env: Env) -> Option<Object> {
         Some(match self {
             Expression::Literal(literal) => literal.eval(env)?,
             Expression::Identifier(ident) => eval_identifier(ident, env),
             Expression::If(cond, cons, alt) => return eval_if_expression(cond, cons, alt, env),
+            Expression::Match(value, arms) => return eval_match_expression(value, arms, env),
+            Expression::Try(body, catch, finally) => {
+                return eval_try_expression(body, catch, finally, env)
+            }
             Expression::Prefix(op, value) => {
                 let value = value.eval(env)?;
                 if value.is_error() {
@@ -67,6 +136,13 @@ impl Eval for Expression {
                 }
                 eval_prefix_expression(op, value)?
             }
+            Expression::Infix(left, TokenKind::NullCoalesce, right) => {
+                let left = left.eval(env.clone()).unwrap_or(Object::Null);
+                if !matches!(left, Object::Null) {
+                    return Some(left);
+                }
+                return right.eval(env);
+            }
             Expression::Infix(left, op, right) => {
                 let left = left.eval(env.clone())?;
                 if left.is_error() {
@@ -79,19 +155,29 @@ impl Eval for Expression {
                 eval_infix_expression(left, op, right)
             }
             Expression::Function(params, body) => {
-                Object::Function(params.clone(), *body.clone(), env)
+                Object::Function(params.as_slice().into(), Rc::new((**body).clone()), env)
             }
+            Expression::Generator(params, body) => {
+                Object::GeneratorFunction(params.as_slice().into(), Rc::new((**body).clone()), env)
+            }
+            Expression::Macro(params, body) => Object::Macro(params.clone(), body.clone(), env),
             Expression::Call(ident, arguments) => {
-                let function = ident.eval(env.clone())?;
-                if function.is_error() {
-                    return Some(function);
-                }
-                let args = eval_expressions(arguments, env);
-                match args {
-                    Err(e) => e,
-                    Ok(args) => return apply_function(function, &args),
+                return match eval_call(ident, arguments, env, false) {
+                    Tail::Value(value) => value,
+                    Tail::Call(_, _, _) => unreachable!("only calls in tail position are deferred"),
+                };
+            }
+            Expression::StructLiteral(name, fields) => {
+                return Some(eval_struct_literal(name, fields, env));
+            }
+            Expression::Field(left, name) => {
+                let left = left.eval(env)?;
+                if left.is_error() {
+                    return Some(left);
                 }
+                eval_field_expression(left, name)
             }
+            Expression::Assign(target, value) => return Some(eval_assignment(target, value, env)),
             Expression::Array(elements) => {
                 let elements = eval_expressions(elements, env);
                 match elements {
@@ -106,6 +192,12 @@ impl Eval for Expression {
                     if key.is_error() {
                         return Some(key);
                     }
+                    if !key.is_hashable() {
+                        return Some(new_error_with_kind(
+                            ErrorKind::Type,
+                            format!("unusable as hash key: {key}"),
+                        ));
+                    }
                     let value = value.eval(env.clone())?;
                     if value.is_error() {
                         return Some(value);
@@ -115,6 +207,17 @@ impl Eval for Expression {
 
                 Object::Hash(map)
             }
+            Expression::Interpolated(parts) => {
+                let mut string = String::new();
+                for part in parts {
+                    let value = part.eval(env.clone()).unwrap_or(Object::Null);
+                    if value.is_error() {
+                        return Some(value);
+                    }
+                    string.push_str(&value.inspect());
+                }
+                Object::String(string)
+            }
             Expression::Index(left, index) => {
                 let left = left.eval(env.clone())?;
                 if left.is_error() {
@@ -128,17 +231,55 @@ impl Eval for Expression {
                 }
                 eval_index_expression(left, index)
             }
+            Expression::Slice(left, start, end) => {
+                let left = left.eval(env.clone())?;
+                if left.is_error() {
+                    return Some(left);
+                }
+                let start = match eval_slice_bound(start, env.clone()) {
+                    Err(e) => return Some(e),
+                    Ok(start) => start,
+                };
+                let end = match eval_slice_bound(end, env) {
+                    Err(e) => return Some(e),
+                    Ok(end) => end,
+                };
+                eval_slice_expression(left, start, end)
+            }
+            Expression::OptionalIndex(left, index) => {
+                let left = left.eval(env.clone()).unwrap_or(Object::Null);
+                if left.is_error() || matches!(left, Object::Null) {
+                    return Some(left);
+                }
+                let index = index.eval(env)?;
+                if index.is_error() {
+                    return Some(index);
+                }
+                eval_index_expression(left, index)
+            }
         })
     }
 }
 
 impl Eval for Literal {
     fn eval(&self, _env: Env) -> Option<Object> {
-        Some(match self {
-            Literal::Integer(int) => Object::Integer(*int),
-            Literal::String(string) => Object::String(string.clone()),
-            Literal::Boolean(bool) => (*bool).into(),
-        })
+        Some(eval_literal(self))
+    }
+}
+
+/// Literals don't depend on the environment, so they can be turned into
+/// objects without one.
+fn eval_literal(literal: &Literal) -> Object {
+    match literal {
+        Literal::Integer(int) => Object::Integer(*int),
+        Literal::BigInteger(digits) => match digits.parse::<BigInt>() {
+            Ok(int) => int.into(),
+            Err(e) => new_error(e),
+        },
+        Literal::Float(float) => Object::Float(*float),
+        Literal::String(string) => Object::String(string.clone()),
+        Literal::Boolean(bool) => (*bool).into(),
+        Literal::Null => Object::Null,
     }
 }
 
@@ -160,7 +301,10 @@ fn eval_prefix_expression(operator: &TokenKind, value: Object) -> Option<Object>
     Some(match operator {
         TokenKind::Bang => eval_bang_operator(value),
         TokenKind::Minus => eval_minus_operator(value),
-        _ => new_error(format!("unknown operator: {operator}{value}")),
+        _ => new_error_with_kind(
+            ErrorKind::Type,
+            format!("unknown operator: {operator}{value}"),
+        ),
     })
 }
 
@@ -174,43 +318,146 @@ fn eval_bang_operator(value: Object) -> Object {
 }
 
 fn eval_minus_operator(value: Object) -> Object {
-    let Object::Integer(int) = value else {
-        return new_error(format!("unknown operator: -{value}"))
-    };
-
-    Object::Integer(-int)
+    match value {
+        Object::Integer(int) => match int.checked_neg() {
+            Some(negated) => Object::Integer(negated),
+            None => (-&BigInt::from(int)).into(),
+        },
+        Object::BigInteger(int) => (-&int).into(),
+        Object::Float(float) => Object::Float(-float),
+        _ => new_error_with_kind(ErrorKind::Type, format!("unknown operator: -{value}")),
+    }
 }
 
 fn eval_infix_expression(left: Object, operator: &TokenKind, right: Object) -> Object {
-    if std::mem::discriminant(&left) != std::mem::discriminant(&right) {
-        return new_error(format!("type mismatch: {left} {operator} {right}"));
+    if operator == &TokenKind::Compose {
+        return eval_composition(left, right);
     }
 
     let created = match (&left, &right) {
         (Object::Integer(left), Object::Integer(right)) => {
             eval_integer_infix_expression(*left, operator, *right)
         }
+        // Mixing integers and floats promotes the integer side to a float.
+        (Object::Float(left), Object::Float(right)) => {
+            eval_float_infix_expression(*left, operator, *right)
+        }
+        (Object::Integer(left), Object::Float(right)) => {
+            eval_float_infix_expression(*left as f64, operator, *right)
+        }
+        (Object::Float(left), Object::Integer(right)) => {
+            eval_float_infix_expression(*left, operator, *right as f64)
+        }
+        (Object::BigInteger(left), Object::Float(right)) => {
+            eval_float_infix_expression(left.to_f64(), operator, *right)
+        }
+        (Object::Float(left), Object::BigInteger(right)) => {
+            eval_float_infix_expression(*left, operator, right.to_f64())
+        }
+        (Object::BigInteger(left), Object::BigInteger(right)) => {
+            eval_big_integer_infix_expression(left, operator, right)
+        }
+        (Object::BigInteger(left), Object::Integer(right)) => {
+            eval_big_integer_infix_expression(left, operator, &BigInt::from(*right))
+        }
+        (Object::Integer(left), Object::BigInteger(right)) => {
+            eval_big_integer_infix_expression(&BigInt::from(*left), operator, right)
+        }
+        (Object::Null, _) | (_, Object::Null) => {
+            eval_null_infix_expression(&left, operator, &right)
+        }
+        _ if std::mem::discriminant(&left) != std::mem::discriminant(&right) => {
+            return new_error_with_kind(
+                ErrorKind::Type,
+                format!("type mismatch: {left} {operator} {right}"),
+            );
+        }
         (Object::Boolean(left), Object::Boolean(right)) => {
             eval_boolean_infix_expression(*left, operator, *right)
         }
         (Object::String(left), Object::String(right)) => {
             eval_string_infix_expression(left, operator, right)
         }
-        (_, _) => None,
+        // Anything else compares by what it holds, or by identity for
+        // functions and types.
+        (_, _) => match operator {
+            TokenKind::Equal => Some((left == right).into()),
+            TokenKind::NotEqual => Some((left != right).into()),
+            _ => None,
+        },
     };
 
     match created {
         Some(obj) => obj,
-        None => new_error(format!("unknown operator: {left} {operator} {right}")),
+        None => new_error_with_kind(
+            ErrorKind::Type,
+            format!("unknown operator: {left} {operator} {right}"),
+        ),
+    }
+}
+
+fn eval_composition(first: Object, second: Object) -> Object {
+    if !first.is_callable() || !second.is_callable() {
+        return new_error_with_kind(
+            ErrorKind::Type,
+            format!("cannot compose {first} >> {second}, both sides must be functions"),
+        );
     }
+    Object::Composition(Box::new(first), Box::new(second))
 }
 
 fn eval_integer_infix_expression(left: i64, operator: &TokenKind, right: i64) -> Option<Object> {
+    let checked = match operator {
+        TokenKind::Plus => left.checked_add(right),
+        TokenKind::Minus => left.checked_sub(right),
+        TokenKind::Asterisk => left.checked_mul(right),
+        TokenKind::Slash | TokenKind::Percent if right == 0 => {
+            return Some(new_error_with_kind(
+                ErrorKind::ZeroDivision,
+                "division by zero".to_string(),
+            ))
+        }
+        TokenKind::Slash => left.checked_div(right),
+        TokenKind::Percent => left.checked_rem(right),
+        _ => return eval_comparison(left, operator, right),
+    };
+
+    // Anything that overflows an i64 gets redone with arbitrary precision.
+    match checked {
+        Some(int) => Some(Object::Integer(int)),
+        None => eval_big_integer_infix_expression(&left.into(), operator, &right.into()),
+    }
+}
+
+fn eval_big_integer_infix_expression(
+    left: &BigInt,
+    operator: &TokenKind,
+    right: &BigInt,
+) -> Option<Object> {
+    let result = match operator {
+        TokenKind::Plus => left + right,
+        TokenKind::Minus => left - right,
+        TokenKind::Asterisk => left * right,
+        TokenKind::Slash | TokenKind::Percent => {
+            let Some((quotient, remainder)) = left.div_rem(right) else {
+                return Some(new_error_with_kind(
+                    ErrorKind::ZeroDivision,
+                    "division by zero".to_string(),
+                ));
+            };
+            if operator == &TokenKind::Slash {
+                quotient
+            } else {
+                remainder
+            }
+        }
+        _ => return eval_comparison(left, operator, right),
+    };
+    Some(result.into())
+}
+
+fn eval_comparison<T: PartialOrd>(left: T, operator: &TokenKind, right: T) -> Option<Object> {
     match operator {
-        TokenKind::Plus => Some(Object::Integer(left + right)),
-        TokenKind::Minus => Some(Object::Integer(left - right)),
-        TokenKind::Asterisk => Some(Object::Integer(left * right)),
-        TokenKind::Slash => Some(Object::Integer(left / right)),
         TokenKind::LessThan => Some((left < right).into()),
         TokenKind::GreaterThan => Some((left > right).into()),
         TokenKind::Equal => Some((left == right).into()),
@@ -219,6 +466,17 @@ fn eval_integer_infix_expression(left: i64, operator: &TokenKind, right: i64) ->
     }
 }
 
+fn eval_float_infix_expression(left: f64, operator: &TokenKind, right: f64) -> Option<Object> {
+    match operator {
+        TokenKind::Plus => Some(Object::Float(left + right)),
+        TokenKind::Minus => Some(Object::Float(left - right)),
+        TokenKind::Asterisk => Some(Object::Float(left * right)),
+        TokenKind::Slash => Some(Object::Float(left / right)),
+        TokenKind::Percent => Some(Object::Float(left % right)),
+        _ => eval_comparison(left, operator, right),
+    }
+}
+
 fn eval_boolean_infix_expression(left: bool, operator: &TokenKind, right: bool) -> Option<Object> {
     match operator {
         TokenKind::Equal => Some((left == right).into()),
@@ -227,6 +485,19 @@ fn eval_boolean_infix_expression(left: bool, operator: &TokenKind, right: bool)
     }
 }
 
+fn eval_null_infix_expression(
+    left: &Object,
+    operator: &TokenKind,
+    right: &Object,
+) -> Option<Object> {
+    let both_null = matches!((left, right), (Object::Null, Object::Null));
+    match operator {
+        TokenKind::Equal => Some(both_null.into()),
+        TokenKind::NotEqual => Some((!both_null).into()),
+        _ => None,
+    }
+}
+
 fn eval_string_infix_expression(left: &str, operator: &TokenKind, right: &str) -> Option<Object> {
     match operator {
         TokenKind::Plus => Some(Object::String(format!("{left}{right}"))),
@@ -255,141 +526,838 @@ fn eval_if_expression(
     None
 }
 
-fn eval_index_expression(left: Object, index: Object) -> Object {
-    match (&left, &index) {
-        (Object::Array(arr), Object::Integer(i)) => {
-            arr.get(*i as usize).unwrap_or(&Object::Null).clone()
+/// Turns a possibly negative index into a position, counting negative
+/// indices from the end like Python does.
+fn resolve_index(index: i64, len: usize) -> Option<usize> {
+    let index = if index < 0 {
+        index.checked_add(len as i64)?
+    } else {
+        index
+    };
+    usize::try_from(index).ok().filter(|&i| i < len)
+}
+
+/// Same as `resolve_index`, but out of range bounds get clamped instead.
+fn resolve_slice_bound(bound: i64, len: usize) -> usize {
+    let bound = if bound < 0 {
+        bound.saturating_add(len as i64)
+    } else {
+        bound
+    };
+    bound.clamp(0, len as i64) as usize
+}
+
+fn eval_match_expression(value: &Expression, arms: &[MatchArm], env: Env) -> Option<Object> {
+    match select_arm(value, arms, env) {
+        Ok(Some((body, arm_env))) => body.eval(arm_env),
+        Ok(None) => Some(Object::Null),
+        Err(e) => Some(e),
+    }
+}
+
+/// Finds the first arm that matches the value of `value`, returning its body
+/// along with the scope holding the arm's bindings.
+fn select_arm<'a>(
+    value: &Expression,
+    arms: &'a [MatchArm],
+    env: Env,
+) -> Result<Option<(&'a Statement, Env)>, Object> {
+    let value = value.eval(env.clone()).unwrap_or(Object::Null);
+    if value.is_error() {
+        return Err(value);
+    }
+
+    for arm in arms {
+        // Every arm gets its own scope, so bindings from an arm that didn't
+        // match never leak into the next one.
+        let mut arm_env = Environment::new_enclosed(env.clone());
+        if bind_pattern(&arm.pattern, value.clone(), &mut arm_env).is_err() {
+            continue;
         }
-        (Object::Hash(map), _) => {
-            if !index.is_hashable() {
-                return new_error(format!("unusable as hash key: {}", &index));
+        let arm_env = arm_env.into_env();
+
+        if let Some(guard) = &arm.guard {
+            let guard = guard.eval(arm_env.clone()).unwrap_or(Object::Null);
+            if guard.is_error() {
+                return Err(guard);
+            }
+            if !guard.is_truthy() {
+                continue;
             }
+        }
 
-            map.get(&index).unwrap_or(&Object::Null).clone()
+        return Ok(Some((&arm.body, arm_env)));
+    }
+    Ok(None)
+}
+
+fn eval_for_statement(
+    pattern: &Pattern,
+    iterable: &Expression,
+    body: &Statement,
+    env: Env,
+) -> Option<Object> {
+    let iterable = iterable.eval(env.clone()).unwrap_or(Object::Null);
+    let values = match Iter::new(iterable) {
+        Ok(values) => values,
+        Err(e) => return Some(e),
+    };
+
+    for value in values {
+        if value.is_error() {
+            return Some(value);
+        }
+        gc::collect_if_due();
+        let mut loop_env = Environment::new_enclosed(env.clone());
+        if let Err(e) = bind_pattern(pattern, value, &mut loop_env) {
+            return Some(e);
+        }
+        let result = body.eval(loop_env.into_env());
+        if matches!(
+            result,
+            Some(Object::ReturnValue(_)) | Some(Object::Error(_))
+        ) {
+            return result;
         }
-        (_, _) => new_error(format!("index operator not supported: {left}")),
     }
+    None
 }
 
-fn eval_expressions(expressions: &[Expression], env: Env) -> Result<Vec<Object>, Object> {
-    let mut result = Vec::with_capacity(expressions.len());
-    for expression in expressions {
-        let Some(evaluated) = expression.eval(env.clone()) else {
-            continue;
+/// Turns the value of a `throw` statement into an error. Strings become the
+/// message, and hashes shaped like a caught error keep their kind and stack
+/// so they can be rethrown.
+fn thrown_error(value: Object) -> Object {
+    let Object::Hash(map) = &value else {
+        let message = match value {
+            Object::String(string) => string,
+            other => other.inspect(),
         };
-        if evaluated.is_error() {
-            return Err(evaluated);
+        return new_error(message);
+    };
+
+    let field = |name: &str| map.get(&Object::String(name.to_string()));
+    let message = match field("message") {
+        Some(Object::String(message)) => message.clone(),
+        Some(other) => other.inspect(),
+        None => value.inspect(),
+    };
+    let kind = match field("kind") {
+        Some(Object::String(kind)) => ErrorKind::from(kind.as_str()),
+        _ => ErrorKind::Error,
+    };
+    let stack = match field("stack") {
+        Some(Object::Array(frames)) => frames.iter().map(|frame| frame.inspect()).collect(),
+        _ => Vec::new(),
+    };
+    Object::Error(Box::new(RuntimeError {
+        kind,
+        message,
+        stack,
+    }))
+}
+
+fn eval_try_expression(
+    body: &Statement,
+    catch: &Option<Box<CatchClause>>,
+    finally: &Option<Box<Statement>>,
+    env: Env,
+) -> Option<Object> {
+    let mut result = body.eval(env.clone());
+
+    if let (Some(Object::Error(error)), Some(catch)) = (&result, catch) {
+        let mut catch_env = Environment::new_enclosed(env.clone());
+        let bound = match &catch.binding {
+            Some(binding) => bind_pattern(binding, error.to_hash(), &mut catch_env),
+            None => Ok(()),
+        };
+        result = match bound {
+            Ok(()) => catch.body.eval(catch_env.into_env()),
+            Err(e) => Some(e),
+        };
+    }
+
+    // A return or error from the finally block wins over whatever the try or
+    // catch block produced.
+    if let Some(finally) = finally {
+        let outcome = finally.eval(env);
+        if matches!(
+            outcome,
+            Some(Object::ReturnValue(_)) | Some(Object::Error(_))
+        ) {
+            return outcome;
         }
-        result.push(evaluated);
     }
-    Ok(result)
+    result
 }
 
-fn apply_function(func: Object, args: &[Object]) -> Option<Object> {
-    Some(match func {
-        Object::Function(params, body, env) => {
-            let extended_env = extend_function_env(env, &params, args);
-            let evaluated = body.eval(extended_env)?;
-            if let Object::ReturnValue(value) = evaluated {
-                *value
-            } else {
-                evaluated
+fn eval_index_expression(left: Object, index: Object) -> Object {
+    match (&left, &index) {
+        (Object::Array(arr), Object::Integer(i)) => match resolve_index(*i, arr.len()) {
+            Some(i) => arr[i].clone(),
+            None => Object::Null,
+        },
+        (Object::String(string), Object::Integer(i)) => {
+            let len = string.chars().count();
+            match resolve_index(*i, len).and_then(|i| string.chars().nth(i)) {
+                Some(character) => Object::String(character.to_string()),
+                None => Object::Null,
             }
         }
-        Object::Builtin(func) => func(args),
-        _ => new_error(format!("not a function: {func}")),
-    })
-}
+        // Only integers that don't fit in an `i64` are big, and no array or
+        // string is that long.
+        (Object::Array(_) | Object::String(_), Object::BigInteger(int)) => {
+            new_error_with_kind(ErrorKind::Key, format!("index out of range: {int}"))
+        }
+        (Object::Module(module), Object::String(name)) => module.get(name).unwrap_or_else(|| {
+            new_error_with_kind(
+                ErrorKind::Key,
+                format!("module {} has no export {name}", module.path.display()),
+            )
+        }),
+        (Object::Struct(instance), Object::String(name)) => {
+            instance.get(name).unwrap_or_else(|e| e)
+        }
+        (Object::Hash(map), _) => {
+            if !index.is_hashable() {
+                return new_error_with_kind(
+                    ErrorKind::Type,
+                    format!("unusable as hash key: {}", &index),
+                );
+            }
 
-fn extend_function_env(func_env: Env, params: &[Expression], args: &[Object]) -> Env {
-    let mut env = Environment::new_enclosed(func_env);
-    for (i, param) in params.iter().enumerate() {
-        if let Expression::Identifier(name) = param {
-            env.set(name.clone(), args[i].clone());
+            map.get(&index).unwrap_or(&Object::Null).clone()
         }
+        (_, _) => new_error_with_kind(
+            ErrorKind::Type,
+            format!("index operator not supported: {left}"),
+        ),
     }
-    env.into_env()
 }
 
-fn eval_identifier(identifier: &Identifier, env: Env) -> Object {
-    if let Some(value) = env.borrow().get(identifier) {
-        return value;
-    }
-    if let Some(builtin) = get_builtin(identifier) {
-        return builtin;
+fn eval_struct_literal(name: &Identifier, fields: &[(Identifier, Expression)], env: Env) -> Object {
+    let ty = match eval_identifier(name, env.clone()) {
+        Object::StructType(ty) => ty,
+        error @ Object::Error(_) => return error,
+        other => {
+            return new_error_with_kind(ErrorKind::Type, format!("{name} is not a struct: {other}"))
+        }
+    };
+
+    let mut values = Vec::with_capacity(fields.len());
+    for (field, expr) in fields {
+        let value = match expr.eval(env.clone()) {
+            Some(value) if value.is_error() => return value,
+            Some(value) => value,
+            None => Object::Null,
+        };
+        values.push((field.clone(), value));
     }
 
-    new_error(format!("identifier not found: {identifier}"))
+    match StructInstance::new(ty, values) {
+        Ok(instance) => Object::Struct(gc::track(instance)),
+        Err(e) => e,
+    }
 }
 
-fn get_builtin(identifier: &Identifier) -> Option<Object> {
-    let function = match identifier.0.as_str() {
-        "len" => len,
-        "first" => first,
-        "last" => last,
-        "rest" => rest,
-        "push" => push,
-        "puts" => puts,
-        _ => return None,
+fn eval_impl_statement(
+    name: &Identifier,
+    methods: &[(Identifier, Expression)],
+    env: Env,
+) -> Result<(), Object> {
+    let ty = match eval_identifier(name, env.clone()) {
+        Object::StructType(ty) => ty,
+        error @ Object::Error(_) => return Err(error),
+        other => {
+            return Err(new_error_with_kind(
+                ErrorKind::Type,
+                format!("{name} is not a struct: {other}"),
+            ))
+        }
     };
 
-    Some(Object::Builtin(function))
+    for (method, function) in methods {
+        let function = function.eval(env.clone()).unwrap_or(Object::Null);
+        if function.is_error() {
+            return Err(function);
+        }
+        ty.define_method(method.clone(), function)?;
+    }
+    Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::{lexer::Lexer, parser::Parser};
-
-    use super::*;
-
-    fn test_eval(input: &str) -> Option<Object> {
-        let mut parser = Parser::new(Lexer::new(input));
-        let program = parser.parse_program();
-        let env = Environment::new().into_env();
-
-        program.eval(env)
+/// Binds the enum type, and each of its variants by name so they can be
+/// built without qualifying them.
+fn eval_enum_statement(
+    name: &Identifier,
+    variants: &[EnumVariant],
+    env: &mut Environment,
+) -> Result<(), Object> {
+    let ty = Rc::new(EnumType {
+        name: name.clone(),
+        variants: variants.to_vec(),
+    });
+    env.set(name.clone(), Object::EnumType(ty.clone()))?;
+    for variant in variants {
+        env.set(variant.name.clone(), ty.variant(&variant.name.0)?)?;
     }
+    Ok(())
+}
 
-    trait TestObject {
-        fn assert_object(&self, object: Option<Object>);
+/// Resolves the callee of `receiver.name(...)`, along with the receiver when
+/// it has to be passed as the first argument. Fields, hash entries and module
+/// exports are called as they are. Otherwise `name` is a method, either from
+/// an `impl` block or from the receiver type's builtin methods.
+fn eval_method(
+    receiver: &Expression,
+    name: &Identifier,
+    env: Env,
+) -> Result<(Object, Option<Object>), Object> {
+    let receiver = receiver.eval(env).unwrap_or(Object::Null);
+    match &receiver {
+        Object::Error(_) => Err(receiver),
+        Object::Module(_) | Object::EnumType(_) => match eval_field_expression(receiver, name) {
+            error @ Object::Error(_) => Err(error),
+            function => Ok((function, None)),
+        },
+        Object::Struct(instance) => {
+            if let Ok(field) = instance.get(&name.0) {
+                return Ok((field, None));
+            }
+            match instance.ty.method(&name.0) {
+                Some(method) => Ok((method, Some(receiver))),
+                None => Err(new_error_with_kind(
+                    ErrorKind::Key,
+                    format!(
+                        "unknown field or method {name} for struct {}",
+                        instance.ty.name
+                    ),
+                )),
+            }
+        }
+        // Methods without a receiver work like associated functions.
+        Object::StructType(ty) => {
+            ty.method(&name.0)
+                .map(|method| (method, None))
+                .ok_or_else(|| {
+                    new_error_with_kind(
+                        ErrorKind::Key,
+                        format!("unknown method {name} for struct {}", ty.name),
+                    )
+                })
+        }
+        Object::Hash(map) if map.contains_key(&Object::String(name.0.clone())) => {
+            Ok((map[&Object::String(name.0.clone())].clone(), None))
+        }
+        _ => match methods::get_method(&receiver, &name.0) {
+            Some(method) => Ok((Object::Builtin(method), Some(receiver))),
+            None => Err(new_error_with_kind(
+                ErrorKind::Type,
+                format!("unknown method {name} for {receiver}"),
+            )),
+        },
     }
+}
 
-    impl TestObject for i64 {
-        fn assert_object(&self, object: Option<Object>) {
-            let Some(Object::Integer(int)) = object else {
-                panic!("object is not an Integer. Got {:?}", object);
-            };
-            assert_eq!(&int, self);
+fn eval_field_expression(left: Object, name: &Identifier) -> Object {
+    match left {
+        Object::Struct(instance) => instance.get(&name.0).unwrap_or_else(|e| e),
+        Object::EnumType(ty) => ty.variant(&name.0).unwrap_or_else(|e| e),
+        Object::Hash(_) | Object::Module(_) => {
+            eval_index_expression(left, Object::String(name.0.clone()))
         }
+        _ => new_error_with_kind(
+            ErrorKind::Type,
+            format!("field access not supported: {left}.{name}"),
+        ),
     }
+}
 
-    impl TestObject for bool {
-        fn assert_object(&self, object: Option<Object>) {
-            let Some(Object::Boolean(bool)) = object else {
-                panic!("object is not a Boolean. Got {:?}", object);
+fn eval_assignment(target: &Expression, value: &Expression, env: Env) -> Object {
+    let (left, name) = match target {
+        Expression::Field(left, name) => (left, name),
+        Expression::Identifier(name) => {
+            let value = match value.eval(env.clone()) {
+                Some(value) if value.is_error() => return value,
+                Some(value) => value,
+                None => Object::Null,
+            };
+            return match env.borrow_mut().assign(name, value.clone()) {
+                Ok(()) => value,
+                Err(e) => e,
             };
-            assert_eq!(&bool, self);
         }
+        _ => {
+            return new_error_with_kind(
+                ErrorKind::Assignment,
+                format!("invalid assignment target: {target}"),
+            )
+        }
+    };
+    let left = match left.eval(env.clone()) {
+        Some(left) if left.is_error() => return left,
+        Some(left) => left,
+        None => Object::Null,
+    };
+    let value = match value.eval(env) {
+        Some(value) if value.is_error() => return value,
+        Some(value) => value,
+        None => Object::Null,
+    };
+
+    match left {
+        Object::Struct(instance) => match instance.set(&name.0, value.clone()) {
+            Ok(()) => value,
+            Err(e) => e,
+        },
+        _ => new_error_with_kind(
+            ErrorKind::Type,
+            format!("cannot assign to field {name} of {left}"),
+        ),
     }
+}
 
-    impl TestObject for Option<Object> {
-        fn assert_object(&self, object: Option<Object>) {
-            assert_eq!(&object, self);
-        }
+fn eval_slice_bound(bound: &Option<Box<Expression>>, env: Env) -> Result<Option<i64>, Object> {
+    let Some(bound) = bound else {
+        return Ok(None);
+    };
+    match bound.eval(env) {
+        Some(Object::Integer(int)) => Ok(Some(int)),
+        Some(Object::BigInteger(int)) => Err(new_error_with_kind(
+            ErrorKind::Key,
+            format!("slice index out of range: {int}"),
+        )),
+        Some(error @ Object::Error(_)) => Err(error),
+        other => Err(new_error_with_kind(
+            ErrorKind::Type,
+            format!(
+                "slice index must be INTEGER, got {}",
+                other.unwrap_or(Object::Null)
+            ),
+        )),
     }
+}
 
-    impl TestObject for &str {
-        fn assert_object(&self, object: Option<Object>) {
-            match object {
-                Some(Object::Error(message)) => assert_eq!(&message, self),
-                Some(Object::String(string)) => assert_eq!(&string, self),
-                _ => panic!("Expected Error or String Object. Got {:?}", object),
-            }
+fn eval_slice_expression(left: Object, start: Option<i64>, end: Option<i64>) -> Object {
+    let range = |len: usize| {
+        let start = start.map_or(0, |s| resolve_slice_bound(s, len));
+        let end = end.map_or(len, |e| resolve_slice_bound(e, len));
+        start..end.max(start)
+    };
+
+    match left {
+        Object::Array(arr) => Object::Array(arr[range(arr.len())].to_vec()),
+        Object::String(string) => {
+            let range = range(string.chars().count());
+            let sliced = string.chars().skip(range.start).take(range.len()).collect();
+            Object::String(sliced)
         }
+        _ => new_error_with_kind(
+            ErrorKind::Type,
+            format!("slice operator not supported: {left}"),
+        ),
     }
+}
 
-    #[test]
-    fn test_eval_integer_expressions() {
+fn eval_expressions(expressions: &[Expression], env: Env) -> Result<Vec<Object>, Object> {
+    let mut result = Vec::with_capacity(expressions.len());
+    for expression in expressions {
+        let Some(evaluated) = expression.eval(env.clone()) else {
+            continue;
+        };
+        if evaluated.is_error() {
+            return Err(evaluated);
+        }
+        result.push(evaluated);
+    }
+    Ok(result)
+}
+
+/// What evaluating part of a function body in tail position gives: its
+/// value, or the call whose result the function returns, which is left for
+/// `apply_function` to make once the body is done.
+enum Tail {
+    Value(Option<Object>),
+    /// The function, its arguments and how the call named it, for the stack
+    /// of any error it ends in.
+    Call(Object, Vec<Object>, String),
+}
+
+/// Evaluates a call. When `tail` is set and the callee is a function defined
+/// in Monkey, the call is handed back instead of made.
+fn eval_call(function: &Expression, arguments: &[Expression], env: Env, tail: bool) -> Tail {
+    if quote_unquote::is_call_to(function, "quote") {
+        return Tail::Value(Some(quote_unquote::quote(arguments, env)));
+    }
+    let (callee, receiver) = match function {
+        Expression::Field(receiver, name) => match eval_method(receiver, name, env.clone()) {
+            Ok(method) => method,
+            Err(e) => return Tail::Value(Some(e)),
+        },
+        _ => match function.eval(env.clone()) {
+            Some(callee) => (callee, None),
+            None => return Tail::Value(None),
+        },
+    };
+    if callee.is_error() {
+        return Tail::Value(Some(callee));
+    }
+    let result = match eval_expressions(arguments, env) {
+        Err(e) => e,
+        Ok(mut args) => {
+            if let Some(receiver) = receiver {
+                args.insert(0, receiver);
+            }
+            if tail && matches!(callee, Object::Function(_, _, _)) {
+                return Tail::Call(callee, args, function.to_string());
+            }
+            match apply_function(callee, &args) {
+                Some(result) => result,
+                None => return Tail::Value(None),
+            }
+        }
+    };
+    Tail::Value(Some(with_frame(result, function.to_string())))
+}
+
+/// Adds the name a function was called by to the stack of an error it
+/// returned.
+fn with_frame(result: Object, name: String) -> Object {
+    match result {
+        Object::Error(mut error) => {
+            error.stack.push(name);
+            Object::Error(error)
+        }
+        result => result,
+    }
+}
+
+/// Evaluates a statement of a function body like `Statement::eval` does,
+/// except that calls whose result the function would return are handed back
+/// instead of made. That covers `return f(...)` and, when `tail` is set, a
+/// call that's the value of the statement, looking through blocks and the
+/// branches of `if` and `match` but not `try`, which has to see the result.
+fn eval_tail(statement: &Statement, env: Env, tail: bool) -> Tail {
+    match statement {
+        Statement::Block(statements) => {
+            let block_env = Environment::new_enclosed(env).into_env();
+            let mut result = None;
+            for (i, statement) in statements.iter().enumerate() {
+                let is_last = i + 1 == statements.len();
+                match eval_tail(statement, block_env.clone(), tail && is_last) {
+                    call @ Tail::Call(_, _, _) => return call,
+                    Tail::Value(value) => result = value,
+                }
+                if matches!(
+                    result,
+                    Some(Object::ReturnValue(_)) | Some(Object::Error(_))
+                ) {
+                    break;
+                }
+            }
+            Tail::Value(result)
+        }
+        Statement::Return(value) => match eval_tail_expression(value, env, true) {
+            Tail::Value(Some(value)) if !value.is_error() => {
+                Tail::Value(Some(Object::ReturnValue(Box::new(value))))
+            }
+            tail => tail,
+        },
+        Statement::Expression(expression) => eval_tail_expression(expression, env, tail),
+        statement => Tail::Value(statement.eval(env)),
+    }
+}
+
+fn eval_tail_expression(expression: &Expression, env: Env, tail: bool) -> Tail {
+    match expression {
+        Expression::If(condition, consequence, alternative) => {
+            let condition = condition.eval(env.clone()).unwrap_or(Object::Null);
+            if condition.is_error() {
+                return Tail::Value(Some(condition));
+            }
+            if condition.is_truthy() {
+                return eval_tail(consequence, env, tail);
+            }
+            match alternative {
+                Some(alternative) => eval_tail(alternative, env, tail),
+                None => Tail::Value(None),
+            }
+        }
+        Expression::Match(value, arms) => match select_arm(value, arms, env) {
+            Ok(Some((body, arm_env))) => eval_tail(body, arm_env, tail),
+            Ok(None) => Tail::Value(Some(Object::Null)),
+            Err(e) => Tail::Value(Some(e)),
+        },
+        Expression::Call(function, arguments) => eval_call(function, arguments, env, tail),
+        expression => Tail::Value(expression.eval(env)),
+    }
+}
+
+fn apply_function(func: Object, args: &[Object]) -> Option<Object> {
+    Some(match func {
+        Object::Function(params, body, env) => {
+            return call_function(params, body, env, args.to_vec());
+        }
+        Object::GeneratorFunction(params, body, env) => {
+            match extend_function_env(env, &params, args) {
+                Ok(env) => Object::Generator(gc::track(Generator::new(&body, env))),
+                Err(e) => e,
+            }
+        }
+        Object::Builtin(func) => func(args),
+        Object::Constructor(ty, tag) => enums::construct(&ty, tag, args),
+        Object::Composition(first, second) => {
+            let value = apply_function(*first, args)?;
+            if value.is_error() {
+                return Some(value);
+            }
+            apply_function(*second, &[value])?
+        }
+        _ => new_error_with_kind(ErrorKind::Type, format!("not a function: {func}")),
+    })
+}
+
+/// Calls a function defined in Monkey. Calls in tail position come back out
+/// of its body and get made here in turn, so tail recursion, mutual or not,
+/// runs without growing the Rust stack however deep it goes.
+fn call_function(
+    mut params: Rc<[Pattern]>,
+    mut body: Rc<Statement>,
+    mut env: Env,
+    mut args: Vec<Object>,
+) -> Option<Object> {
+    // A tail call replaces the frame of the function making it, so only the
+    // last function called that way shows up in the stack of an error.
+    let mut tail_callee = None;
+    loop {
+        gc::collect_if_due();
+        let result = match extend_function_env(env, &params, &args) {
+            Ok(extended_env) => eval_tail(&body, extended_env, true),
+            Err(e) => Tail::Value(Some(e)),
+        };
+        let value = match result {
+            Tail::Call(Object::Function(next_params, next_body, next_env), next_args, name) => {
+                (params, body, env, args) = (next_params, next_body, next_env, next_args);
+                tail_callee = Some(name);
+                continue;
+            }
+            Tail::Call(_, _, _) => unreachable!("only calls to functions are deferred"),
+            Tail::Value(value) => value?,
+        };
+        let value = match value {
+            Object::ReturnValue(value) => *value,
+            value => value,
+        };
+        return Some(match tail_callee {
+            Some(name) => with_frame(value, name),
+            None => value,
+        });
+    }
+}
+
+fn extend_function_env(func_env: Env, params: &[Pattern], args: &[Object]) -> Result<Env, Object> {
+    if args.len() < params.len() {
+        return Err(new_error_with_kind(
+            ErrorKind::Argument,
+            format!(
+                "wrong number of arguments. got={}, want={}",
+                args.len(),
+                params.len()
+            ),
+        ));
+    }
+
+    let mut env = Environment::new_enclosed(func_env);
+    for (param, arg) in params.iter().zip(args) {
+        bind_pattern(param, arg.clone(), &mut env)?;
+    }
+    Ok(env.into_env())
+}
+
+/// Binds the parts of `value` picked out by `pattern`, failing if the value
+/// doesn't have the shape the pattern expects.
+fn bind_pattern(pattern: &Pattern, value: Object, env: &mut Environment) -> Result<(), Object> {
+    match pattern {
+        Pattern::Wildcard => {}
+        Pattern::Identifier(ident) => env.set(ident.clone(), value)?,
+        Pattern::Literal(literal) => {
+            // Compares the way `==` does, so `2` matches `2.0`.
+            if eval_literal(literal) != value {
+                return Err(new_error_with_kind(
+                    ErrorKind::Match,
+                    format!("{} does not match pattern {pattern}", value.inspect()),
+                ));
+            }
+        }
+        Pattern::Array(patterns, rest) => {
+            let Object::Array(mut elements) = value else {
+                return Err(new_error_with_kind(
+                    ErrorKind::Match,
+                    format!("cannot destructure {value} as ARRAY"),
+                ));
+            };
+            let length_matches = match rest {
+                Some(_) => elements.len() >= patterns.len(),
+                None => elements.len() == patterns.len(),
+            };
+            if !length_matches {
+                return Err(new_error_with_kind(
+                    ErrorKind::Match,
+                    format!(
+                        "cannot destructure ARRAY of length {} with pattern {pattern}",
+                        elements.len()
+                    ),
+                ));
+            }
+
+            let remaining = elements.split_off(patterns.len());
+            for (pattern, element) in patterns.iter().zip(elements) {
+                bind_pattern(pattern, element, env)?;
+            }
+            if let Some(rest) = rest {
+                env.set(rest.clone(), Object::Array(remaining))?;
+            }
+        }
+        Pattern::Hash(entries) => {
+            let Object::Hash(map) = value else {
+                return Err(new_error_with_kind(
+                    ErrorKind::Match,
+                    format!("cannot destructure {value} as HASH"),
+                ));
+            };
+            for (key, pattern) in entries {
+                let key = eval_literal(key);
+                let Some(value) = map.get(&key) else {
+                    return Err(new_error_with_kind(
+                        ErrorKind::Match,
+                        format!("key not found: {}", key.inspect()),
+                    ));
+                };
+                bind_pattern(pattern, value.clone(), env)?;
+            }
+        }
+        Pattern::Variant(enum_name, name, payload) => {
+            let Object::Variant(variant) = &value else {
+                return Err(new_error_with_kind(
+                    ErrorKind::Match,
+                    format!("{} does not match pattern {pattern}", value.inspect()),
+                ));
+            };
+            let matches = variant.name() == name
+                && enum_name.as_ref().is_none_or(|e| e == &variant.ty.name)
+                && payload
+                    .as_ref()
+                    .is_none_or(|p| p.len() == variant.payload.len());
+            if !matches {
+                return Err(new_error_with_kind(
+                    ErrorKind::Match,
+                    format!("{} does not match pattern {pattern}", value.inspect()),
+                ));
+            }
+            for (pattern, value) in payload.iter().flatten().zip(&variant.payload) {
+                bind_pattern(pattern, value.clone(), env)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn eval_identifier(identifier: &Identifier, env: Env) -> Object {
+    if let Some(value) = env.borrow().get(identifier) {
+        return value;
+    }
+    if let Some(builtin) = get_builtin(identifier) {
+        return builtin;
+    }
+
+    new_error_with_kind(
+        ErrorKind::Name,
+        format!("identifier not found: {identifier}"),
+    )
+}
+
+fn get_builtin(identifier: &Identifier) -> Option<Object> {
+    let function = match identifier.0.as_str() {
+        "len" => len,
+        "first" => first,
+        "last" => last,
+        "rest" => rest,
+        "push" => push,
+        "puts" => puts,
+        "int" => int,
+        "float" => float,
+        "next" => next,
+        "done" => done,
+        "collect" => collect,
+        "take" => take,
+        _ => return None,
+    };
+
+    Some(Object::Builtin(function))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lexer::Lexer, parser::Parser};
+
+    use super::*;
+
+    fn test_eval(input: &str) -> Option<Object> {
+        let mut parser = Parser::new(Lexer::new(input));
+        let program = parser.parse_program();
+        let env = Environment::new().into_env();
+
+        program.eval(env)
+    }
+
+    trait TestObject {
+        fn assert_object(&self, object: Option<Object>);
+    }
+
+    impl TestObject for i64 {
+        fn assert_object(&self, object: Option<Object>) {
+            let Some(Object::Integer(int)) = object else {
+                panic!("object is not an Integer. Got {:?}", object);
+            };
+            assert_eq!(&int, self);
+        }
+    }
+
+    impl TestObject for f64 {
+        fn assert_object(&self, object: Option<Object>) {
+            let Some(Object::Float(float)) = object else {
+                panic!("object is not a Float. Got {:?}", object);
+            };
+            assert_eq!(&float, self);
+        }
+    }
+
+    impl TestObject for bool {
+        fn assert_object(&self, object: Option<Object>) {
+            let Some(Object::Boolean(bool)) = object else {
+                panic!("object is not a Boolean. Got {:?}", object);
+            };
+            assert_eq!(&bool, self);
+        }
+    }
+
+    impl TestObject for Option<Object> {
+        fn assert_object(&self, object: Option<Object>) {
+            assert_eq!(&object, self);
+        }
+    }
+
+    impl TestObject for &str {
+        fn assert_object(&self, object: Option<Object>) {
+            match object {
+                Some(Object::Error(error)) => assert_eq!(&error.message, self),
+                Some(Object::String(string)) => assert_eq!(&string, self),
+                _ => panic!("Expected Error or String Object. Got {:?}", object),
+            }
+        }
+    }
+
+    #[test]
+    fn test_eval_integer_expressions() {
         let inputs: Vec<(&str, i64)> = vec![
             ("5", 5),
             ("10", 10),
@@ -414,6 +1382,97 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_eval_big_integer_expressions() {
+        let inputs: Vec<(&str, &str)> = vec![
+            ("9223372036854775807 + 1", "9223372036854775808"),
+            ("-9223372036854775807 - 2", "-9223372036854775809"),
+            ("-(-9223372036854775807 - 1)", "9223372036854775808"),
+            ("int(9223372036854775808.0)", "9223372036854775808"),
+            ("int(-100000000000000000000.5)", "-100000000000000000000"),
+            ("int(9223372036854774784.0)", "9223372036854774784"),
+            ("4294967296 * 4294967296", "18446744073709551616"),
+            ("100000000000000000000 / 3", "33333333333333333333"),
+            ("100000000000000000000 % 7", "2"),
+            ("-100000000000000000000 % 7", "-2"),
+            ("100000000000000000000 - 99999999999999999999", "1"),
+            ("100000000000000000000 > 99999999999999999999", "true"),
+            ("100000000000000000000 == 100000000000000000000", "true"),
+            ("100000000000000000000 < 5", "false"),
+            ("100000000000000000000 * 0.5", "5e19"),
+            ("100000000000000000000 / 0", "ERROR: division by zero"),
+            (
+                r#"{100000000000000000000: "big"}[99999999999999999999 + 1]"#,
+                "big",
+            ),
+            (
+                r#"
+let factorial = fn(n) { if (n < 2) { 1 } else { n * factorial(n - 1) } };
+factorial(30)"#,
+                "265252859812191058636308480000000",
+            ),
+        ];
+
+        for input in inputs {
+            let evaluated = test_eval(input.0).unwrap();
+            assert_eq!(evaluated.inspect(), input.1, "{}", input.0);
+        }
+    }
+
+    #[test]
+    fn test_big_integers_demote() {
+        let inputs: Vec<(&str, i64)> = vec![
+            ("(9223372036854775807 + 1) - 1", i64::MAX),
+            ("100000000000000000000 / 100000000000000000000", 1),
+            ("int(\"100000000000000000000\") / 10000000000", 10000000000),
+        ];
+
+        for input in inputs {
+            input.1.assert_object(test_eval(input.0));
+        }
+    }
+
+    #[test]
+    fn test_eval_float_expressions() {
+        let inputs: Vec<(&str, &dyn TestObject)> = vec![
+            ("3.5", &3.5),
+            ("-2.5", &-2.5),
+            ("1e-3", &0.001),
+            ("1.5 + 1.5", &3.0),
+            ("1 + 0.5", &1.5),
+            ("0.5 * 4", &2.0),
+            ("7 / 2.0", &3.5),
+            ("7 / 2", &3),
+            ("7 % 3", &1),
+            ("7.5 % 2", &1.5),
+            ("1.5 < 2", &true),
+            ("2 == 2.0", &true),
+            ("2.5 != 2.5", &false),
+            ("1.0 / 0", &f64::INFINITY),
+            ("1.5 + true", &"type mismatch: FLOAT + BOOLEAN"),
+        ];
+
+        for input in inputs {
+            let evaluated = test_eval(input.0);
+            input.1.assert_object(evaluated);
+        }
+    }
+
+    #[test]
+    fn test_float_inspect() {
+        let inputs: Vec<(&str, &str)> = vec![
+            ("3.0", "3.0"),
+            ("0.25", "0.25"),
+            ("1 / 3.0", "0.3333333333333333"),
+            ("[1.5, 2]", "[1.5, 2]"),
+        ];
+
+        for input in inputs {
+            let evaluated = test_eval(input.0).unwrap();
+            assert_eq!(evaluated.inspect(), input.1);
+        }
+    }
+
     #[test]
     fn test_eval_boolean_expression() {
         let inputs: Vec<(&str, bool)> = vec![
@@ -479,6 +1538,73 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_else_if_expressions() {
+        let classify = "let classify = fn(n) { if (n < 0) { \"negative\" } else if (n == 0) { \"zero\" } else if (n < 10) { \"small\" } else { \"large\" } };";
+        let inputs: Vec<(&str, &dyn TestObject)> = vec![
+            ("classify(-5)", &"negative"),
+            ("classify(0)", &"zero"),
+            ("classify(3)", &"small"),
+            ("classify(30)", &"large"),
+            ("if (false) { 1 } else if (false) { 2 }", &None),
+            (
+                "if (false) { 1 } else if (x) { 2 }",
+                &"identifier not found: x",
+            ),
+        ];
+
+        for input in inputs {
+            let evaluated = test_eval(&format!("{classify} {}", input.0));
+            input.1.assert_object(evaluated);
+        }
+    }
+
+    #[test]
+    fn test_block_scoping() {
+        let inputs: Vec<(&str, &dyn TestObject)> = vec![
+            // Bindings made inside a block don't leak out of it.
+            ("if (true) { let a = 1; }; a", &"identifier not found: a"),
+            ("if (false) { 1 } else { let b = 2; }; b", &"identifier not found: b"),
+            (
+                "match (1) { 1 => { let c = 3; c } }; c",
+                &"identifier not found: c",
+            ),
+            // Shadowing in a block leaves the outer binding untouched.
+            ("let x = 1; if (true) { let x = 2; x }", &2),
+            ("let x = 1; if (true) { let x = 2; }; x", &1),
+            ("let x = 1; if (true) { let x = x + 10; x }", &11),
+            (
+                "let x = 1; if (true) { let x = 2; if (true) { let x = 3; }; x }",
+                &2,
+            ),
+            // Outer bindings stay visible from nested blocks.
+            ("let x = 5; if (true) { if (true) { x * 2 } }", &10),
+            // Function parameters are scoped to their call.
+            ("let x = 1; let f = fn(x) { x }; f(9); x", &1),
+            ("let f = fn() { let inner = 1; inner }; f(); inner", &"identifier not found: inner"),
+            // Closures capture the block they were created in.
+            (
+                "let make = fn() { if (true) { let hidden = 42; fn() { hidden } } }; make()()",
+                &42,
+            ),
+            (
+                "let f = if (true) { let n = 7; fn(m) { n + m } }; let n = 100; f(1)",
+                &8,
+            ),
+            (
+                "let counters = fn(start) { if (true) { let start = start * 10; fn() { start } } }; let a = counters(1); let b = counters(2); a() + b()",
+                &30,
+            ),
+            // Returning from a nested block still unwinds the whole function.
+            ("let f = fn() { if (true) { let y = 1; return y; }; 2 }; f()", &1),
+        ];
+
+        for input in inputs {
+            let evaluated = test_eval(input.0);
+            input.1.assert_object(evaluated);
+        }
+    }
+
     #[test]
     fn test_return_statements() {
         let inputs: Vec<(&str, i64)> = vec![
@@ -512,6 +1638,8 @@ mod tests {
                 "unknown operator: BOOLEAN + BOOLEAN",
             ),
             ("foobar", "identifier not found: foobar"),
+            ("5 / 0", "division by zero"),
+            ("5 % 0", "division by zero"),
             (r#""Hello" - "World""#, "unknown operator: STRING - STRING"),
             (
                 r#"{"name": "Monkey"}[fn(x) { x }];"#,
@@ -521,27 +1649,310 @@ mod tests {
 
         for input in inputs {
             let evaluated = test_eval(input.0);
-            let Some(Object::Error(error_message)) = evaluated else {
+            let Some(Object::Error(error)) = evaluated else {
                 panic!("Expected Error Object. Got {:?} from {}", evaluated, input.0);
             };
 
-            assert_eq!(&error_message, input.1);
+            assert_eq!(error.message, input.1);
         }
     }
 
     #[test]
-    fn test_let_statements() {
-        let inputs: Vec<(&str, i64)> = vec![
-            ("let a = 5; a;", 5),
-            ("let a = 5 * 5; a;", 25),
-            ("let a = 5; let b = a; b;", 5),
-            ("let a = 5; let b = a; let c = a + b + 5; c;", 15),
-        ];
-
-        for input in inputs {
-            let evaluated = test_eval(input.0);
-            input.1.assert_object(evaluated);
-        }
+    fn test_try_catch_finally() {
+        let inputs: Vec<(&str, &dyn TestObject)> = vec![
+            ("try { 1 } catch (e) { 2 }", &1),
+            (
+                r#"try { throw "boom"; 1 } catch (e) { e["message"] }"#,
+                &"boom",
+            ),
+            (r#"try { throw "boom" } catch (e) { e["kind"] }"#, &"Error"),
+            ("try { throw 42 } catch (e) { e[\"message\"] }", &"42"),
+            (
+                r#"try { throw {"message": "bad input", "kind": "InputError"} } catch (e) { e["kind"] + ": " + e["message"] }"#,
+                &"InputError: bad input",
+            ),
+            (r#"throw "uncaught"; 1"#, &"uncaught"),
+            (r#"try { throw "x" } catch { "ignored" }"#, &"ignored"),
+            (r#"try { throw "x" } catch ({"message": m}) { m }"#, &"x"),
+            // Builtin failures are caught the same way, with their own kinds.
+            (r#"try { 1 + true } catch (e) { e["kind"] }"#, &"TypeError"),
+            (
+                r#"try { 1 + true } catch (e) { e["message"] }"#,
+                &"type mismatch: INTEGER + BOOLEAN",
+            ),
+            (r#"try { missing } catch (e) { e["kind"] }"#, &"NameError"),
+            (
+                r#"try { 1 / 0 } catch (e) { e["kind"] }"#,
+                &"ZeroDivisionError",
+            ),
+            (
+                r#"try { len(1, 2) } catch (e) { e["kind"] }"#,
+                &"ArgumentError",
+            ),
+            (
+                r#"try { let [a] = 1; } catch (e) { e["kind"] }"#,
+                &"MatchError",
+            ),
+            // Errors propagate out of nested calls, and catch blocks can throw.
+            (
+                r#"let f = fn() { throw "deep" }; let g = fn() { f() + 1 }; try { g() } catch (e) { e["message"] }"#,
+                &"deep",
+            ),
+            (
+                r#"try { try { throw "inner" } catch (e) { throw "outer: " + e["message"] } } catch (e) { e["message"] }"#,
+                &"outer: inner",
+            ),
+            (
+                r#"try { try { 1 / 0 } catch (e) { throw e } } catch (e) { e["kind"] }"#,
+                &"ZeroDivisionError",
+            ),
+            // Bindings from the try block don't leak into the catch block.
+            (
+                r#"try { let a = 1; throw "x" } catch (e) { a }"#,
+                &"identifier not found: a",
+            ),
+        ];
+
+        for input in inputs {
+            let evaluated = test_eval(input.0);
+            input.1.assert_object(evaluated);
+        }
+    }
+
+    #[test]
+    fn test_finally_blocks() {
+        let inputs: Vec<(&str, &dyn TestObject)> = vec![
+            ("try { 1 } finally { 2 }", &1),
+            (r#"try { throw "kept" } finally { 2 }"#, &"kept"),
+            (r#"try { throw "x" } catch (e) { 3 } finally { 4 }"#, &3),
+            (
+                r#"try { 1 } finally { throw "from finally" }"#,
+                &"from finally",
+            ),
+            ("let f = fn() { try { return 1; } finally { 2 } }; f()", &1),
+            (
+                "let f = fn() { try { return 1; } finally { return 2; } }; f()",
+                &2,
+            ),
+            (
+                r#"let f = fn() { try { throw "x" } finally { return 5; } }; f()"#,
+                &5,
+            ),
+        ];
+
+        for input in inputs {
+            let evaluated = test_eval(input.0);
+            input.1.assert_object(evaluated);
+        }
+    }
+
+    #[test]
+    fn test_error_stack() {
+        let input = r#"
+let inner = fn() { 1 / 0 };
+let outer = fn() { inner() };
+try { outer() } catch (e) { e["stack"] }
+"#;
+        let Some(Object::Array(frames)) = test_eval(input) else {
+            panic!("expected an array of frames");
+        };
+        let frames: Vec<_> = frames.iter().map(Object::inspect).collect();
+        assert_eq!(frames, vec!["inner", "outer"]);
+
+        let Some(Object::Error(error)) = test_eval("let f = fn() { missing }; f()") else {
+            panic!("expected an error");
+        };
+        assert_eq!(error.kind, ErrorKind::Name);
+        assert_eq!(error.stack, vec!["f".to_string()]);
+    }
+
+    #[test]
+    fn test_const_statements() {
+        let inputs: Vec<(&str, &dyn TestObject)> = vec![
+            ("const a = 5; a", &5),
+            ("const a = 5; const b = a * 2; b", &10),
+            ("const a = 1; if (true) { const a = 2; a }", &2),
+            ("const a = 1; if (true) { const a = 2; }; a", &1),
+            ("const f = fn(x) { x + 1 }; f(1)", &2),
+            ("const a = missing;", &"identifier not found: missing"),
+        ];
+
+        for input in inputs {
+            input.1.assert_object(test_eval(input.0));
+        }
+    }
+
+    #[test]
+    fn test_const_redeclaration_at_runtime() {
+        // Separate parses, like REPL lines, can only be checked at runtime.
+        let env = Environment::new().into_env();
+        let run = |input: &str| {
+            Parser::new(Lexer::new(input))
+                .parse_program()
+                .eval(env.clone())
+        };
+
+        run("let x = 1;\nconst limit = 10;");
+        let inputs = vec![
+            "let limit = 5;",
+            "const limit = 5;",
+            "let [a, limit] = [1, 2];",
+        ];
+        for input in inputs {
+            let Some(Object::Error(error)) = run(input) else {
+                panic!("expected an error from {input}");
+            };
+            assert_eq!(error.kind, ErrorKind::Assignment);
+            assert_eq!(
+                error.message,
+                "cannot redeclare constant limit, declared at line 2, column 7"
+            );
+        }
+        3.assert_object(run("const x = 3; x"));
+        10.assert_object(run("limit"));
+    }
+
+    #[test]
+    fn test_let_statements() {
+        let inputs: Vec<(&str, i64)> = vec![
+            ("let a = 5; a;", 5),
+            ("let a = 5 * 5; a;", 25),
+            ("let a = 5; let b = a; b;", 5),
+            ("let a = 5; let b = a; let c = a + b + 5; c;", 15),
+        ];
+
+        for input in inputs {
+            let evaluated = test_eval(input.0);
+            input.1.assert_object(evaluated);
+        }
+    }
+
+    #[test]
+    fn test_destructuring_let_statements() {
+        let inputs: Vec<(&str, &dyn TestObject)> = vec![
+            ("let [a, b] = [1, 2]; a + b;", &3),
+            ("let [a, ...rest] = [1, 2, 3]; len(rest) * 10 + a;", &21),
+            ("let [a, ...rest] = [1]; len(rest);", &0),
+            ("let [[a, b], [c]] = [[1, 2], [3]]; a + b + c;", &6),
+            (r#"let {name, age} = {"name": "x", "age": 2}; age;"#, &2),
+            (r#"let {"n": [first, ...rest]} = {"n": [4, 5]}; first;"#, &4),
+            (
+                "let {1: one, true: yes} = {1: 10, true: 20}; one + yes;",
+                &30,
+            ),
+            (
+                "let [a, b] = [1, 2, 3];",
+                &"cannot destructure ARRAY of length 3 with pattern [a, b]",
+            ),
+            (
+                "let [a, b, ...c] = [1];",
+                &"cannot destructure ARRAY of length 1 with pattern [a, b, ...c]",
+            ),
+            ("let [a] = 5;", &"cannot destructure INTEGER as ARRAY"),
+            ("let {a} = [1];", &"cannot destructure ARRAY as HASH"),
+            (r#"let {name} = {"age": 1};"#, &"key not found: name"),
+        ];
+
+        for input in inputs {
+            input.1.assert_object(test_eval(input.0));
+        }
+    }
+
+    #[test]
+    fn test_destructuring_function_params() {
+        let inputs: Vec<(&str, &dyn TestObject)> = vec![
+            ("let add = fn([a, b]) { a + b }; add([1, 2]);", &3),
+            (
+                r#"let area = fn({w, h}) { w * h }; area({"w": 3, "h": 4});"#,
+                &12,
+            ),
+            (
+                "let f = fn(x, [y, ...z]) { x + y + len(z) }; f(1, [2, 3, 4]);",
+                &5,
+            ),
+            (
+                "let f = fn([a, b]) { a }; f(1);",
+                &"cannot destructure INTEGER as ARRAY",
+            ),
+            (
+                "let f = fn(a, b) { a }; f(1);",
+                &"wrong number of arguments. got=1, want=2",
+            ),
+        ];
+
+        for input in inputs {
+            input.1.assert_object(test_eval(input.0));
+        }
+    }
+
+    #[test]
+    fn test_match_expressions() {
+        let describe = r#"
+let describe = fn(value) {
+    match (value) {
+        0 => "zero",
+        -1 => "minus one",
+        "hi" => "greeting",
+        true => "yes",
+        null => "nothing",
+        [] => "empty",
+        [x] => "one: ${x}",
+        [x, y] => "pair: ${x + y}",
+        [first, ...rest] => "many: ${first} then ${len(rest)}",
+        {"type": "add", "n": n} if n > 10 => "big add ${n}",
+        {"type": "add", "n": n} => "add ${n}",
+        {"type": _} => "other type",
+        n if n > 100 => { let doubled = n * 2; "large ${doubled}" },
+        _ => "unknown"
+    }
+};
+"#;
+        let inputs: Vec<(&str, &str)> = vec![
+            ("0", "zero"),
+            ("-1", "minus one"),
+            (r#""hi""#, "greeting"),
+            ("true", "yes"),
+            ("null", "nothing"),
+            ("[]", "empty"),
+            ("[7]", "one: 7"),
+            ("[1, 2]", "pair: 3"),
+            ("[1, 2, 3, 4]", "many: 1 then 3"),
+            (r#"{"type": "add", "n": 20}"#, "big add 20"),
+            (r#"{"type": "add", "n": 2, "extra": 1}"#, "add 2"),
+            (r#"{"type": "sub"}"#, "other type"),
+            ("101", "large 202"),
+            ("5", "unknown"),
+        ];
+
+        for input in inputs {
+            let program = format!("{describe} describe({});", input.0);
+            input.1.assert_object(test_eval(&program));
+        }
+    }
+
+    #[test]
+    fn test_match_scoping_and_results() {
+        let inputs: Vec<(&str, &dyn TestObject)> = vec![
+            ("match (3) { 1 => 10 }", &Some(Object::Null)),
+            ("let x = 1; match ([5]) { [x] => x }; x", &1),
+            ("match ([1, 2]) { [a, 3] => a, [b, 2] => b * 10 }", &10),
+            (
+                "let f = fn() { match (1) { 1 => { return 5; } }; 6 }; f()",
+                &5,
+            ),
+            (
+                "match (missing) { _ => 1 }",
+                &"identifier not found: missing",
+            ),
+            ("match (1) { x if y => x }", &"identifier not found: y"),
+            ("let 5 = 5; 1", &1),
+            ("let 5 = 4;", &"4 does not match pattern 5"),
+            ("let [_, b] = [1, 2]; b", &2),
+        ];
+
+        for input in inputs {
+            input.1.assert_object(test_eval(input.0));
+        }
     }
 
     #[test]
@@ -593,6 +2004,85 @@ addTwo(2);"#,
         input.1.assert_object(test_eval(input.0));
     }
 
+    #[test]
+    fn test_assignment() {
+        let inputs: Vec<(&str, &dyn TestObject)> = vec![
+            ("let x = 1; x = x + 1; x", &2),
+            ("let x = 1; let y = x = 5; x * 10 + y", &55),
+            ("let a = 1; let b = 2; a = b = 3; a + b", &6),
+            // Assignment changes the innermost variable with that name.
+            ("let x = 1; if (true) { let x = 2; x = 3; } x", &1),
+            ("let x = 1; if (true) { x = 3; } x", &3),
+            ("let f = fn(x) { x = x * 2; x }; let x = 5; f(x) + x", &15),
+            ("y = 1", &"cannot assign to undeclared variable y"),
+            ("len = 1", &"cannot assign to undeclared variable len"),
+            (
+                "const c = 1; let f = fn() { c = 2 }; f()",
+                &"cannot assign to constant c, declared at line 1, column 7",
+            ),
+            // A new binding in an inner scope isn't the constant.
+            ("const c = 1; let f = fn() { let c = 1; c = 2 }; f()", &2),
+            (
+                "let x = 1; x = 1 + true; x",
+                &"type mismatch: INTEGER + BOOLEAN",
+            ),
+        ];
+
+        for (input, expected) in inputs {
+            let evaluated = test_eval(input);
+            expected.assert_object(evaluated);
+        }
+    }
+
+    #[test]
+    fn test_mutable_closures() {
+        let inputs: Vec<(&str, &dyn TestObject)> = vec![
+            (
+                "let count = 0; let counter = fn() { count = count + 1 }; counter(); counter(); count",
+                &2,
+            ),
+            (
+                "let make = fn() { let n = 0; || { n = n + 1; n } }; let a = make(); let b = make(); a(); a(); b(); a() * 10 + b()",
+                &32,
+            ),
+            // Closures over the same scope see each other's updates.
+            (
+                r#"
+let account = fn(balance) {
+    {
+        "deposit": fn(amount) { balance = balance + amount },
+        "withdraw": fn(amount) { balance = balance - amount },
+        "balance": fn() { balance },
+    }
+};
+let a = account(100);
+a.deposit(50);
+a.withdraw(30);
+a.balance()"#,
+                &120,
+            ),
+            // Closures capture the variable, not its value when they were made.
+            ("let x = 1; let get = fn() { x }; x = 2; get()", &2),
+            (
+                "let fs = []; let i = 0; let add = fn() { fs = push(fs, fn() { i }); i = i + 1 }; add(); add(); fs[0]() + fs[1]()",
+                &4,
+            ),
+            (
+                "let total = 0; for (x in [1, 2, 3]) { total = total + x; } total",
+                &6,
+            ),
+            (
+                "let seen = 0; let g = fn*() { seen = seen + 1; yield seen; seen = seen + 1; yield seen; }(); next(g); next(g); seen",
+                &2,
+            ),
+        ];
+
+        for (input, expected) in inputs {
+            let evaluated = test_eval(input);
+            expected.assert_object(evaluated);
+        }
+    }
+
     #[test]
     fn parse_string_literal() {
         let input = "\"Hello World!\"";
@@ -617,17 +2107,103 @@ addTwo(2);"#,
         assert_eq!(string, "Hello World!")
     }
 
+    #[test]
+    fn test_string_interpolation() {
+        let inputs: Vec<(&str, &str)> = vec![
+            (r#"let count = 21; "total: ${count * 2}""#, "total: 42"),
+            (
+                r#""${1.5} ${true} ${[1, "a"]} ${if (false) { 1 }}""#,
+                "1.5 true [1, a] null",
+            ),
+            (
+                r#"let name = "x"; "outer ${"inner ${name}"}!""#,
+                "outer inner x!",
+            ),
+            (r#""no ${missing} here""#, "identifier not found: missing"),
+        ];
+
+        for input in inputs {
+            let evaluated = test_eval(input.0);
+            input.1.assert_object(evaluated);
+        }
+    }
+
+    #[test]
+    fn test_null_operators() {
+        let inputs: Vec<(&str, &dyn TestObject)> = vec![
+            ("null", &Some(Object::Null)),
+            ("null == null", &true),
+            ("1 != null", &true),
+            ("null == false", &false),
+            ("null ?? 5", &5),
+            ("3 ?? 5", &3),
+            ("false ?? 5", &false),
+            ("if (false) { 1 } ?? 2", &2),
+            ("null ?? null ?? 7", &7),
+            ("1 ?? missing", &1),
+            ("missing ?? 1", &"identifier not found: missing"),
+            (
+                r#"let config = {"db": {"port": 1234}}; config?["db"]?["port"] ?? 5432"#,
+                &1234,
+            ),
+            (r#"let config = {}; config?["db"]?["port"] ?? 5432"#, &5432),
+            (
+                r#"let config = null; config?["db"]?["port"] ?? 5432"#,
+                &5432,
+            ),
+            (r#"let config = {"db": {"port": 1}}; config?.db?.port"#, &1),
+            (r#"null?.missing"#, &Some(Object::Null)),
+            ("null + 1", &"unknown operator: NULL + INTEGER"),
+        ];
+
+        for input in inputs {
+            let evaluated = test_eval(input.0);
+            input.1.assert_object(evaluated);
+        }
+    }
+
     #[test]
     fn test_builtin_functions() {
         let inputs: Vec<(&str, &dyn TestObject)> = vec![
             (r#"len("")"#, &0),
             (r#"len("four")"#, &4),
             (r#"len("hello world")"#, &11),
+            (r#"len("héllo")"#, &5),
+            (r#"len("日本")"#, &2),
             (r#"len(1)"#, &"argument to `len` not supported, got INTEGER"),
             (
                 r#"len("one", "two")"#,
                 &"wrong number of arguments. got=2, want=1",
             ),
+            ("int(3.9)", &3),
+            ("int(-3.9)", &-3),
+            ("int(-0.5)", &0),
+            (r#"int("42")"#, &42),
+            (r#"int("4x")"#, &r#"could not convert "4x" to INTEGER"#),
+            ("float(2)", &2.0),
+            (r#"float("1e3")"#, &1000.0),
+            (
+                "float(true)",
+                &"argument to `float` not supported, got BOOLEAN",
+            ),
+        ];
+
+        for input in inputs {
+            let evaluated = test_eval(input.0);
+            input.1.assert_object(evaluated)
+        }
+    }
+
+    #[test]
+    fn test_string_index_expressions() {
+        let inputs: Vec<(&str, &dyn TestObject)> = vec![
+            (r#""abc"[0]"#, &"a"),
+            (r#""héllo"[1]"#, &"é"),
+            (r#""日本語"[2]"#, &"語"),
+            (r#""abc"[3]"#, &Some(Object::Null)),
+            (r#""abc"[-1]"#, &"c"),
+            (r#""héllo"[-4]"#, &"é"),
+            (r#""abc"[-4]"#, &Some(Object::Null)),
         ];
 
         for input in inputs {
@@ -668,7 +2244,17 @@ addTwo(2);"#,
                 &2,
             ),
             ("[1, 2, 3][3]", &Some(Object::Null)),
-            ("[1, 2, 3][-1]", &Some(Object::Null)),
+            ("[1, 2, 3][-1]", &3),
+            ("[1, 2, 3][-3]", &1),
+            ("[1, 2, 3][-4]", &Some(Object::Null)),
+            (
+                "[1, 2][100000000000000000000]",
+                &"index out of range: 100000000000000000000",
+            ),
+            (
+                r#""ab"[-100000000000000000000]"#,
+                &"index out of range: -100000000000000000000",
+            ),
         ];
 
         for input in inputs {
@@ -677,6 +2263,45 @@ addTwo(2);"#,
         }
     }
 
+    #[test]
+    fn test_slice_expressions() {
+        let inputs: Vec<(&str, &str)> = vec![
+            ("[1, 2, 3, 4][1:3]", "[2, 3]"),
+            ("[1, 2, 3, 4][:-1]", "[1, 2, 3]"),
+            ("[1, 2, 3, 4][2:]", "[3, 4]"),
+            ("[1, 2, 3, 4][:]", "[1, 2, 3, 4]"),
+            ("[1, 2, 3, 4][-2:]", "[3, 4]"),
+            ("[1, 2, 3, 4][3:1]", "[]"),
+            ("[1, 2, 3, 4][-10:10]", "[1, 2, 3, 4]"),
+            ("let i = 1; [1, 2, 3, 4][i:i + 2]", "[2, 3]"),
+            (r#""hello"[1:3]"#, "el"),
+            (r#""héllo wörld"[:-3]"#, "héllo wö"),
+            (r#""hello"[2:]"#, "llo"),
+            (r#""hello"[10:]"#, ""),
+            (
+                r#"[1, 2]["a":]"#,
+                "ERROR: slice index must be INTEGER, got STRING",
+            ),
+            (
+                "[1, 2][:100000000000000000000]",
+                "ERROR: slice index out of range: 100000000000000000000",
+            ),
+            (
+                r#""ab"[-100000000000000000000:]"#,
+                "ERROR: slice index out of range: -100000000000000000000",
+            ),
+            (
+                r#"{"a": 1}[0:1]"#,
+                "ERROR: slice operator not supported: HASH",
+            ),
+        ];
+
+        for input in inputs {
+            let evaluated = test_eval(input.0).unwrap();
+            assert_eq!(evaluated.inspect(), input.1, "{}", input.0);
+        }
+    }
+
     #[test]
     fn test_hash_literal_values() {
         let input = r#"{
@@ -756,6 +2381,61 @@ addTwo(2);"#,
             (r#"{5: 5}[5]"#, &5),
             (r#"{true: 5}[true]"#, &5),
             (r#"{false: 5}[false]"#, &5),
+            (r#"{[1, "a"]: 5}[[1, "a"]]"#, &5),
+            (r#"{[1, "a"]: 5}[["a", 1]]"#, &Some(Object::Null)),
+            (r#"{{"a": 1, "b": 2}: 5}[{"b": 2, "a": 1}]"#, &5),
+            (
+                r#"let k = [1]; let h = {k: 5}; let k = push(k, 2); h[[1]]"#,
+                &5,
+            ),
+            ("{[fn() { 1 }]: 5}", &"unusable as hash key: ARRAY"),
+            (
+                "struct Point { x, y } {Point { x: 1, y: 2 }: 5}",
+                &"unusable as hash key: Point",
+            ),
+        ];
+
+        for input in inputs {
+            let evaluated = test_eval(input.0);
+            input.1.assert_object(evaluated);
+        }
+    }
+
+    #[test]
+    fn test_equality() {
+        let inputs: Vec<(&str, &dyn TestObject)> = vec![
+            ("[1, [2, 3]] == [1, [2, 3]]", &true),
+            ("[1, 2] == [2, 1]", &false),
+            ("[1, 2] != [1, 2, 3]", &true),
+            ("[] == []", &true),
+            (r#"{"a": 1, "b": [2]} == {"b": [2], "a": 1}"#, &true),
+            (r#"{"a": 1} == {"a": 2}"#, &false),
+            (r#"{"a": 1} != {"b": 1}"#, &true),
+            // Functions are only equal to themselves, however they're written.
+            ("let f = fn(x) { x }; f == f", &true),
+            ("let f = fn(x) { x }; let g = f; f != g", &false),
+            ("fn(x) { x } == fn(x) { x }", &false),
+            ("let make = fn() { fn(x) { x } }; make() == make()", &false),
+            ("len == len", &true),
+            ("len == first", &false),
+            ("[len, 1] == [len, 1]", &true),
+            (
+                "struct A { x } struct B { x } A { x: 1 } == B { x: 1 }",
+                &false,
+            ),
+            // Numbers inside other values compare the way they do on their own.
+            ("2 == 2.0", &true),
+            ("[2] == [2.0]", &true),
+            (r#"{"a": 2} == {"a": 2.0}"#, &true),
+            ("[2] == [2.5]", &false),
+            (
+                "[100000000000000000000] == [100000000000000000000.0]",
+                &true,
+            ),
+            ("[1, 2.0].contains(2)", &true),
+            ("match (2.0) { 2 => \"two\", _ => \"other\" }", &"two"),
+            ("[1] == 1", &"type mismatch: ARRAY == INTEGER"),
+            ("[1] < [2]", &"unknown operator: ARRAY < ARRAY"),
         ];
 
         for input in inputs {
@@ -763,4 +2443,578 @@ addTwo(2);"#,
             input.1.assert_object(evaluated);
         }
     }
+
+    #[test]
+    fn test_structs() {
+        let point = "struct Point { x, y }\n";
+        let inputs: Vec<(&str, &dyn TestObject)> = vec![
+            ("let p = Point { x: 1, y: 2 }; p.x + p.y", &3),
+            ("let x = 5; let p = Point { y: 1, x }; p.x", &5),
+            ("let p = Point { x: 1, y: 2 }; p.x = 10; p.x", &10),
+            ("let p = Point { x: 1, y: 2 }; p.y = p.y + 1", &3),
+            // Instances are shared, so an assignment is seen through every
+            // binding.
+            (
+                "let p = Point { x: 1, y: 2 }; let q = p; q.x = 7; p.x",
+                &7,
+            ),
+            (
+                "let move = fn(p) { p.x = p.x + 1 }; let p = Point { x: 1, y: 2 }; move(p); move(p); p.x",
+                &3,
+            ),
+            (r#"let p = Point { x: 1, y: 2 }; p["y"]"#, &2),
+            (
+                "struct Line { from, to } let l = Line { from: Point { x: 0, y: 0 }, to: Point { x: 3, y: 4 } }; l.to.y",
+                &4,
+            ),
+            ("Point { x: 1, y: 2 } == Point { x: 1, y: 2 }", &true),
+            ("Point { x: 1, y: 2 } == Point { x: 2, y: 1 }", &false),
+            (
+                "let a = Point { x: 1, y: null }; a.y = a; let b = Point { x: 1, y: null }; b.y = b; a == b",
+                &true,
+            ),
+            (
+                "let a = Point { x: 1, y: null }; a.y = a; let b = Point { x: 2, y: null }; b.y = b; a == b",
+                &false,
+            ),
+            ("let a = Point { x: 1, y: null }; a.y = a; a == a.y", &true),
+            (
+                r#"let a = Point { x: 1, y: null }; a.y = a; "${a}""#,
+                &"Point { x: 1, y: Point { ... } }",
+            ),
+            (
+                r#"let a = Point { x: 1, y: null }; let b = Point { x: 2, y: a }; a.y = [b, b]; "${a}""#,
+                &"Point { x: 1, y: [Point { x: 2, y: Point { ... } }, Point { x: 2, y: Point { ... } }] }",
+            ),
+            (r#"let h = {"name": "monkey"}; h.name"#, &"monkey"),
+            (r#"{"name": "monkey"}.age"#, &Some(Object::Null)),
+            (
+                "let p = Point { x: 1, y: 2 }; p.z",
+                &"unknown field z for struct Point",
+            ),
+            (
+                "let p = Point { x: 1, y: 2 }; p.z = 1",
+                &"unknown field z for struct Point",
+            ),
+            (
+                "Point { x: 1, y: 2, z: 3 }",
+                &"unknown field z for struct Point",
+            ),
+            ("Point { x: 1 }", &"missing fields for struct Point: y"),
+            ("let p = 1; p.x", &"field access not supported: INTEGER.x"),
+            (
+                r#"let h = {"x": 1}; h.x = 2"#,
+                &"cannot assign to field x of HASH",
+            ),
+            ("let n = 1; n { x: 1 }", &"n is not a struct: INTEGER"),
+            ("Point { x: 1, y: 2 } + 1", &"type mismatch: Point + INTEGER"),
+            (
+                "let p = Point { x: 1, y: 2 }; try { p.z } catch (e) { e[\"kind\"] }",
+                &"KeyError",
+            ),
+        ];
+
+        for (input, expected) in inputs {
+            let evaluated = test_eval(&format!("{point}{input}"));
+            expected.assert_object(evaluated);
+        }
+    }
+
+    #[test]
+    fn test_struct_inspect() {
+        let inputs = vec![
+            ("struct Point { x, y } Point", "struct Point { x, y }"),
+            (
+                r#"struct Point { x, y } Point { y: "b", x: [1] }"#,
+                "Point { x: [1], y: b }",
+            ),
+        ];
+
+        for (input, expected) in inputs {
+            let Some(evaluated) = test_eval(input) else {
+                panic!("no result for {input}");
+            };
+            assert_eq!(evaluated.inspect(), expected);
+        }
+    }
+
+    #[test]
+    fn test_method_calls() {
+        let inputs: Vec<(&str, &dyn TestObject)> = vec![
+            ("[1, 2].push(3).len()", &3),
+            ("let arr = [1, 2, 3]; arr.rest().first()", &2),
+            ("[1, 2, 3].reverse().last()", &1),
+            (r#"[1, 2, 3].join(", ")"#, &"1, 2, 3"),
+            ("[1, 2, 3].contains(2)", &true),
+            (r#""abc".upper()"#, &"ABC"),
+            (r#""  Monkey ".trim().lower()"#, &"monkey"),
+            (r#""a,b,c".split(",").len()"#, &3),
+            (r#""héllo".len()"#, &5),
+            (r#"{"a": 1, "b": 2}.len()"#, &2),
+            (r#"{"a": 1}.keys()[0]"#, &"a"),
+            (r#"{"a": 1}.contains("a")"#, &true),
+            ("(-5).abs()", &5),
+            ("let n = 3; n.to_string()", &"3"),
+            ("2.float()", &2.0),
+            // A hash entry holding a function is called rather than looked
+            // up as a method.
+            (r#"let h = {"len": fn() { 42 }}; h.len()"#, &42),
+            (r#""abc".shout()"#, &"unknown method shout for STRING"),
+            ("true.len()", &"unknown method len for BOOLEAN"),
+            ("[1].push()", &"wrong number of arguments. got=1, want=2"),
+            (
+                r#""a".split(1)"#,
+                &"separator for `split` must be STRING, got INTEGER",
+            ),
+        ];
+
+        for (input, expected) in inputs {
+            let evaluated = test_eval(input);
+            expected.assert_object(evaluated);
+        }
+    }
+
+    #[test]
+    fn test_impl_blocks() {
+        let point = "struct Point { x, y }
+            impl Point {
+                fn new(x, y) { Point { x, y } }
+                fn sum(self) { self.x + self.y }
+                fn scale(self, k) { Point { x: self.x * k, y: self.y * k } }
+                fn shift(self) { self.x = self.x + 1; self }
+            }\n";
+        let inputs: Vec<(&str, &dyn TestObject)> = vec![
+            ("Point { x: 1, y: 2 }.sum()", &3),
+            ("Point.new(1, 2).scale(10).sum()", &30),
+            ("let p = Point.new(1, 2); p.shift().shift(); p.x", &3),
+            // Later impl blocks add to or replace earlier methods.
+            (
+                "impl Point { fn sum(self) { 0 } fn dot(self, o) { self.x * o.x + self.y * o.y } } let p = Point.new(1, 2); p.sum() + p.dot(p)",
+                &5,
+            ),
+            // Methods are closures over the scope of the impl block.
+            (
+                "let offset = 100; impl Point { fn far(self) { self.x + offset } } Point.new(1, 2).far()",
+                &101,
+            ),
+            (
+                "let f = fn(p) { p.missing() }; f(Point.new(1, 2))",
+                &"unknown field or method missing for struct Point",
+            ),
+            ("Point.sum()", &"wrong number of arguments. got=0, want=1"),
+            ("Point.nope()", &"unknown method nope for struct Point"),
+            (
+                "impl Point { fn x(self) { 1 } }",
+                &"method x conflicts with field x of struct Point",
+            ),
+            ("let n = 1; impl n { fn f() { 1 } }", &"n is not a struct: INTEGER"),
+            ("impl Missing { fn f() { 1 } }", &"identifier not found: Missing"),
+        ];
+
+        for (input, expected) in inputs {
+            let evaluated = test_eval(&format!("{point}{input}"));
+            expected.assert_object(evaluated);
+        }
+    }
+
+    #[test]
+    fn test_enums() {
+        let shape = "enum Shape { Circle(r), Rect(w, h), Empty }
+            let area = fn(shape) {
+                match (shape) {
+                    Circle(r) => 3 * r * r,
+                    Shape.Rect(w, h) => w * h,
+                    Shape.Empty => 0,
+                }
+            };\n";
+        let inputs: Vec<(&str, &dyn TestObject)> = vec![
+            ("area(Circle(2))", &12),
+            ("area(Rect(2, 3))", &6),
+            ("area(Shape.Rect(4, 5))", &20),
+            ("area(Empty)", &0),
+            ("area(Shape.Empty)", &0),
+            ("Rect(1, 2) == Rect(1, 2)", &true),
+            ("Rect(1, 2) == Rect(2, 1)", &false),
+            ("Rect(1, 2) != Circle(1)", &true),
+            ("Empty == Shape.Empty", &true),
+            ("Circle([1, 2]) == Circle([1, 2])", &true),
+            (r#"let h = {Circle(1): "small", Circle(9): "big"}; h[Circle(9)]"#, &"big"),
+            (r#"let h = {Empty: "nothing"}; h[Shape.Empty]"#, &"nothing"),
+            // Variants nest, and payload patterns are matched recursively.
+            (
+                "enum Tree { Leaf(v), Node(l, r) }
+                let sum = fn(t) { match (t) { Leaf(v) => v, Node(l, r) => sum(l) + sum(r) } };
+                sum(Node(Leaf(1), Node(Leaf(2), Leaf(3))))",
+                &6,
+            ),
+            (
+                "match (Rect(2, 2)) { Rect(w, h) if w == h => \"square\", Rect(_, _) => \"rect\", _ => \"other\" }",
+                &"square",
+            ),
+            ("match (Circle(1)) { Circle(0) => 0, Circle(1) => 1, _ => 2 }", &1),
+            // A bare unit variant matches that variant instead of binding.
+            (
+                "match (Circle(1)) { Empty => \"empty\", Circle(r) => r }",
+                &1,
+            ),
+            ("match (Empty) { Empty => \"empty\", _ => \"other\" }", &"empty"),
+            ("match (7) { Empty => \"empty\", n => n }", &7),
+            (
+                "enum E { A, B(x) } match (B(1)) { A => \"a\", B(v) => v }",
+                &1,
+            ),
+            // Without parentheses the payload isn't checked.
+            ("match (Rect(1, 2)) { Shape.Rect => 1, _ => 2 }", &1),
+            ("match (Rect(1, 2)) { Rect(w) => 1, _ => 2 }", &2),
+            // Variants of different enums with the same name don't match
+            // when qualified.
+            (
+                "enum Other { Circle(r) } match (Other.Circle(1)) { Shape.Circle(r) => 1, _ => 2 }",
+                &2,
+            ),
+            ("let [Circle(r)] = [Circle(7)]; r", &7),
+            (
+                "let Circle(r) = Rect(1, 2);",
+                &"Shape.Rect(1, 2) does not match pattern Circle(r)",
+            ),
+            (
+                "Rect(1)",
+                &"wrong number of arguments for Shape.Rect. got=1, want=2",
+            ),
+            ("Shape.Triangle", &"unknown variant Triangle for enum Shape"),
+            ("Shape.Triangle(1)", &"unknown variant Triangle for enum Shape"),
+            ("Circle(1) + 1", &"type mismatch: Shape + INTEGER"),
+            ("Circle(1) < Circle(2)", &"unknown operator: Shape < Shape"),
+            (
+                r#"let h = {}; h[Circle(fn() { 1 })]"#,
+                &"unusable as hash key: Shape",
+            ),
+        ];
+
+        for (input, expected) in inputs {
+            let evaluated = test_eval(&format!("{shape}{input}"));
+            expected.assert_object(evaluated);
+        }
+    }
+
+    #[test]
+    fn test_enum_inspect() {
+        let shape = "enum Shape { Circle(r), Rect(w, h), Empty }\n";
+        let inputs = vec![
+            ("Shape", "enum Shape { Circle(r), Rect(w, h), Empty }"),
+            ("Rect(1, \"b\")", "Shape.Rect(1, b)"),
+            ("Circle(Empty)", "Shape.Circle(Shape.Empty)"),
+            ("Empty", "Shape.Empty"),
+            ("Shape.Rect", "fn Shape.Rect(w, h)"),
+        ];
+
+        for (input, expected) in inputs {
+            let Some(evaluated) = test_eval(&format!("{shape}{input}")) else {
+                panic!("no result for {input}");
+            };
+            assert_eq!(evaluated.inspect(), expected);
+        }
+    }
+
+    #[test]
+    fn test_pipelines_and_composition() {
+        let inputs: Vec<(&str, &dyn TestObject)> = vec![
+            ("[1, 2] |> push(3) |> rest() |> len()", &2),
+            ("[1, 2, 3] |> len", &3),
+            ("let add = fn(a, b) { a + b }; 1 |> add(2) |> add(3)", &6),
+            ("let sub = fn(a, b) { a - b }; 10 |> sub(3)", &7),
+            (r#""monkey" |> len"#, &6),
+            (
+                "let double = fn(x) { x * 2 }; let inc = fn(x) { x + 1 }; (double >> inc)(5)",
+                &11,
+            ),
+            (
+                "let double = fn(x) { x * 2 }; let inc = fn(x) { x + 1 }; 5 |> inc >> double",
+                &12,
+            ),
+            // The first function gets every argument.
+            (
+                "let add = fn(a, b) { a + b }; let f = add >> fn(x) { x * 10 }; f(1, 2)",
+                &30,
+            ),
+            ("let f = rest >> len; f([1, 2, 3])", &2),
+            (
+                "enum Box { Full(v) } let f = fn(x) { x + 1 } >> Full; match (f(1)) { Full(v) => v }",
+                &2,
+            ),
+            (
+                "let f = len >> fn(x) { x * 2 } >> fn(x) { x + 1 }; [1, 2] |> f",
+                &5,
+            ),
+            (
+                "let f = fn(x) { x + true } >> len; f(1)",
+                &"type mismatch: INTEGER + BOOLEAN",
+            ),
+            (
+                "let f = fn(x) { x } >> 1;",
+                &"cannot compose FUNCTION >> INTEGER, both sides must be functions",
+            ),
+            ("1 |> 2", &"not a function: INTEGER"),
+        ];
+
+        for (input, expected) in inputs {
+            let evaluated = test_eval(input);
+            expected.assert_object(evaluated);
+        }
+
+        let Some(composed) = test_eval("len >> fn(x) { x }") else {
+            panic!("no result for composition");
+        };
+        assert_eq!(composed.inspect(), "builtin function >> fn(x) { x }");
+    }
+
+    #[test]
+    fn test_short_lambdas() {
+        let inputs: Vec<(&str, &dyn TestObject)> = vec![
+            ("let double = |x| x * 2; double(21)", &42),
+            ("let add = (a, b) => a + b; add(1, 2)", &3),
+            ("(|| 7)()", &7),
+            ("let f = |x| { let y = x + 1; y * 2 }; f(1)", &4),
+            ("let adder = |x| |y| x + y; adder(1)(2)", &3),
+            ("let first = |[a, _]| a; first([1, 2])", &1),
+            (
+                "let name = ({name: n}) => n; name({\"name\": \"monkey\"})",
+                &"monkey",
+            ),
+            ("5 |> |x| x + 1", &6),
+            ("let f = (|x| x + 1) >> |x| x * 2; f(1)", &4),
+            ("[1, 2] |> push(3) |> (xs) => len(xs)", &3),
+            (
+                r#"let ap = fn(f, x) { f(x) }; match (1) { n if ap((x) => x == n, 1) => "yes", _ => "no" }"#,
+                &"yes",
+            ),
+        ];
+
+        for (input, expected) in inputs {
+            let evaluated = test_eval(input);
+            expected.assert_object(evaluated);
+        }
+    }
+
+    #[test]
+    fn test_function_inspect() {
+        let inputs = vec![
+            ("|x| x * 2", "fn(x) { x * 2 }"),
+            ("(a, b) => (a + b) * 2", "fn(a, b) { (a + b) * 2 }"),
+            ("fn() { }", "fn() { }"),
+            (
+                r#"fn(x) { let s = "a\t\"b\""; puts(s); if (x > 1) { x } else { -x } }"#,
+                r#"fn(x) { let s = "a\t\"b\""; puts(s); if (x > 1) { x } else { -x } }"#,
+            ),
+            (
+                "|[a, ...b]| { return a - (b[0] - 1); }",
+                "fn([a, ...b]) { return a - (b[0] - 1); }",
+            ),
+            (
+                "macro(x) { quote(unquote(x) + 1) }",
+                "macro(x) { quote(unquote(x) + 1) }",
+            ),
+        ];
+
+        for (input, expected) in inputs {
+            let Some(evaluated) = test_eval(input) else {
+                panic!("no result for {input}");
+            };
+            assert_eq!(evaluated.inspect(), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_generators() {
+        let inputs = vec![
+            ("let g = fn*() { yield 1; yield 2; }; collect(g())", "[1, 2]"),
+            (
+                "let g = fn*() { yield 1; yield 2; }(); [g.next(), next(g), g.next(), g.next()]",
+                "[1, 2, null, null]",
+            ),
+            // `done` tells a yielded null apart from the end.
+            (
+                "let g = fn*() { yield null; yield 2; }(); [done(g), g.next(), g.done(), g.next(), g.done(), g.next(), g.done()]",
+                "[false, null, false, 2, false, null, true]",
+            ),
+            (
+                "let g = fn*() { 1 + true; }(); try { g.next() } catch (e) { 0 }; done(g)",
+                "true",
+            ),
+            ("done([1])", "ERROR: argument to `done` must be GENERATOR, got ARRAY"),
+            // Nothing runs until the first value is asked for.
+            (
+                "let g = fn*() { yield 1; 1 + true; }(); let a = g.next(); let b = try { g.next() } catch (e) { e.message }; [a, b, g.next()]",
+                "[1, type mismatch: INTEGER + BOOLEAN, null]",
+            ),
+            (
+                "let from = fn*(n) { yield n; for (x in from(n + 1)) { yield x; } }; take(from(1), 4)",
+                "[1, 2, 3, 4]",
+            ),
+            (
+                "let evens = fn*(xs) { for (x in xs) { if (x % 2 == 0) { yield x; } else { yield -x; } } }; collect(evens([1, 2, 3]))",
+                "[-1, 2, -3]",
+            ),
+            (
+                "let names = fn*(xs) { for (x in xs) { match (x) { [n, _] => { yield n; }, _ => { yield x; } } } }; collect(names([[1, 2], 3]))",
+                "[1, 3]",
+            ),
+            (
+                "let g = fn*() { yield 1; if (true) { let x = 2; yield x; } yield 3; return null; yield 4; }; collect(g())",
+                "[1, 2, 3]",
+            ),
+            (
+                "let g = fn*(n) { let step = |x| x * n; yield step(1); yield step(2); }; g(10) |> collect",
+                "[10, 20]",
+            ),
+            (
+                "let zip = fn*(a, b) { for (x in a) { yield [x, b.next()]; } }; collect(zip([1, 2], fn*() { yield 3; yield 4; }()))",
+                "[[1, 3], [2, 4]]",
+            ),
+            (
+                "struct Tree { items } impl Tree { fn* walk(self) { for (x in self.items) { yield x; } } } collect(Tree { items: [1, 2] }.walk())",
+                "[1, 2]",
+            ),
+            ("fn*(a) { yield a; }", "fn*(a) { yield a; }"),
+            ("fn*(a) { yield a; }(1)", "<generator>"),
+            ("collect(\"ab\")", "[a, b]"),
+            ("take([1, 2, 3], 2)", "[1, 2]"),
+            (
+                "let g = fn*() { let x = if (true) { yield 1; }; }; collect(g())",
+                "ERROR: yield can only be used as a statement of a generator, outside of expressions and try blocks",
+            ),
+            (
+                "let g = fn*() { for (x in g) { yield x; } }(); next(g)",
+                "ERROR: generator is already running",
+            ),
+            ("collect(1)", "ERROR: cannot iterate over INTEGER"),
+            ("next([1])", "ERROR: argument to `next` must be GENERATOR, got ARRAY"),
+            (
+                "take([1], -1)",
+                "ERROR: count for `take` must be a non-negative INTEGER, got INTEGER",
+            ),
+        ];
+
+        for (input, expected) in inputs {
+            let Some(evaluated) = test_eval(input) else {
+                panic!("no result for {input}");
+            };
+            assert_eq!(evaluated.inspect(), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_for_statements() {
+        let inputs: Vec<(&str, &dyn TestObject)> = vec![
+            (
+                "let f = fn(xs) { for (x in xs) { if (x > 1) { return x; } } 0 }; f([1, 2, 3])",
+                &2,
+            ),
+            (
+                "let f = fn(xs) { for ([a, b] in xs) { return a + b; } }; f([[1, 2]])",
+                &3,
+            ),
+            ("let x = 1; for (x in [2, 3]) { } x", &1),
+            ("for (c in \"ab\") { puts(c); }", &None),
+            ("for (x in 5) { }", &"cannot iterate over INTEGER"),
+            (
+                "for ([a] in [1]) { }",
+                &"cannot destructure INTEGER as ARRAY",
+            ),
+            (
+                "for (x in [1]) { x + true; }",
+                &"type mismatch: INTEGER + BOOLEAN",
+            ),
+        ];
+
+        for (input, expected) in inputs {
+            let evaluated = test_eval(input);
+            expected.assert_object(evaluated);
+        }
+    }
+
+    fn assert_counts_down(n: u32) {
+        let input = format!("let countdown = fn(n) {{ if (n == 0) {{ \"done\" }} else {{ countdown(n - 1) }} }}; countdown({n})");
+        "done".assert_object(test_eval(&input));
+    }
+
+    #[test]
+    fn test_deep_tail_calls() {
+        assert_counts_down(100000);
+    }
+
+    // Takes a while in debug builds, so it only runs with `--ignored`.
+    #[test]
+    #[ignore]
+    fn test_million_tail_calls() {
+        assert_counts_down(1000000);
+    }
+
+    #[test]
+    fn test_tail_calls() {
+        let inputs: Vec<(&str, &dyn TestObject)> = vec![
+            (
+                "let even = fn(n) { if (n == 0) { return true; } return odd(n - 1); }; let odd = fn(n) { match (n) { 0 => false, _ => even(n - 1) } }; even(100001)",
+                &false,
+            ),
+            (
+                "let sum = |n, acc| if (n == 0) { acc } else { sum(n - 1, acc + n) }; sum(100000, 0)",
+                &5000050000,
+            ),
+            (
+                "struct C { step } impl C { fn down(self, n) { if (n < 1) { n } else { self.down(n - self.step) } } } C { step: 1 }.down(100000)",
+                &0,
+            ),
+            // Calls that aren't the last thing a function does still nest.
+            (
+                "let count = fn(n) { if (n == 0) { 0 } else { 1 + count(n - 1) } }; count(20)",
+                &20,
+            ),
+            (
+                "let f = fn(n) { let x = g(n); x + 1 }; let g = fn(n) { n * 2 }; f(2)",
+                &5,
+            ),
+            // A try block has to see the result, so calls in it aren't
+            // deferred.
+            (
+                "let fail = fn() { 1 / 0 }; let f = fn() { try { fail() } catch (e) { \"caught\" } }; f()",
+                &"caught",
+            ),
+            (
+                "let g = fn(a) { a }; let f = fn() { g() }; f()",
+                &"wrong number of arguments. got=0, want=1",
+            ),
+        ];
+
+        for (input, expected) in inputs {
+            let evaluated = test_eval(input);
+            expected.assert_object(evaluated);
+        }
+
+        let input = r#"
+let inner = fn() { 1 / 0 };
+let middle = fn() { inner() };
+let outer = fn() { middle() };
+try { outer() } catch (e) { e["stack"] }
+"#;
+        let Some(Object::Array(frames)) = test_eval(input) else {
+            panic!("expected an array of frames");
+        };
+        let frames: Vec<_> = frames.iter().map(Object::inspect).collect();
+        assert_eq!(frames, vec!["inner", "outer"]);
+
+        // A chain of tail calls keeps only the call that failed and the
+        // outermost one, so none of the calls to odd show.
+        let input = r#"
+let even = fn(n) { if (n == 0) { 1 / 0 } else { odd(n - 1) } };
+let odd = fn(n) { even(n - 1) };
+let start = fn() { let x = even(4); x };
+try { start() } catch (e) { e["stack"] }
+"#;
+        let Some(Object::Array(frames)) = test_eval(input) else {
+            panic!("expected an array of frames");
+        };
+        let frames: Vec<_> = frames.iter().map(Object::inspect).collect();
+        assert_eq!(frames, vec!["even", "even", "start"]);
+    }
 }
@@ -0,0 +1,345 @@
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    ops::{Add, Mul, Neg, Sub},
+    str::FromStr,
+};
+
+/// Every limb holds nine decimal digits, which keeps printing trivial and
+/// lets the product of two limbs fit comfortably in a `u64`.
+const BASE: u64 = 1_000_000_000;
+const BASE_DIGITS: usize = 9;
+
+/// An arbitrary-precision signed integer.
+///
+/// Limbs are stored least significant first and are always normalized: there
+/// are no leading zero limbs and zero is never negative, so the derived `Hash`
+/// and `Eq` agree with numeric equality.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    limbs: Vec<u32>,
+}
+
+impl BigInt {
+    fn from_parts(negative: bool, mut limbs: Vec<u32>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        let negative = negative && !limbs.is_empty();
+        Self { negative, limbs }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        let mut magnitude: i128 = 0;
+        for limb in self.limbs.iter().rev() {
+            magnitude = magnitude.checked_mul(BASE as i128)? + *limb as i128;
+            if magnitude > i64::MAX as i128 + 1 {
+                return None;
+            }
+        }
+        let value = if self.negative { -magnitude } else { magnitude };
+        i64::try_from(value).ok()
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = self
+            .limbs
+            .iter()
+            .rev()
+            .fold(0.0, |acc, limb| acc * BASE as f64 + *limb as f64);
+        if self.negative {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    /// Truncating division returning both quotient and remainder, with the
+    /// same sign rules as Rust's `/` and `%` on primitive integers.
+    /// Returns `None` when dividing by zero.
+    pub fn div_rem(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.is_zero() {
+            return None;
+        }
+        let (quotient, remainder) = div_rem_magnitude(&self.limbs, &other.limbs);
+        Some((
+            BigInt::from_parts(self.negative != other.negative, quotient),
+            BigInt::from_parts(self.negative, remainder),
+        ))
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        let mut magnitude = value.unsigned_abs();
+        let mut limbs = Vec::new();
+        while magnitude > 0 {
+            limbs.push((magnitude % BASE) as u32);
+            magnitude /= BASE;
+        }
+        BigInt::from_parts(value < 0, limbs)
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(format!("invalid integer literal: {s}"));
+        }
+
+        let limbs = digits
+            .as_bytes()
+            .rchunks(BASE_DIGITS)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .fold(0u32, |acc, digit| acc * 10 + (digit - b'0') as u32)
+            })
+            .collect();
+        Ok(BigInt::from_parts(negative, limbs))
+    }
+}
+
+impl Display for BigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some((most_significant, rest)) = self.limbs.split_last() else {
+            return write!(f, "0");
+        };
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{most_significant}")?;
+        for limb in rest.iter().rev() {
+            write!(f, "{limb:0width$}", width = BASE_DIGITS)?;
+        }
+        Ok(())
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitude(&self.limbs, &other.limbs),
+            (true, true) => cmp_magnitude(&other.limbs, &self.limbs),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> Self::Output {
+        BigInt::from_parts(!self.negative, self.limbs.clone())
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: Self) -> Self::Output {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, add_magnitude(&self.limbs, &other.limbs));
+        }
+        match cmp_magnitude(&self.limbs, &other.limbs) {
+            Ordering::Less => {
+                BigInt::from_parts(other.negative, sub_magnitude(&other.limbs, &self.limbs))
+            }
+            _ => BigInt::from_parts(self.negative, sub_magnitude(&self.limbs, &other.limbs)),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: Self) -> Self::Output {
+        self + &-other
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: Self) -> Self::Output {
+        let mut result = vec![0u64; self.limbs.len() + other.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0;
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let current = result[i + j] + a as u64 * b as u64 + carry;
+                result[i + j] = current % BASE;
+                carry = current / BASE;
+            }
+            result[i + other.limbs.len()] += carry;
+        }
+        let limbs = result.into_iter().map(|limb| limb as u32).collect();
+        BigInt::from_parts(self.negative != other.negative, limbs)
+    }
+}
+
+fn cmp_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0;
+    for i in 0..a.len().max(b.len()) {
+        let sum = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        result.push((sum % BASE) as u32);
+        carry = sum / BASE;
+    }
+    if carry > 0 {
+        result.push(carry as u32);
+    }
+    result
+}
+
+/// Subtracts `b` from `a`, which must have the larger or equal magnitude.
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len());
+    let mut borrow = 0;
+    for (i, &limb) in a.iter().enumerate() {
+        let subtrahend = *b.get(i).unwrap_or(&0) as i64 + borrow;
+        let mut difference = limb as i64 - subtrahend;
+        borrow = 0;
+        if difference < 0 {
+            difference += BASE as i64;
+            borrow = 1;
+        }
+        result.push(difference as u32);
+    }
+    while result.last() == Some(&0) {
+        result.pop();
+    }
+    result
+}
+
+fn mul_small_magnitude(a: &[u32], factor: u32) -> Vec<u32> {
+    let mut result = Vec::with_capacity(a.len() + 1);
+    let mut carry = 0;
+    for &limb in a {
+        let product = limb as u64 * factor as u64 + carry;
+        result.push((product % BASE) as u32);
+        carry = product / BASE;
+    }
+    if carry > 0 {
+        result.push(carry as u32);
+    }
+    while result.last() == Some(&0) {
+        result.pop();
+    }
+    result
+}
+
+/// Schoolbook long division, one limb at a time. Each quotient limb is found
+/// by binary search, which is slow-ish but hard to get wrong.
+fn div_rem_magnitude(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    let mut quotient = vec![0; a.len()];
+    let mut remainder: Vec<u32> = Vec::new();
+
+    for (i, &limb) in a.iter().enumerate().rev() {
+        remainder.insert(0, limb);
+        while remainder.last() == Some(&0) {
+            remainder.pop();
+        }
+
+        let (mut low, mut high) = (0u32, (BASE - 1) as u32);
+        while low < high {
+            let middle = low + (high - low).div_ceil(2);
+            if cmp_magnitude(&mul_small_magnitude(b, middle), &remainder) == Ordering::Greater {
+                high = middle - 1;
+            } else {
+                low = middle;
+            }
+        }
+
+        quotient[i] = low;
+        remainder = sub_magnitude(&remainder, &mul_small_magnitude(b, low));
+    }
+
+    (quotient, remainder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(s: &str) -> BigInt {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        let inputs = vec![
+            "0",
+            "7",
+            "-7",
+            "1000000000",
+            "123456789012345678901234567890",
+            "-98765432109876543210",
+        ];
+
+        for input in inputs {
+            assert_eq!(big(input).to_string(), input);
+        }
+        assert_eq!(big("-0").to_string(), "0");
+        assert_eq!(big("000042").to_string(), "42");
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = big("123456789012345678901234567890");
+        let b = big("-987654321098765432");
+
+        assert_eq!((&a + &b).to_string(), "123456789011358024580135802458");
+        assert_eq!((&a - &b).to_string(), "123456789013333333222333333322");
+        assert_eq!(
+            (&a * &b).to_string(),
+            "-121932631137021795212620027521140070120989178480"
+        );
+
+        let (quotient, remainder) = a.div_rem(&b).unwrap();
+        assert_eq!(quotient.to_string(), "-124999998860");
+        assert_eq!(remainder.to_string(), "925925953827160370");
+        assert_eq!(&(&quotient * &b) + &remainder, a);
+
+        assert!(a.div_rem(&BigInt::from(0)).is_none());
+    }
+
+    #[test]
+    fn test_i64_round_trip() {
+        for value in [0, 1, -1, i64::MAX, i64::MIN] {
+            assert_eq!(BigInt::from(value).to_i64(), Some(value));
+        }
+        assert_eq!((&BigInt::from(i64::MAX) + &BigInt::from(1)).to_i64(), None);
+    }
+
+    #[test]
+    fn test_ordering() {
+        assert!(big("-10") < big("-9"));
+        assert!(big("-1") < big("0"));
+        assert!(big("1000000000") > big("999999999"));
+        assert!(big("99999999999999999999") > big("-99999999999999999999"));
+    }
+}
@@ -1,12 +1,17 @@
-use super::object::{new_error, Object};
+use super::{
+    bigint::BigInt,
+    generator::Iter,
+    object::{new_error_with_kind, ErrorKind, Object},
+};
 
 pub type BuiltinFunction = fn(&[Object]) -> Object;
 
 pub fn is_wrong_arg_amount(len: usize, want: usize) -> Option<Object> {
     if len != want {
-        return Some(new_error(format!(
-            "wrong number of arguments. got={len}, want=1",
-        )));
+        return Some(new_error_with_kind(
+            ErrorKind::Argument,
+            format!("wrong number of arguments. got={len}, want={want}",),
+        ));
     }
     None
 }
@@ -17,9 +22,12 @@ pub fn len(args: &[Object]) -> Object {
     }
 
     match &args[0] {
-        Object::String(string) => Object::Integer(string.len() as i64),
+        Object::String(string) => Object::Integer(string.chars().count() as i64),
         Object::Array(arr) => Object::Integer(arr.len() as i64),
-        got => new_error(format!("argument to `len` not supported, got {got}")),
+        got => new_error_with_kind(
+            ErrorKind::Type,
+            format!("argument to `len` not supported, got {got}"),
+        ),
     }
 }
 
@@ -30,7 +38,10 @@ pub fn first(args: &[Object]) -> Object {
 
     match &args[0] {
         Object::Array(arr) => arr.first().unwrap_or(&Object::Null).clone(),
-        got => new_error(format!("argument to `first` must be ARRAY, got {got}",)),
+        got => new_error_with_kind(
+            ErrorKind::Type,
+            format!("argument to `first` must be ARRAY, got {got}",),
+        ),
     }
 }
 
@@ -41,7 +52,10 @@ pub fn last(args: &[Object]) -> Object {
 
     match &args[0] {
         Object::Array(arr) => arr.last().unwrap_or(&Object::Null).clone(),
-        got => new_error(format!("argument to `first` must be ARRAY, got {got}",)),
+        got => new_error_with_kind(
+            ErrorKind::Type,
+            format!("argument to `first` must be ARRAY, got {got}",),
+        ),
     }
 }
 
@@ -60,7 +74,10 @@ pub fn rest(args: &[Object]) -> Object {
             new_arr.clone_from_slice(&arr[1..]);
             Object::Array(new_arr)
         }
-        got => new_error(format!("argument to `first` must be ARRAY, got {got}",)),
+        got => new_error_with_kind(
+            ErrorKind::Type,
+            format!("argument to `first` must be ARRAY, got {got}",),
+        ),
     }
 }
 
@@ -75,10 +92,137 @@ pub fn push(args: &[Object]) -> Object {
             new_arr.push(args[1].clone());
             Object::Array(new_arr)
         }
-        got => new_error(format!("argument to `first` must be ARRAY, got {got}",)),
+        got => new_error_with_kind(
+            ErrorKind::Type,
+            format!("argument to `first` must be ARRAY, got {got}",),
+        ),
     }
 }
 
+pub fn int(args: &[Object]) -> Object {
+    if let Some(error) = is_wrong_arg_amount(args.len(), 1) {
+        return error;
+    }
+
+    match &args[0] {
+        Object::Integer(_) | Object::BigInteger(_) => args[0].clone(),
+        // Formatting a float with no decimals gives every digit of its
+        // integer part, so ones outside the `i64` range convert exactly too.
+        Object::Float(float) if float.is_finite() => format!("{:.0}", float.trunc())
+            .parse::<BigInt>()
+            .expect("a float formatted without decimals is an integer")
+            .into(),
+        Object::Boolean(bool) => Object::Integer(*bool as i64),
+        Object::String(string) => match string.trim().parse::<BigInt>() {
+            Ok(int) => int.into(),
+            Err(_) => new_error_with_kind(
+                ErrorKind::Argument,
+                format!("could not convert {string:?} to INTEGER"),
+            ),
+        },
+        got => new_error_with_kind(
+            ErrorKind::Type,
+            format!("argument to `int` not supported, got {got}"),
+        ),
+    }
+}
+
+pub fn float(args: &[Object]) -> Object {
+    if let Some(error) = is_wrong_arg_amount(args.len(), 1) {
+        return error;
+    }
+
+    match &args[0] {
+        Object::Integer(int) => Object::Float(*int as f64),
+        Object::BigInteger(int) => Object::Float(int.to_f64()),
+        Object::Float(float) => Object::Float(*float),
+        Object::String(string) => match string.trim().parse() {
+            Ok(float) => Object::Float(float),
+            Err(_) => new_error_with_kind(
+                ErrorKind::Argument,
+                format!("could not convert {string:?} to FLOAT"),
+            ),
+        },
+        got => new_error_with_kind(
+            ErrorKind::Type,
+            format!("argument to `float` not supported, got {got}"),
+        ),
+    }
+}
+
+/// The next value of a generator, or null once it has finished. A generator
+/// can yield null too, so `done` tells the two apart.
+pub fn next(args: &[Object]) -> Object {
+    if let Some(error) = is_wrong_arg_amount(args.len(), 1) {
+        return error;
+    }
+
+    match &args[0] {
+        Object::Generator(generator) => generator.resume().unwrap_or(Object::Null),
+        got => new_error_with_kind(
+            ErrorKind::Type,
+            format!("argument to `next` must be GENERATOR, got {got}"),
+        ),
+    }
+}
+
+/// Whether a generator has finished, which is known once `next` has run it
+/// to the end of its body, or to an error.
+pub fn done(args: &[Object]) -> Object {
+    if let Some(error) = is_wrong_arg_amount(args.len(), 1) {
+        return error;
+    }
+
+    match &args[0] {
+        Object::Generator(generator) => generator.is_done().into(),
+        got => new_error_with_kind(
+            ErrorKind::Type,
+            format!("argument to `done` must be GENERATOR, got {got}"),
+        ),
+    }
+}
+
+/// Every value left in an array, string or generator, as an array.
+pub fn collect(args: &[Object]) -> Object {
+    if let Some(error) = is_wrong_arg_amount(args.len(), 1) {
+        return error;
+    }
+
+    collect_values(args[0].clone(), usize::MAX)
+}
+
+/// The first `n` values left in an array, string or generator, which only
+/// runs a generator as far as it needs to, so it works on endless ones.
+pub fn take(args: &[Object]) -> Object {
+    if let Some(error) = is_wrong_arg_amount(args.len(), 2) {
+        return error;
+    }
+
+    match &args[1] {
+        Object::Integer(n) if *n >= 0 => collect_values(args[0].clone(), *n as usize),
+        got => new_error_with_kind(
+            ErrorKind::Type,
+            format!("count for `take` must be a non-negative INTEGER, got {got}"),
+        ),
+    }
+}
+
+fn collect_values(iterable: Object, limit: usize) -> Object {
+    let values = match Iter::new(iterable) {
+        Ok(values) => values,
+        Err(e) => return e,
+    };
+
+    let mut collected = Vec::new();
+    for value in values.take(limit) {
+        if value.is_error() {
+            return value;
+        }
+        collected.push(value);
+    }
+    Object::Array(collected)
+}
+
 pub fn puts(args: &[Object]) -> Object {
     args.iter().for_each(|arg| println!("{}", arg.inspect()));
 
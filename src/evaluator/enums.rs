@@ -0,0 +1,114 @@
+use std::rc::Rc;
+
+use super::{
+    gc::{self, Address, Trace},
+    object::{new_error_with_kind, ErrorKind, Object},
+};
+use crate::parser::ast::{EnumVariant, Identifier};
+
+/// A type declared with `enum Name { Variant(fields), Unit, ... }`.
+#[derive(Debug)]
+pub struct EnumType {
+    pub name: Identifier,
+    pub variants: Vec<EnumVariant>,
+}
+
+impl EnumType {
+    /// The value `Enum.name` refers to: a constructor for variants with
+    /// fields, or the value itself for unit variants.
+    pub fn variant(self: &Rc<Self>, name: &str) -> Result<Object, Object> {
+        let Some(tag) = self.variants.iter().position(|v| v.name.0 == name) else {
+            return Err(new_error_with_kind(
+                ErrorKind::Key,
+                format!("unknown variant {name} for enum {}", self.name),
+            ));
+        };
+        Ok(match self.variants[tag].fields {
+            Some(_) => Object::Constructor(self.clone(), tag),
+            None => Object::Variant(Rc::new(Variant {
+                ty: self.clone(),
+                tag,
+                payload: Vec::new(),
+            })),
+        })
+    }
+
+    pub fn inspect(&self) -> String {
+        let variants = self
+            .variants
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("enum {} {{ {variants} }}", self.name)
+    }
+}
+
+/// A value built from one of an enum's variants.
+#[derive(Debug)]
+pub struct Variant {
+    pub ty: Rc<EnumType>,
+    tag: usize,
+    pub payload: Vec<Object>,
+}
+
+impl Variant {
+    pub fn name(&self) -> &Identifier {
+        &self.ty.variants[self.tag].name
+    }
+
+    pub fn inspect(&self) -> String {
+        let name = format!("{}.{}", self.ty.name, self.name());
+        if self.ty.variants[self.tag].fields.is_none() {
+            return name;
+        }
+        let payload = self
+            .payload
+            .iter()
+            .map(|value| value.inspect())
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("{name}({payload})")
+    }
+}
+
+// A variant can't change once it's built, so it's never the value that has
+// to be cleared to break a cycle, but the references it holds still count.
+impl Trace for Variant {
+    fn trace(&self, visit: &mut dyn FnMut(Address)) -> bool {
+        self.payload
+            .iter()
+            .for_each(|value| gc::trace_object(value, visit));
+        true
+    }
+
+    fn clear(&self) {}
+}
+
+/// Calls the constructor for the variant at `tag`, which takes one argument
+/// per declared field.
+pub fn construct(ty: &Rc<EnumType>, tag: usize, args: &[Object]) -> Object {
+    let fields = ty.variants[tag].fields.as_deref().unwrap_or_default();
+    if args.len() != fields.len() {
+        return new_error_with_kind(
+            ErrorKind::Argument,
+            format!(
+                "wrong number of arguments for {}.{}. got={}, want={}",
+                ty.name,
+                ty.variants[tag].name,
+                args.len(),
+                fields.len()
+            ),
+        );
+    }
+    Object::Variant(gc::track(Variant {
+        ty: ty.clone(),
+        tag,
+        payload: args.to_vec(),
+    }))
+}
+
+/// How a constructor inspects, e.g. `fn Shape.Rect(w, h)`.
+pub fn inspect_constructor(ty: &EnumType, tag: usize) -> String {
+    format!("fn {}.{}", ty.name, ty.variants[tag])
+}
@@ -0,0 +1,260 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    rc::{Rc, Weak},
+};
+
+use super::object::Object;
+
+/// Where a tracked value lives, which identifies it while collecting.
+pub type Address = *const ();
+
+/// A value shared through an `Rc` that can hold on to other shared values,
+/// and so can end up in a reference cycle: a function stored in the
+/// environment it closes over, a struct with a field holding itself, a
+/// generator whose frames hold the generator, and so on.
+pub trait Trace {
+    /// Calls `visit` with every tracked value this holds a strong reference
+    /// to, once per reference. Returns `false` if this is borrowed mutably
+    /// and can't be looked into right now.
+    fn trace(&self, visit: &mut dyn FnMut(Address)) -> bool;
+
+    /// Drops what this holds, to break the cycles it's part of once nothing
+    /// outside of them can reach it.
+    fn clear(&self);
+}
+
+/// Calls `visit` with every tracked value `object` holds, looking through
+/// the values it owns outright such as the elements of an array.
+pub fn trace_object(object: &Object, visit: &mut dyn FnMut(Address)) {
+    match object {
+        Object::Array(elements) => elements.iter().for_each(|e| trace_object(e, visit)),
+        Object::Hash(map) => map.iter().for_each(|(key, value)| {
+            trace_object(key, visit);
+            trace_object(value, visit);
+        }),
+        Object::ReturnValue(value) => trace_object(value, visit),
+        Object::Composition(first, second) => {
+            trace_object(first, visit);
+            trace_object(second, visit);
+        }
+        Object::Function(_, _, env)
+        | Object::GeneratorFunction(_, _, env)
+        | Object::Macro(_, _, env) => visit(address(env)),
+        Object::Generator(generator) => visit(address(generator)),
+        Object::StructType(ty) => visit(address(ty)),
+        Object::Struct(instance) => visit(address(instance)),
+        Object::Variant(variant) => visit(address(variant)),
+        // Modules are never collected: the module cache keeps them loaded,
+        // so nothing they hold is garbage either.
+        Object::Module(_)
+        | Object::Null
+        | Object::Error(_)
+        | Object::Boolean(_)
+        | Object::Integer(_)
+        | Object::BigInteger(_)
+        | Object::Float(_)
+        | Object::String(_)
+        | Object::Builtin(_)
+        | Object::Quote(_)
+        | Object::EnumType(_)
+        | Object::Constructor(_, _) => {}
+    }
+}
+
+pub fn address<T>(value: &Rc<T>) -> Address {
+    Rc::as_ptr(value) as Address
+}
+
+/// Collecting is skipped until this many values have been tracked, so
+/// short programs never pay for it.
+const MIN_THRESHOLD: usize = 1000;
+
+struct Heap {
+    tracked: Vec<Weak<dyn Trace>>,
+    /// How long `tracked` can get before the next collection is due.
+    threshold: usize,
+}
+
+thread_local! {
+    static HEAP: RefCell<Heap> = RefCell::new(Heap {
+        tracked: Vec::new(),
+        threshold: MIN_THRESHOLD,
+    });
+}
+
+/// Shares `value` and keeps track of it so it can be collected if it ends
+/// up in a cycle.
+pub fn track<T: Trace + 'static>(value: T) -> Rc<T> {
+    let value = Rc::new(value);
+    let weak: Weak<dyn Trace> = Rc::downgrade(&value) as Weak<T>;
+    HEAP.with(|heap| heap.borrow_mut().tracked.push(weak));
+    value
+}
+
+/// Collects if enough values have been tracked since the last collection.
+/// The evaluator calls this between statements and loop iterations, where
+/// it isn't in the middle of changing any tracked value.
+pub fn collect_if_due() {
+    let due = HEAP.with(|heap| {
+        let heap = heap.borrow();
+        heap.tracked.len() >= heap.threshold
+    });
+    if due {
+        collect();
+    }
+}
+
+/// Frees the tracked values that are only reachable from each other, and
+/// returns how many there were.
+///
+/// Every reference the tracked values hold to each other is subtracted
+/// from their reference counts. Whatever has references left over is
+/// referenced from outside, by the Rust stack or a host, and so is in use
+/// along with everything reachable from it. The rest is garbage, which is
+/// cleared so the cycles holding it together fall apart.
+pub fn collect() -> usize {
+    let live: Vec<Rc<dyn Trace>> = HEAP.with(|heap| {
+        let mut heap = heap.borrow_mut();
+        heap.tracked.retain(|value| value.strong_count() > 0);
+        heap.tracked.iter().filter_map(Weak::upgrade).collect()
+    });
+    let garbage = find_garbage(&live);
+    garbage.iter().for_each(|&i| live[i].clear());
+
+    // The next collection waits until the heap has doubled, so the time
+    // spent collecting stays proportional to the values allocated.
+    HEAP.with(|heap| {
+        heap.borrow_mut().threshold = (2 * (live.len() - garbage.len())).max(MIN_THRESHOLD);
+    });
+    garbage.len()
+}
+
+/// The indices of the values in `live` that nothing outside of `live` can
+/// reach. Finds nothing if any of them can't be traced right now.
+fn find_garbage(live: &[Rc<dyn Trace>]) -> Vec<usize> {
+    let index: HashMap<Address, usize> = live
+        .iter()
+        .enumerate()
+        .map(|(i, value)| (Rc::as_ptr(value) as Address, i))
+        .collect();
+
+    // The references to each value from outside, not counting `live` itself.
+    let mut external: Vec<usize> = live.iter().map(|v| Rc::strong_count(v) - 1).collect();
+    for value in live {
+        let traced = value.trace(&mut |child| {
+            if let Some(&i) = index.get(&child) {
+                external[i] -= 1;
+            }
+        });
+        if !traced {
+            return Vec::new();
+        }
+    }
+
+    let mut reachable: Vec<bool> = external.iter().map(|&count| count > 0).collect();
+    let mut pending: Vec<usize> = (0..live.len()).filter(|&i| reachable[i]).collect();
+    while let Some(i) = pending.pop() {
+        live[i].trace(&mut |child| {
+            if let Some(&j) = index.get(&child) {
+                if !reachable[j] {
+                    reachable[j] = true;
+                    pending.push(j);
+                }
+            }
+        });
+    }
+    (0..live.len()).filter(|&i| !reachable[i]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        evaluator::{object::Environment, Eval},
+        lexer::Lexer,
+        parser::Parser,
+    };
+
+    fn eval(input: &str) -> Object {
+        let program = Parser::new(Lexer::new(input)).parse_program();
+        let env = Environment::new().into_env();
+        program.eval(env).unwrap_or(Object::Null)
+    }
+
+    /// How many tracked values are still alive after collecting.
+    fn live_after_collecting() -> usize {
+        collect();
+        HEAP.with(|heap| {
+            let mut heap = heap.borrow_mut();
+            heap.tracked.retain(|value| value.strong_count() > 0);
+            heap.tracked.len()
+        })
+    }
+
+    #[test]
+    fn test_collects_cycles() {
+        let tests = [
+            "let f = fn(n) { if (n < 1) { 0 } else { f(n - 1) } }; f",
+            "let f = fn*() { yield f; }; let g = f(); next(g); g",
+            "struct Node { next }; let n = Node { next: null }; n.next = n; n",
+            "struct Counter { n }; impl Counter { fn get() { self.n } }; Counter",
+            "enum Box { Full(f) }; let b = Box.Full(fn() { b }); b",
+            r#"let a = {"f": fn() { a }}; a"#,
+        ];
+
+        for input in tests {
+            let value = eval(input);
+            let mut references = Vec::new();
+            trace_object(&value, &mut |address| references.push(address));
+            assert!(!references.is_empty(), "{input} holds nothing tracked");
+
+            let live = live_after_collecting();
+            drop(value);
+            assert!(live_after_collecting() < live, "{input} was not collected");
+        }
+    }
+
+    #[test]
+    fn test_keeps_reachable_values() {
+        let env = Environment::new().into_env();
+        let program = Parser::new(Lexer::new(
+            "let count = fn(n) { if (n < 1) { 0 } else { 1 + count(n - 1) } };",
+        ))
+        .parse_program();
+        program.eval(env.clone());
+        collect();
+
+        let program = Parser::new(Lexer::new("count(5)")).parse_program();
+        assert_eq!(program.eval(env).unwrap().inspect(), "5");
+    }
+
+    #[test]
+    fn test_memory_stays_flat() {
+        // Each round defines and drops a recursive closure, which is a cycle
+        // between it and the environment it's stored in.
+        let rounds = |n| {
+            format!(
+                "let go = fn(i) {{
+                    if (i < 1) {{ return 0; }}
+                    let f = fn(n) {{ if (n < 1) {{ 0 }} else {{ f(n - 1) }} }};
+                    f(3);
+                    go(i - 1)
+                }};
+                go({n});"
+            )
+        };
+
+        eval(&rounds(100));
+        let live = live_after_collecting();
+        eval(&rounds(10000));
+        assert_eq!(live_after_collecting(), live);
+
+        let peak = HEAP.with(|heap| heap.borrow().threshold);
+        eval(&rounds(20000));
+        assert!(
+            HEAP.with(|heap| heap.borrow().tracked.len()) <= peak * 2,
+            "values piled up while running"
+        );
+    }
+}
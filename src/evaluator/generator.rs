@@ -0,0 +1,280 @@
+use std::{cell::RefCell, ops::ControlFlow, rc::Rc};
+
+use super::{
+    bind_pattern,
+    gc::{self, Address, Trace},
+    object::{new_error, new_error_with_kind, Env, Environment, ErrorKind, Object},
+    select_arm, Eval,
+};
+use crate::parser::ast::{Expression, Pattern, Statement};
+
+/// A call of a `fn*` function, which runs its body up to each `yield` and
+/// then pauses until asked for the next value.
+///
+/// The evaluator walks the AST recursively, so a paused call can't keep its
+/// place on the Rust stack. Instead the generator keeps its own stack of
+/// frames for the blocks and loops it's inside of, which lets `yield` appear
+/// anywhere a statement can in the body, and in the blocks of any `if`,
+/// `match` or `for` in it.
+#[derive(Debug)]
+pub struct Generator {
+    state: RefCell<State>,
+}
+
+#[derive(Debug)]
+enum State {
+    Suspended(Vec<Frame>),
+    Running,
+    Done,
+}
+
+#[derive(Debug)]
+enum Frame {
+    /// The statements of a block, and the index of the one to run next.
+    Block {
+        statements: Rc<[Statement]>,
+        next: usize,
+        env: Env,
+    },
+    /// A `for` loop, which runs its body once for each value left.
+    Loop {
+        pattern: Pattern,
+        values: Iter,
+        body: Rc<[Statement]>,
+        env: Env,
+    },
+}
+
+impl Frame {
+    fn block(statements: Rc<[Statement]>, env: Env) -> Self {
+        Frame::Block {
+            statements,
+            next: 0,
+            env: Environment::new_enclosed(env).into_env(),
+        }
+    }
+}
+
+/// The statements of a block, or the statement itself if it's on its own.
+fn statements_of(statement: &Statement) -> Rc<[Statement]> {
+    match statement {
+        Statement::Block(statements) => statements.as_slice().into(),
+        statement => Rc::new([statement.clone()]),
+    }
+}
+
+impl Generator {
+    /// A generator that hasn't started running `body` yet. `env` already
+    /// holds the arguments of the call.
+    pub fn new(body: &Statement, env: Env) -> Self {
+        let frame = Frame::block(statements_of(body), env);
+        Self {
+            state: RefCell::new(State::Suspended(vec![frame])),
+        }
+    }
+
+    /// Whether the body has run to its end, or failed.
+    pub fn is_done(&self) -> bool {
+        matches!(*self.state.borrow(), State::Done)
+    }
+
+    /// Runs the body until the next `yield` and returns the yielded value.
+    /// Returns `None` once the body has finished, and an error if running it
+    /// failed, after which the generator is finished too.
+    pub fn resume(&self) -> Option<Object> {
+        let mut frames = match self.state.replace(State::Running) {
+            State::Suspended(frames) => frames,
+            State::Running => return Some(new_error("generator is already running".to_string())),
+            State::Done => {
+                self.state.replace(State::Done);
+                return None;
+            }
+        };
+
+        let result = run(&mut frames);
+        let state = match result {
+            Some(Object::Error(_)) | None => State::Done,
+            Some(_) => State::Suspended(frames),
+        };
+        self.state.replace(state);
+        result
+    }
+}
+
+impl Trace for Generator {
+    fn trace(&self, visit: &mut dyn FnMut(Address)) -> bool {
+        let Ok(state) = self.state.try_borrow() else {
+            return false;
+        };
+        // A running generator's frames are on the Rust stack, where they
+        // count as references from outside.
+        let State::Suspended(frames) = &*state else {
+            return true;
+        };
+        for frame in frames {
+            match frame {
+                Frame::Block { env, .. } => visit(gc::address(env)),
+                Frame::Loop { values, env, .. } => {
+                    match values {
+                        Iter::Items(items) => items
+                            .as_slice()
+                            .iter()
+                            .for_each(|item| gc::trace_object(item, visit)),
+                        Iter::Generator(generator) => visit(gc::address(generator)),
+                    }
+                    visit(gc::address(env));
+                }
+            }
+        }
+        true
+    }
+
+    fn clear(&self) {
+        if let Ok(mut state) = self.state.try_borrow_mut() {
+            *state = State::Done;
+        }
+    }
+}
+
+fn run(frames: &mut Vec<Frame>) -> Option<Object> {
+    while let Some(frame) = frames.last_mut() {
+        let step = match frame {
+            Frame::Block {
+                statements,
+                next,
+                env,
+            } if *next < statements.len() => {
+                *next += 1;
+                let (statements, index, env) = (statements.clone(), *next - 1, env.clone());
+                execute(&statements[index], env, frames)
+            }
+            Frame::Block { .. } => {
+                frames.pop();
+                continue;
+            }
+            Frame::Loop {
+                pattern,
+                values,
+                body,
+                env,
+            } => match values.next() {
+                None => {
+                    frames.pop();
+                    continue;
+                }
+                Some(error @ Object::Error(_)) => return Some(error),
+                Some(value) => {
+                    let mut loop_env = Environment::new_enclosed(env.clone());
+                    if let Err(e) = bind_pattern(pattern, value, &mut loop_env) {
+                        return Some(e);
+                    }
+                    let frame = Frame::block(body.clone(), loop_env.into_env());
+                    frames.push(frame);
+                    continue;
+                }
+            },
+        };
+
+        if let ControlFlow::Break(result) = step {
+            return result;
+        }
+    }
+    None
+}
+
+/// Runs one statement of a generator. Statements that can hold a `yield`
+/// push frames for their blocks, everything else runs straight through.
+/// Breaks with the value to yield or an error, or with `None` when the
+/// generator returns.
+fn execute(
+    statement: &Statement,
+    env: Env,
+    frames: &mut Vec<Frame>,
+) -> ControlFlow<Option<Object>> {
+    match statement {
+        Statement::Yield(value) => {
+            return ControlFlow::Break(Some(value.eval(env).unwrap_or(Object::Null)));
+        }
+        Statement::Return(value) => {
+            let value = value.eval(env).unwrap_or(Object::Null);
+            return ControlFlow::Break(value.is_error().then_some(value));
+        }
+        Statement::Block(_) => frames.push(Frame::block(statements_of(statement), env)),
+        Statement::For(pattern, iterable, body) => {
+            let iterable = iterable.eval(env.clone()).unwrap_or(Object::Null);
+            let values = match Iter::new(iterable) {
+                Ok(values) => values,
+                Err(e) => return ControlFlow::Break(Some(e)),
+            };
+            frames.push(Frame::Loop {
+                pattern: pattern.clone(),
+                values,
+                body: statements_of(body),
+                env,
+            });
+        }
+        Statement::Expression(Expression::If(condition, consequence, alternative)) => {
+            let condition = condition.eval(env.clone()).unwrap_or(Object::Null);
+            if condition.is_error() {
+                return ControlFlow::Break(Some(condition));
+            }
+            if condition.is_truthy() {
+                return execute(consequence, env, frames);
+            }
+            if let Some(alternative) = alternative {
+                return execute(alternative, env, frames);
+            }
+        }
+        Statement::Expression(Expression::Match(value, arms)) => {
+            match select_arm(value, arms, env) {
+                Ok(Some((body, arm_env))) => return execute(body, arm_env, frames),
+                Ok(None) => {}
+                Err(e) => return ControlFlow::Break(Some(e)),
+            }
+        }
+        statement => match statement.eval(env) {
+            Some(error @ Object::Error(_)) => return ControlFlow::Break(Some(error)),
+            Some(Object::ReturnValue(_)) => return ControlFlow::Break(None),
+            _ => {}
+        },
+    }
+    ControlFlow::Continue(())
+}
+
+/// The values a `for` loop goes through: the elements of an array, the
+/// characters of a string, or whatever a generator yields.
+#[derive(Debug)]
+pub enum Iter {
+    Items(std::vec::IntoIter<Object>),
+    Generator(Rc<Generator>),
+}
+
+impl Iter {
+    pub fn new(iterable: Object) -> Result<Self, Object> {
+        match iterable {
+            Object::Array(elements) => Ok(Iter::Items(elements.into_iter())),
+            Object::String(string) => {
+                let chars = string.chars().map(|c| Object::String(c.to_string()));
+                Ok(Iter::Items(chars.collect::<Vec<_>>().into_iter()))
+            }
+            Object::Generator(generator) => Ok(Iter::Generator(generator)),
+            error @ Object::Error(_) => Err(error),
+            got => Err(new_error_with_kind(
+                ErrorKind::Type,
+                format!("cannot iterate over {got}"),
+            )),
+        }
+    }
+}
+
+impl Iterator for Iter {
+    type Item = Object;
+
+    /// The next value, or an error if producing it failed.
+    fn next(&mut self) -> Option<Object> {
+        match self {
+            Iter::Items(items) => items.next(),
+            Iter::Generator(generator) => generator.resume(),
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use super::{
+    extend_function_env,
+    object::{new_error_with_kind, Env, ErrorKind, Object},
+    Eval,
+};
+use crate::parser::ast::{modify::modify, Expression, Pattern, Program, Statement};
+
+/// Moves every top level `let name = macro(...) { ... };` out of `program`
+/// and binds it in `env`, so later calls to `name` can be expanded.
+pub fn define_macros(program: &mut Program, env: &Env) -> Result<(), Object> {
+    let mut result = Ok(());
+    program.statements.retain(|statement| {
+        let Statement::Let(Pattern::Identifier(name), Expression::Macro(params, body)) = statement
+        else {
+            return true;
+        };
+        let definition = Object::Macro(params.clone(), body.clone(), env.clone());
+        if let Err(e) = env.borrow_mut().set(name.clone(), definition) {
+            result = Err(e);
+        }
+        false
+    });
+    result
+}
+
+/// Replaces every call to a macro defined in `env` with the quoted
+/// expression the macro returns. Arguments are passed to the macro quoted,
+/// without evaluating them.
+pub fn expand_macros(program: Program, env: &Env) -> Result<Program, Object> {
+    let mut error = None;
+    let expanded = modify(program, &mut |expression| {
+        let Expression::Call(function, args) = &expression else {
+            return expression;
+        };
+        let Expression::Identifier(name) = &**function else {
+            return expression;
+        };
+        let Some(Object::Macro(params, body, macro_env)) = env.borrow().get(name) else {
+            return expression;
+        };
+        if error.is_some() {
+            return expression;
+        }
+
+        let args: Vec<_> = args.iter().cloned().map(Object::Quote).collect();
+        match expand_macro(&params, &body, macro_env, &args) {
+            Ok(node) => node,
+            Err(e) => {
+                error = Some(e);
+                expression
+            }
+        }
+    });
+
+    match error {
+        Some(e) => Err(e),
+        None => Ok(expanded),
+    }
+}
+
+fn expand_macro(
+    params: &[Pattern],
+    body: &Statement,
+    env: Env,
+    args: &[Object],
+) -> Result<Expression, Object> {
+    let extended_env = extend_function_env(env, params, args)?;
+    let evaluated = match body.eval(extended_env) {
+        Some(Object::ReturnValue(value)) => *value,
+        evaluated => evaluated.unwrap_or(Object::Null),
+    };
+
+    match evaluated {
+        Object::Quote(node) => Ok(node),
+        error @ Object::Error(_) => Err(error),
+        other => Err(new_error_with_kind(
+            ErrorKind::Type,
+            format!("macros must return a quoted expression, got {other}"),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        evaluator::object::Environment,
+        lexer::Lexer,
+        parser::{ast::Identifier, Parser},
+    };
+
+    fn parse(input: &str) -> Program {
+        let mut parser = Parser::new(Lexer::new(input));
+        let program = parser.parse_program();
+        assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+        program
+    }
+
+    fn expand(input: &str) -> Result<Program, Object> {
+        let env = Environment::new().into_env();
+        let mut program = parse(input);
+        define_macros(&mut program, &env)?;
+        expand_macros(program, &env)
+    }
+
+    #[test]
+    fn test_define_macros() {
+        let input = r#"
+let number = 1;
+let function = fn(x, y) { x + y };
+let mymacro = macro(x, y) { x + y; };
+"#;
+        let env = Environment::new().into_env();
+        let mut program = parse(input);
+        define_macros(&mut program, &env).unwrap();
+
+        assert_eq!(program.statements.len(), 2);
+        let env = env.borrow();
+        assert!(env.get(&Identifier("number".into())).is_none());
+        assert!(env.get(&Identifier("function".into())).is_none());
+
+        let Some(Object::Macro(params, body, _)) = env.get(&Identifier("mymacro".into())) else {
+            panic!("mymacro is not a macro");
+        };
+        assert_eq!(params.len(), 2);
+        assert_eq!(params[0].to_string(), "x");
+        assert_eq!(params[1].to_string(), "y");
+        assert_eq!(body.to_string(), "(x + y)");
+    }
+
+    #[test]
+    fn test_expand_macros() {
+        let inputs = vec![
+            (
+                "let infixExpression = macro() { quote(1 + 2); }; infixExpression();",
+                "(1 + 2)",
+            ),
+            (
+                "let reverse = macro(a, b) { quote(unquote(b) - unquote(a)); }; reverse(2 + 2, 10 - 5);",
+                "(10 - 5) - (2 + 2)",
+            ),
+            (
+                r#"
+let unless = macro(condition, consequence, alternative) {
+    quote(if (!(unquote(condition))) {
+        unquote(consequence);
+    } else {
+        unquote(alternative);
+    });
+};
+unless(10 > 5, puts("not greater"), puts("greater"));
+"#,
+                r#"if (!(10 > 5)) { puts("not greater") } else { puts("greater") }"#,
+            ),
+            (
+                "let early = macro(x) { return quote(unquote(x) * 2); }; fn() { early(y) }",
+                "fn() { y * 2 }",
+            ),
+        ];
+
+        for (input, expected) in inputs {
+            let expanded = expand(input).unwrap();
+            assert_eq!(expanded.to_string(), parse(expected).to_string(), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_expand_macro_errors() {
+        let inputs = vec![
+            (
+                "let m = macro() { 1 }; m();",
+                "macros must return a quoted expression, got INTEGER",
+            ),
+            (
+                "let m = macro() { missing }; m();",
+                "identifier not found: missing",
+            ),
+            (
+                "let m = macro(a, b) { quote(a) }; m(1);",
+                "wrong number of arguments. got=1, want=2",
+            ),
+        ];
+
+        for (input, expected) in inputs {
+            match expand(input) {
+                Err(Object::Error(error)) => assert_eq!(error.message, expected),
+                other => panic!(
+                    "expected an error from {input}, got {:?}",
+                    other.map(|program| program.to_string())
+                ),
+            }
+        }
+    }
+}
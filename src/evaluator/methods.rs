@@ -0,0 +1,256 @@
+use super::{
+    bigint::BigInt,
+    builtins::{
+        collect, done, first, float, is_wrong_arg_amount, last, len, next, push, rest, take,
+        BuiltinFunction,
+    },
+    object::{new_error_with_kind, ErrorKind, Object},
+};
+
+/// Looks up a method that every value of the receiver's type has, for
+/// `value.name(args)`. Methods are builtins that get the receiver as their
+/// first argument, so free functions like `len` can be reused as they are.
+pub fn get_method(receiver: &Object, name: &str) -> Option<BuiltinFunction> {
+    let method: BuiltinFunction = match (receiver, name) {
+        (Object::Array(_), "len") => len,
+        (Object::Array(_), "first") => first,
+        (Object::Array(_), "last") => last,
+        (Object::Array(_), "rest") => rest,
+        (Object::Array(_), "push") => push,
+        (Object::Array(_), "reverse") => reverse,
+        (Object::Array(_), "join") => join,
+        (Object::Array(_), "contains") => contains,
+
+        (Object::String(_), "len") => len,
+        (Object::String(_), "upper") => upper,
+        (Object::String(_), "lower") => lower,
+        (Object::String(_), "trim") => trim,
+        (Object::String(_), "split") => split,
+        (Object::String(_), "contains") => contains,
+
+        (Object::Hash(_), "len") => hash_len,
+        (Object::Hash(_), "keys") => keys,
+        (Object::Hash(_), "values") => values,
+        (Object::Hash(_), "contains") => contains,
+
+        (Object::Integer(_) | Object::BigInteger(_), "abs") => abs,
+        (Object::Integer(_) | Object::BigInteger(_), "float") => float,
+        (Object::Integer(_) | Object::BigInteger(_), "to_string") => to_string,
+
+        (Object::Generator(_), "next") => next,
+        (Object::Generator(_), "done") => done,
+        (Object::Generator(_), "collect") => collect,
+        (Object::Generator(_), "take") => take,
+        _ => return None,
+    };
+    Some(method)
+}
+
+fn reverse(args: &[Object]) -> Object {
+    if let Some(error) = is_wrong_arg_amount(args.len(), 1) {
+        return error;
+    }
+
+    match &args[0] {
+        Object::Array(arr) => Object::Array(arr.iter().rev().cloned().collect()),
+        got => new_error_with_kind(
+            ErrorKind::Type,
+            format!("argument to `reverse` must be ARRAY, got {got}"),
+        ),
+    }
+}
+
+fn join(args: &[Object]) -> Object {
+    if let Some(error) = is_wrong_arg_amount(args.len(), 2) {
+        return error;
+    }
+
+    match (&args[0], &args[1]) {
+        (Object::Array(arr), Object::String(separator)) => Object::String(
+            arr.iter()
+                .map(|element| element.inspect())
+                .collect::<Vec<_>>()
+                .join(separator),
+        ),
+        (Object::Array(_), got) => new_error_with_kind(
+            ErrorKind::Type,
+            format!("separator for `join` must be STRING, got {got}"),
+        ),
+        (got, _) => new_error_with_kind(
+            ErrorKind::Type,
+            format!("argument to `join` must be ARRAY, got {got}"),
+        ),
+    }
+}
+
+/// Whether an array has an element, a string has a substring or a hash has
+/// a key.
+fn contains(args: &[Object]) -> Object {
+    if let Some(error) = is_wrong_arg_amount(args.len(), 2) {
+        return error;
+    }
+
+    match (&args[0], &args[1]) {
+        (Object::Array(arr), element) => arr.contains(element).into(),
+        (Object::String(string), Object::String(substring)) => {
+            string.contains(substring.as_str()).into()
+        }
+        (Object::String(_), got) => new_error_with_kind(
+            ErrorKind::Type,
+            format!("argument to `contains` on a STRING must be STRING, got {got}"),
+        ),
+        (Object::Hash(map), key) => map.contains_key(key).into(),
+        (got, _) => new_error_with_kind(
+            ErrorKind::Type,
+            format!("argument to `contains` not supported, got {got}"),
+        ),
+    }
+}
+
+fn map_string(args: &[Object], name: &str, f: fn(&str) -> String) -> Object {
+    if let Some(error) = is_wrong_arg_amount(args.len(), 1) {
+        return error;
+    }
+
+    match &args[0] {
+        Object::String(string) => Object::String(f(string)),
+        got => new_error_with_kind(
+            ErrorKind::Type,
+            format!("argument to `{name}` must be STRING, got {got}"),
+        ),
+    }
+}
+
+fn upper(args: &[Object]) -> Object {
+    map_string(args, "upper", str::to_uppercase)
+}
+
+fn lower(args: &[Object]) -> Object {
+    map_string(args, "lower", str::to_lowercase)
+}
+
+fn trim(args: &[Object]) -> Object {
+    map_string(args, "trim", |string| string.trim().to_string())
+}
+
+fn split(args: &[Object]) -> Object {
+    if let Some(error) = is_wrong_arg_amount(args.len(), 2) {
+        return error;
+    }
+
+    match (&args[0], &args[1]) {
+        (Object::String(string), Object::String(separator)) => Object::Array(
+            string
+                .split(separator.as_str())
+                .map(|part| Object::String(part.to_string()))
+                .collect(),
+        ),
+        (Object::String(_), got) => new_error_with_kind(
+            ErrorKind::Type,
+            format!("separator for `split` must be STRING, got {got}"),
+        ),
+        (got, _) => new_error_with_kind(
+            ErrorKind::Type,
+            format!("argument to `split` must be STRING, got {got}"),
+        ),
+    }
+}
+
+fn hash_len(args: &[Object]) -> Object {
+    if let Some(error) = is_wrong_arg_amount(args.len(), 1) {
+        return error;
+    }
+
+    match &args[0] {
+        Object::Hash(map) => Object::Integer(map.len() as i64),
+        got => new_error_with_kind(
+            ErrorKind::Type,
+            format!("argument to `len` not supported, got {got}"),
+        ),
+    }
+}
+
+fn hash_entries(args: &[Object], name: &str, keys: bool) -> Object {
+    if let Some(error) = is_wrong_arg_amount(args.len(), 1) {
+        return error;
+    }
+
+    match &args[0] {
+        Object::Hash(map) => {
+            let entries = map
+                .iter()
+                .map(|(key, value)| if keys { key } else { value });
+            Object::Array(entries.cloned().collect())
+        }
+        got => new_error_with_kind(
+            ErrorKind::Type,
+            format!("argument to `{name}` must be HASH, got {got}"),
+        ),
+    }
+}
+
+fn keys(args: &[Object]) -> Object {
+    hash_entries(args, "keys", true)
+}
+
+fn values(args: &[Object]) -> Object {
+    hash_entries(args, "values", false)
+}
+
+fn abs(args: &[Object]) -> Object {
+    if let Some(error) = is_wrong_arg_amount(args.len(), 1) {
+        return error;
+    }
+
+    match &args[0] {
+        Object::Integer(int) => match int.checked_abs() {
+            Some(abs) => Object::Integer(abs),
+            None => (-&BigInt::from(*int)).into(),
+        },
+        Object::BigInteger(int) if int < &BigInt::from(0) => (-int).into(),
+        Object::BigInteger(_) => args[0].clone(),
+        got => new_error_with_kind(
+            ErrorKind::Type,
+            format!("argument to `abs` must be INTEGER, got {got}"),
+        ),
+    }
+}
+
+fn to_string(args: &[Object]) -> Object {
+    if let Some(error) = is_wrong_arg_amount(args.len(), 1) {
+        return error;
+    }
+
+    Object::String(args[0].inspect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_method_tables() {
+        let array = Object::Array(vec![Object::Integer(1), Object::Integer(2)]);
+        let string = Object::String(" Monkey ".to_string());
+
+        assert!(get_method(&array, "push").is_some());
+        assert!(get_method(&array, "upper").is_none());
+        assert!(get_method(&string, "upper").is_some());
+        assert!(get_method(&Object::Integer(1), "abs").is_some());
+        assert!(get_method(&Object::Boolean(true), "len").is_none());
+
+        let cases = vec![
+            (reverse(std::slice::from_ref(&array)), "[2, 1]"),
+            (join(&[array.clone(), Object::String("-".into())]), "1-2"),
+            (contains(&[array, Object::Integer(2)]), "true"),
+            (trim(std::slice::from_ref(&string)), "Monkey"),
+            (upper(std::slice::from_ref(&string)), " MONKEY "),
+            (split(&[string, Object::String("n".into())]), "[ Mo, key ]"),
+            (abs(&[Object::Integer(i64::MIN)]), "9223372036854775808"),
+            (abs(&[Object::Integer(-3)]), "3"),
+        ];
+        for (result, expected) in cases {
+            assert_eq!(result.inspect(), expected);
+        }
+    }
+}
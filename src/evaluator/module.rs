@@ -0,0 +1,358 @@
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use super::{
+    macro_expansion::{define_macros, expand_macros},
+    object::{new_error_with_kind, Env, Environment, ErrorKind, Object},
+    Eval,
+};
+use crate::{
+    lexer::Lexer,
+    parser::{
+        ast::{Identifier, Program, Statement},
+        Parser,
+    },
+};
+
+/// A file that has been imported. Only the names it exports can be read
+/// through it.
+#[derive(Debug)]
+pub struct Module {
+    pub path: PathBuf,
+    env: Env,
+    exports: Vec<Identifier>,
+}
+
+impl Module {
+    pub fn get(&self, name: &str) -> Option<Object> {
+        self.exports
+            .iter()
+            .find(|export| export.0 == name)
+            .and_then(|export| self.env.borrow().get(export))
+    }
+}
+
+/// Every module loaded so far, shared by all environments that descend from
+/// the same root so each file is only evaluated once.
+#[derive(Default)]
+pub struct ModuleCache {
+    loaded: HashMap<PathBuf, Rc<Module>>,
+    /// The modules currently being evaluated, outermost first.
+    loading: Vec<PathBuf>,
+}
+
+// Modules hold on to their environments, which point back at the cache, so
+// a derived `Debug` would never finish.
+impl Debug for ModuleCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ModuleCache")
+            .field("loaded", &self.loaded.keys().collect::<Vec<_>>())
+            .field("loading", &self.loading)
+            .finish()
+    }
+}
+
+/// Loads the module at `path`, relative to the file `env` belongs to, or
+/// returns it straight from the cache if it was already imported.
+pub fn import_module(path: &str, env: &Env) -> Result<Object, Object> {
+    let (directory, modules) = {
+        let env = env.borrow();
+        (env.directory(), env.modules())
+    };
+
+    let joined = directory.join(path);
+    let path = fs::canonicalize(&joined)
+        .map_err(|e| import_error(format!("cannot import {}: {e}", joined.display())))?;
+
+    if let Some(module) = modules.borrow().loaded.get(&path) {
+        return Ok(Object::Module(module.clone()));
+    }
+    check_cycle(&modules.borrow().loading, &path)?;
+
+    let source = fs::read_to_string(&path)
+        .map_err(|e| import_error(format!("cannot import {}: {e}", path.display())))?;
+    let mut parser = Parser::new(Lexer::new(&source));
+    let mut program = parser.parse_program();
+    if !parser.errors().is_empty() {
+        return Err(import_error(format!(
+            "cannot import {}: {}",
+            path.display(),
+            parser.errors().join("; ")
+        )));
+    }
+
+    let macro_env = Environment::new().into_env();
+    define_macros(&mut program, &macro_env)?;
+    let program = expand_macros(program, &macro_env)?;
+
+    let module_env = Environment::new_for_file(&path, modules.clone()).into_env();
+    if let Some(error @ Object::Error(_)) = eval_file(&path, &program, module_env) {
+        return Err(error);
+    }
+    let module = modules.borrow().loaded[&path].clone();
+    Ok(Object::Module(module))
+}
+
+/// Runs `program`, the contents of the file at `path`, in `env`. The file
+/// counts as loading while it runs, so a module importing it back is
+/// reported as a cycle, and is cached once it finishes without an error.
+///
+/// This is also how the script a run starts from is evaluated, so importing
+/// it from one of its modules doesn't run it a second time.
+pub fn eval_file(path: &Path, program: &Program, env: Env) -> Option<Object> {
+    let modules = env.borrow().modules();
+    modules.borrow_mut().loading.push(path.to_path_buf());
+    let evaluated = program.eval(env.clone());
+    modules.borrow_mut().loading.pop();
+    if let Some(Object::Error(_)) = evaluated {
+        return evaluated;
+    }
+
+    let exports = program
+        .statements
+        .iter()
+        .flat_map(Statement::exported_identifiers)
+        .cloned()
+        .collect();
+    let module = Rc::new(Module {
+        path: path.to_path_buf(),
+        env,
+        exports,
+    });
+    modules
+        .borrow_mut()
+        .loaded
+        .insert(path.to_path_buf(), module);
+    evaluated
+}
+
+fn check_cycle(loading: &[PathBuf], path: &Path) -> Result<(), Object> {
+    let Some(start) = loading.iter().position(|loading| loading == path) else {
+        return Ok(());
+    };
+    let cycle = loading[start..]
+        .iter()
+        .map(PathBuf::as_path)
+        .chain([path])
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(" -> ");
+    Err(import_error(format!("import cycle: {cycle}")))
+}
+
+fn import_error(reason: String) -> Object {
+    new_error_with_kind(ErrorKind::Import, reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluator::object::RuntimeError;
+
+    /// Writes `files` into a fresh directory and runs `main.mk` from it.
+    fn run(test_name: &str, files: &[(&str, &str)]) -> (PathBuf, Option<Object>) {
+        let dir = std::env::temp_dir().join(format!("monkey-{}-{test_name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        for (name, source) in files {
+            let path = dir.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, source).unwrap();
+        }
+
+        let main = fs::canonicalize(dir.join("main.mk")).unwrap();
+        let source = fs::read_to_string(&main).unwrap();
+        let program = Parser::new(Lexer::new(&source)).parse_program();
+        let env = Environment::new_for_file(&main, Rc::default()).into_env();
+        let evaluated = eval_file(&main, &program, env);
+
+        let canonical = fs::canonicalize(&dir).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+        (canonical, evaluated)
+    }
+
+    fn expect_error(evaluated: Option<Object>) -> Box<RuntimeError> {
+        match evaluated {
+            Some(Object::Error(error)) => error,
+            other => panic!("expected an error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_import_exports() {
+        let (_, evaluated) = run(
+            "exports",
+            &[
+                (
+                    "main.mk",
+                    r#"import "lib/math.mk" as math; [math.square(3), math["pi"], math.cube(2)]"#,
+                ),
+                (
+                    "lib/math.mk",
+                    r#"
+import "util.mk" as util;
+export let square = fn(x) { x * x };
+export const pi = 3;
+export let [cube] = [fn(x) { util.times(x, square(x)) }];
+let hidden = 1;
+"#,
+                ),
+                ("lib/util.mk", "export let times = fn(a, b) { a * b };"),
+            ],
+        );
+
+        let Some(Object::Array(values)) = evaluated else {
+            panic!("expected an array, got {evaluated:?}");
+        };
+        let values: Vec<_> = values.iter().map(Object::inspect).collect();
+        assert_eq!(values, vec!["9", "3", "8"]);
+    }
+
+    #[test]
+    fn test_unexported_names_are_hidden() {
+        let (dir, evaluated) = run(
+            "hidden",
+            &[
+                ("main.mk", r#"import "lib.mk" as lib; lib.hidden"#),
+                ("lib.mk", "let hidden = 1; export let shown = 2;"),
+            ],
+        );
+
+        let error = expect_error(evaluated);
+        assert_eq!(error.kind, ErrorKind::Key);
+        assert_eq!(
+            error.message,
+            format!(
+                "module {} has no export hidden",
+                dir.join("lib.mk").display()
+            )
+        );
+    }
+
+    #[test]
+    fn test_modules_are_evaluated_once() {
+        let (_, evaluated) = run(
+            "cached",
+            &[
+                (
+                    "main.mk",
+                    r#"import "a.mk" as a; import "b.mk" as b; [a.shared, b.shared, a.shared.value]"#,
+                ),
+                ("a.mk", r#"import "shared.mk" as s; export let shared = s;"#),
+                (
+                    "b.mk",
+                    r#"import "./shared.mk" as s; export let shared = s;"#,
+                ),
+                ("shared.mk", "export let value = 7;"),
+            ],
+        );
+
+        let Some(Object::Array(values)) = evaluated else {
+            panic!("expected an array, got {evaluated:?}");
+        };
+        let (Object::Module(first), Object::Module(second)) = (&values[0], &values[1]) else {
+            panic!("expected two modules, got {values:?}");
+        };
+        assert!(Rc::ptr_eq(first, second));
+        assert_eq!(values[2].inspect(), "7");
+    }
+
+    #[test]
+    fn test_import_cycles() {
+        let (dir, evaluated) = run(
+            "cycle",
+            &[
+                ("main.mk", r#"import "a.mk" as a;"#),
+                ("a.mk", r#"import "b.mk" as b;"#),
+                ("b.mk", r#"import "a.mk" as a;"#),
+            ],
+        );
+
+        let error = expect_error(evaluated);
+        assert_eq!(error.kind, ErrorKind::Import);
+        let a = dir.join("a.mk");
+        let b = dir.join("b.mk");
+        assert_eq!(
+            error.message,
+            format!(
+                "import cycle: {} -> {} -> {}",
+                a.display(),
+                b.display(),
+                a.display()
+            )
+        );
+    }
+
+    #[test]
+    fn test_importing_the_main_file() {
+        let (dir, evaluated) = run(
+            "main-cycle",
+            &[
+                ("main.mk", r#"import "a.mk" as a; puts("main runs");"#),
+                ("a.mk", r#"import "main.mk" as main;"#),
+            ],
+        );
+
+        // Without main counting as loading, a would import and run main
+        // again, and only the second import of a would be caught.
+        let error = expect_error(evaluated);
+        let main = dir.join("main.mk");
+        assert_eq!(
+            error.message,
+            format!(
+                "import cycle: {} -> {} -> {}",
+                main.display(),
+                dir.join("a.mk").display(),
+                main.display()
+            )
+        );
+    }
+
+    #[test]
+    fn test_import_errors() {
+        let (_, evaluated) = run("missing", &[("main.mk", r#"import "nope.mk" as nope;"#)]);
+        let error = expect_error(evaluated);
+        assert_eq!(error.kind, ErrorKind::Import);
+        assert!(
+            error.message.starts_with("cannot import "),
+            "{}",
+            error.message
+        );
+
+        let (dir, evaluated) = run(
+            "unparsable",
+            &[
+                ("main.mk", r#"import "bad.mk" as bad;"#),
+                ("bad.mk", "let = 1;"),
+            ],
+        );
+        let error = expect_error(evaluated);
+        assert_eq!(error.kind, ErrorKind::Import);
+        assert_eq!(
+            error.message,
+            format!(
+                "cannot import {}: expected a pattern, got: =",
+                dir.join("bad.mk").display()
+            )
+        );
+
+        let (_, evaluated) = run(
+            "failing",
+            &[
+                (
+                    "main.mk",
+                    r#"try { import "fail.mk" as fail; } catch (e) { e["kind"] }"#,
+                ),
+                ("fail.mk", "export let broken = 1 / 0;"),
+            ],
+        );
+        assert_eq!(
+            evaluated.map(|e| e.inspect()),
+            Some("ZeroDivisionError".into())
+        );
+    }
+}
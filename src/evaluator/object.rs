@@ -3,11 +3,23 @@ use std::{
     collections::{hash_map::DefaultHasher, HashMap},
     fmt::{Debug, Display},
     hash::{Hash, Hasher},
+    path::{Path, PathBuf},
     rc::Rc,
 };
 
-use super::builtins::BuiltinFunction;
-use crate::parser::ast::{Expression, Identifier, Statement};
+use super::{
+    bigint::BigInt,
+    builtins::BuiltinFunction,
+    enums::{self, EnumType, Variant},
+    gc::{self, Address, Trace},
+    generator::Generator,
+    module::{Module, ModuleCache},
+    structs::{StructInstance, StructType},
+};
+use crate::{
+    lexer::token::Position,
+    parser::ast::{source, Expression, Identifier, Pattern, Statement},
+};
 
 pub const TRUE: Object = Object::Boolean(true);
 pub const FALSE: Object = Object::Boolean(false);
@@ -15,24 +27,50 @@ pub const FALSE: Object = Object::Boolean(false);
 #[derive(Clone)]
 pub enum Object {
     Null,
-    Error(String),
+    Error(Box<RuntimeError>),
     Boolean(bool),
     Integer(i64),
+    /// An integer outside the `i64` range. Arithmetic only produces this
+    /// when a result overflows, and demotes back to `Integer` when it fits.
+    BigInteger(BigInt),
+    Float(f64),
     String(String),
     Array(Vec<Object>),
     Hash(HashMap<Object, Object>),
     ReturnValue(Box<Object>),
     Builtin(BuiltinFunction),
-    Function(Vec<Expression>, Statement, Env),
+    /// A function and the environment it closes over. Copies of it share
+    /// the parameters and body.
+    Function(Rc<[Pattern]>, Rc<Statement>, Env),
+    /// A `fn*` function, which returns a generator when called.
+    GeneratorFunction(Rc<[Pattern]>, Rc<Statement>, Env),
+    /// A paused call of a generator function. Cloning it shares its place.
+    Generator(Rc<Generator>),
+    Module(Rc<Module>),
+    /// An unevaluated expression, produced by `quote`.
+    Quote(Expression),
+    Macro(Vec<Pattern>, Box<Statement>, Env),
+    StructType(Rc<StructType>),
+    /// An instance of a struct. Cloning it shares the same fields.
+    Struct(Rc<StructInstance>),
+    EnumType(Rc<EnumType>),
+    /// A value built from an enum variant.
+    Variant(Rc<Variant>),
+    /// The function that builds the enum variant with the given index.
+    Constructor(Rc<EnumType>, usize),
+    /// `f >> g`, which passes its arguments to `f` and the result to `g`.
+    Composition(Box<Object>, Box<Object>),
 }
 
 impl Object {
     pub fn inspect(&self) -> String {
         match self {
             Object::Null => "null".to_string(),
-            Object::Error(message) => format!("ERROR: {message}"),
+            Object::Error(error) => format!("ERROR: {}", error.message),
             Object::Boolean(bool) => bool.to_string(),
             Object::Integer(int) => int.to_string(),
+            Object::BigInteger(int) => int.to_string(),
+            Object::Float(float) => format!("{float:?}"),
             Object::String(string) => string.clone(),
             Object::Array(elements) => {
                 let elements = elements
@@ -52,13 +90,19 @@ impl Object {
             }
             Object::ReturnValue(value) => value.to_string(),
             Object::Builtin(_) => "builtin function".to_string(),
-            Object::Function(params, body, _) => {
-                let params = params
-                    .iter()
-                    .map(|p| p.to_string())
-                    .collect::<Vec<_>>()
-                    .join(", ");
-                format!("fn({params}) {{\n{body}\n}}")
+            Object::Function(params, body, _) => source::function(params, body),
+            Object::GeneratorFunction(params, body, _) => source::generator(params, body),
+            Object::Generator(_) => "<generator>".to_string(),
+            Object::Module(module) => format!("<module {}>", module.path.display()),
+            Object::Quote(node) => format!("QUOTE({node})"),
+            Object::Macro(params, body, _) => source::macro_definition(params, body),
+            Object::StructType(ty) => ty.inspect(),
+            Object::Struct(instance) => instance.inspect(),
+            Object::EnumType(ty) => ty.inspect(),
+            Object::Variant(variant) => variant.inspect(),
+            Object::Constructor(ty, tag) => enums::inspect_constructor(ty, *tag),
+            Object::Composition(first, second) => {
+                format!("{} >> {}", first.inspect(), second.inspect())
             }
         }
     }
@@ -71,12 +115,32 @@ impl Object {
         matches!(self, Object::Error(_))
     }
 
-    pub fn is_hashable(&self) -> bool {
+    pub fn is_callable(&self) -> bool {
         matches!(
             self,
-            Object::String(_) | Object::Integer(_) | Object::Boolean(_)
+            Object::Function(_, _, _)
+                | Object::GeneratorFunction(_, _, _)
+                | Object::Builtin(_)
+                | Object::Constructor(_, _)
+                | Object::Composition(_, _)
         )
     }
+
+    pub fn is_hashable(&self) -> bool {
+        match self {
+            Object::String(_) | Object::Integer(_) | Object::BigInteger(_) | Object::Boolean(_) => {
+                true
+            }
+            Object::Variant(variant) => variant.payload.iter().all(Object::is_hashable),
+            // Arrays and hashes are copied rather than shared, so one used as
+            // a key can't change under the map holding it.
+            Object::Array(elements) => elements.iter().all(Object::is_hashable),
+            Object::Hash(map) => map
+                .iter()
+                .all(|(key, value)| key.is_hashable() && value.is_hashable()),
+            _ => false,
+        }
+    }
 }
 
 impl Display for Object {
@@ -85,13 +149,25 @@ impl Display for Object {
             Object::Null => "NULL",
             Object::Error(_) => "ERROR",
             Object::Boolean(_) => "BOOLEAN",
-            Object::Integer(_) => "INTEGER",
+            Object::Integer(_) | Object::BigInteger(_) => "INTEGER",
+            Object::Float(_) => "FLOAT",
             Object::String(_) => "STRING",
             Object::Array(_) => "ARRAY",
             Object::Hash(_) => "HASH",
             Object::ReturnValue(_) => "RETURN_VALUE",
             Object::Builtin(_) => "BUILTIN",
-            Object::Function(_, _, _) => "FUNCTION",
+            Object::Function(_, _, _)
+            | Object::GeneratorFunction(_, _, _)
+            | Object::Composition(_, _) => "FUNCTION",
+            Object::Generator(_) => "GENERATOR",
+            Object::Module(_) => "MODULE",
+            Object::Quote(_) => "QUOTE",
+            Object::Macro(_, _, _) => "MACRO",
+            Object::StructType(_) => "STRUCT",
+            Object::Struct(instance) => return write!(f, "{}", instance.ty.name),
+            Object::EnumType(_) => "ENUM",
+            Object::Variant(variant) => return write!(f, "{}", variant.ty.name),
+            Object::Constructor(_, _) => "CONSTRUCTOR",
         };
         write!(f, "{kind}")
     }
@@ -104,6 +180,8 @@ impl Debug for Object {
             Self::Error(arg0) => f.debug_tuple("Error").field(arg0).finish(),
             Self::Boolean(arg0) => f.debug_tuple("Boolean").field(arg0).finish(),
             Self::Integer(arg0) => f.debug_tuple("Integer").field(arg0).finish(),
+            Self::BigInteger(arg0) => f.debug_tuple("BigInteger").field(arg0).finish(),
+            Self::Float(arg0) => f.debug_tuple("Float").field(arg0).finish(),
             Self::String(arg0) => f.debug_tuple("String").field(arg0).finish(),
             Self::Array(arg0) => f.debug_tuple("Array").field(arg0).finish(),
             Self::Hash(arg0) => f.debug_tuple("Hash").field(arg0).finish(),
@@ -118,6 +196,35 @@ impl Debug for Object {
                 .field(arg1)
                 .field(arg2)
                 .finish(),
+            Self::GeneratorFunction(arg0, arg1, arg2) => f
+                .debug_tuple("GeneratorFunction")
+                .field(arg0)
+                .field(arg1)
+                .field(arg2)
+                .finish(),
+            Self::Generator(arg0) => f.debug_tuple("Generator").field(arg0).finish(),
+            Self::Module(arg0) => f.debug_tuple("Module").field(&arg0.path).finish(),
+            Self::Quote(arg0) => f.debug_tuple("Quote").field(arg0).finish(),
+            Self::Macro(arg0, arg1, arg2) => f
+                .debug_tuple("Macro")
+                .field(arg0)
+                .field(arg1)
+                .field(arg2)
+                .finish(),
+            Self::StructType(arg0) => f.debug_tuple("StructType").field(arg0).finish(),
+            Self::Struct(arg0) => f.debug_tuple("Struct").field(arg0).finish(),
+            Self::EnumType(arg0) => f.debug_tuple("EnumType").field(arg0).finish(),
+            Self::Variant(arg0) => f.debug_tuple("Variant").field(arg0).finish(),
+            Self::Constructor(arg0, arg1) => f
+                .debug_tuple("Constructor")
+                .field(&arg0.name)
+                .field(arg1)
+                .finish(),
+            Self::Composition(arg0, arg1) => f
+                .debug_tuple("Composition")
+                .field(arg0)
+                .field(arg1)
+                .finish(),
         }
     }
 }
@@ -131,39 +238,236 @@ impl From<bool> for Object {
     }
 }
 
-#[allow(clippy::all)]
+impl From<BigInt> for Object {
+    fn from(value: BigInt) -> Self {
+        match value.to_i64() {
+            Some(int) => Object::Integer(int),
+            None => Object::BigInteger(value),
+        }
+    }
+}
+
+// Hashing agrees with `==` for every value `is_hashable` accepts. Functions,
+// generators, modules and types are compared and hashed by identity, and
+// everything else by what it holds.
 impl Hash for Object {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Integers and floats are compared by promoting the integer to a
+        // float, so every number hashes as the float it'd be promoted to.
+        // `0.0 == -0.0`, so both hash the same.
+        let number = match self {
+            Object::Integer(int) => Some(*int as f64),
+            Object::BigInteger(int) => Some(int.to_f64()),
+            Object::Float(float) => Some(*float),
+            _ => None,
+        };
+        if let Some(number) = number {
+            (number + 0.0).to_bits().hash(state);
+            return;
+        }
+
+        std::mem::discriminant(self).hash(state);
         match self {
+            Object::Null => {}
             Object::Boolean(bool) => bool.hash(state),
-            Object::Integer(int) => int.hash(state),
+            Object::Integer(_) | Object::BigInteger(_) | Object::Float(_) => {}
             Object::String(string) => string.hash(state),
-            Object::Null => 0.hash(state),
-            Object::Error(error) => error.hash(state),
+            Object::Error(error) => error.message.hash(state),
             Object::Array(elements) => elements.hash(state),
+            // The entries of a map come out in no particular order, so their
+            // hashes are combined in a way that doesn't depend on it.
+            Object::Hash(map) => {
+                let entries = map.iter().fold(0u64, |sum, entry| {
+                    let mut hasher = DefaultHasher::new();
+                    entry.hash(&mut hasher);
+                    sum.wrapping_add(hasher.finish())
+                });
+                map.len().hash(state);
+                entries.hash(state);
+            }
             Object::ReturnValue(value) => value.hash(state),
-            Object::Hash(_) | Object::Builtin(_) | Object::Function(_, _, _) => "".hash(state),
+            Object::Builtin(builtin) => (*builtin as usize).hash(state),
+            Object::Function(_, body, env) | Object::GeneratorFunction(_, body, env) => {
+                Rc::as_ptr(body).hash(state);
+                Rc::as_ptr(env).hash(state);
+            }
+            Object::Generator(generator) => Rc::as_ptr(generator).hash(state),
+            Object::Module(module) => Rc::as_ptr(module).hash(state),
+            Object::Quote(node) => node.to_string().hash(state),
+            Object::Macro(_, body, env) => {
+                body.to_string().hash(state);
+                Rc::as_ptr(env).hash(state);
+            }
+            Object::StructType(ty) => Rc::as_ptr(ty).hash(state),
+            // Instances can hold themselves, so only their type is hashed.
+            Object::Struct(instance) => Rc::as_ptr(&instance.ty).hash(state),
+            Object::EnumType(ty) => Rc::as_ptr(ty).hash(state),
+            Object::Variant(variant) => {
+                Rc::as_ptr(&variant.ty).hash(state);
+                variant.name().hash(state);
+                variant.payload.hash(state);
+            }
+            Object::Constructor(ty, tag) => {
+                Rc::as_ptr(ty).hash(state);
+                tag.hash(state);
+            }
+            Object::Composition(first, second) => {
+                first.hash(state);
+                second.hash(state);
+            }
         }
     }
 }
 
 impl PartialEq for Object {
     fn eq(&self, other: &Self) -> bool {
-        let mut hasher = DefaultHasher::new();
-        self.hash(&mut hasher);
-        let hash_self = hasher.finish();
+        match (self, other) {
+            (Object::Null, Object::Null) => true,
+            (Object::Boolean(a), Object::Boolean(b)) => a == b,
+            (Object::Integer(a), Object::Integer(b)) => a == b,
+            (Object::BigInteger(a), Object::BigInteger(b)) => a == b,
+            (Object::Float(a), Object::Float(b)) => a == b,
+            // Mixing integers and floats promotes the integer, as `==` does.
+            (Object::Integer(a), Object::Float(b)) | (Object::Float(b), Object::Integer(a)) => {
+                *a as f64 == *b
+            }
+            (Object::BigInteger(a), Object::Float(b))
+            | (Object::Float(b), Object::BigInteger(a)) => a.to_f64() == *b,
+            (Object::String(a), Object::String(b)) => a == b,
+            (Object::Error(a), Object::Error(b)) => a.kind == b.kind && a.message == b.message,
+            (Object::Array(a), Object::Array(b)) => a == b,
+            (Object::Hash(a), Object::Hash(b)) => a == b,
+            (Object::ReturnValue(a), Object::ReturnValue(b)) => a == b,
+            (Object::Builtin(a), Object::Builtin(b)) => std::ptr::fn_addr_eq(*a, *b),
+            (Object::Function(_, a, a_env), Object::Function(_, b, b_env))
+            | (Object::GeneratorFunction(_, a, a_env), Object::GeneratorFunction(_, b, b_env)) => {
+                Rc::ptr_eq(a, b) && Rc::ptr_eq(a_env, b_env)
+            }
+            (Object::Generator(a), Object::Generator(b)) => Rc::ptr_eq(a, b),
+            (Object::Module(a), Object::Module(b)) => Rc::ptr_eq(a, b),
+            (Object::Quote(a), Object::Quote(b)) => a == b,
+            (Object::Macro(a_params, a, a_env), Object::Macro(b_params, b, b_env)) => {
+                a_params == b_params && a == b && Rc::ptr_eq(a_env, b_env)
+            }
+            (Object::StructType(a), Object::StructType(b)) => Rc::ptr_eq(a, b),
+            (Object::Struct(a), Object::Struct(b)) => a.equals(b),
+            (Object::EnumType(a), Object::EnumType(b)) => Rc::ptr_eq(a, b),
+            (Object::Variant(a), Object::Variant(b)) => {
+                Rc::ptr_eq(&a.ty, &b.ty) && a.name() == b.name() && a.payload == b.payload
+            }
+            (Object::Constructor(a, a_tag), Object::Constructor(b, b_tag)) => {
+                Rc::ptr_eq(a, b) && a_tag == b_tag
+            }
+            (Object::Composition(a, a_then), Object::Composition(b, b_then)) => {
+                a == b && a_then == b_then
+            }
+            _ => false,
+        }
+    }
+}
 
-        let mut hasher = DefaultHasher::new();
-        other.hash(&mut hasher);
-        let hash_other = hasher.finish();
+// Floats aren't hashable, so `NaN != NaN` never reaches a map.
+impl Eq for Object {}
+
+/// The category of a runtime error, exposed to scripts as the `kind` of a
+/// caught error.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    /// Anything that doesn't fit a more specific kind, including values
+    /// thrown by scripts.
+    Error,
+    Type,
+    Name,
+    ZeroDivision,
+    Argument,
+    Key,
+    Match,
+    /// Rebinding a constant.
+    Assignment,
+    /// A module that couldn't be found, parsed or loaded.
+    Import,
+    /// A kind named by a script when throwing a hash.
+    Custom(String),
+}
 
-        hash_self == hash_other
+impl From<&str> for ErrorKind {
+    fn from(name: &str) -> Self {
+        match name {
+            "Error" => ErrorKind::Error,
+            "TypeError" => ErrorKind::Type,
+            "NameError" => ErrorKind::Name,
+            "ZeroDivisionError" => ErrorKind::ZeroDivision,
+            "ArgumentError" => ErrorKind::Argument,
+            "KeyError" => ErrorKind::Key,
+            "MatchError" => ErrorKind::Match,
+            "AssignmentError" => ErrorKind::Assignment,
+            "ImportError" => ErrorKind::Import,
+            _ => ErrorKind::Custom(name.to_string()),
+        }
+    }
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ErrorKind::Error => "Error",
+            ErrorKind::Type => "TypeError",
+            ErrorKind::Name => "NameError",
+            ErrorKind::ZeroDivision => "ZeroDivisionError",
+            ErrorKind::Argument => "ArgumentError",
+            ErrorKind::Key => "KeyError",
+            ErrorKind::Match => "MatchError",
+            ErrorKind::Assignment => "AssignmentError",
+            ErrorKind::Import => "ImportError",
+            ErrorKind::Custom(name) => name,
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeError {
+    pub kind: ErrorKind,
+    pub message: String,
+    /// The calls the error unwound through, innermost first. A call in tail
+    /// position replaces the frame of its caller, so of a chain of tail
+    /// calls only the outermost caller and the call that failed are kept.
+    pub stack: Vec<String>,
+}
+
+impl RuntimeError {
+    /// The hash a `catch` clause binds: `message`, `kind` and `stack`.
+    #[allow(clippy::mutable_key_type)]
+    pub fn to_hash(&self) -> Object {
+        let stack = self
+            .stack
+            .iter()
+            .map(|frame| Object::String(frame.clone()))
+            .collect();
+        Object::Hash(HashMap::from([
+            (
+                Object::String("message".to_string()),
+                Object::String(self.message.clone()),
+            ),
+            (
+                Object::String("kind".to_string()),
+                Object::String(self.kind.to_string()),
+            ),
+            (Object::String("stack".to_string()), Object::Array(stack)),
+        ]))
     }
 }
-impl Eq for Object {}
 
 pub fn new_error(reason: String) -> Object {
-    Object::Error(reason)
+    new_error_with_kind(ErrorKind::Error, reason)
+}
+
+pub fn new_error_with_kind(kind: ErrorKind, reason: String) -> Object {
+    Object::Error(Box::new(RuntimeError {
+        kind,
+        message: reason,
+        stack: Vec::new(),
+    }))
 }
 
 pub type Env = Rc<RefCell<Environment>>;
@@ -171,26 +475,65 @@ pub type Env = Rc<RefCell<Environment>>;
 #[derive(Debug)]
 pub struct Environment {
     store: HashMap<Identifier, Object>,
+    /// Names in `store` bound by `const`, with where they were declared.
+    constants: HashMap<Identifier, Position>,
     outer: Option<Env>,
+    /// The file the code running in this scope came from, if any. Imports
+    /// are resolved relative to it.
+    file: Option<Rc<Path>>,
+    modules: Rc<RefCell<ModuleCache>>,
 }
 
 impl Environment {
     pub fn new() -> Self {
         Self {
             store: HashMap::new(),
+            constants: HashMap::new(),
             outer: None,
+            file: None,
+            modules: Rc::default(),
         }
     }
 
     pub fn new_enclosed(outer: Env) -> Self {
+        let (file, modules) = {
+            let outer = outer.borrow();
+            (outer.file.clone(), outer.modules.clone())
+        };
         Self {
             store: HashMap::new(),
+            constants: HashMap::new(),
             outer: Some(outer),
+            file,
+            modules,
+        }
+    }
+
+    /// The top level scope of the file at `path`, sharing already loaded
+    /// modules with whoever imported it.
+    pub fn new_for_file(path: &Path, modules: Rc<RefCell<ModuleCache>>) -> Self {
+        Self {
+            file: Some(path.into()),
+            modules,
+            ..Self::new()
         }
     }
 
+    /// The directory imports are resolved against, which is the current
+    /// directory for code that didn't come from a file.
+    pub fn directory(&self) -> PathBuf {
+        match self.file.as_deref().and_then(Path::parent) {
+            Some(parent) => parent.to_path_buf(),
+            None => PathBuf::from("."),
+        }
+    }
+
+    pub fn modules(&self) -> Rc<RefCell<ModuleCache>> {
+        self.modules.clone()
+    }
+
     pub fn into_env(self) -> Env {
-        Rc::new(RefCell::new(self))
+        gc::track(RefCell::new(self))
     }
 
     pub fn get(&self, name: &Identifier) -> Option<Object> {
@@ -201,8 +544,77 @@ impl Environment {
         }
     }
 
-    pub fn set(&mut self, name: Identifier, value: Object) {
+    /// Changes the value of `name` in the innermost scope that has it, so
+    /// every closure over that scope sees the new value.
+    pub fn assign(&mut self, name: &Identifier, value: Object) -> Result<(), Object> {
+        if !self.store.contains_key(name) {
+            return match &self.outer {
+                Some(outer) => outer.borrow_mut().assign(name, value),
+                None => Err(new_error_with_kind(
+                    ErrorKind::Name,
+                    format!("cannot assign to undeclared variable {name}"),
+                )),
+            };
+        }
+        if let Some(position) = self.constants.get(name) {
+            return Err(new_error_with_kind(
+                ErrorKind::Assignment,
+                format!("cannot assign to constant {name}, declared at {position}"),
+            ));
+        }
+        self.store.insert(name.clone(), value);
+        Ok(())
+    }
+
+    /// Binds `name` in this scope, failing if it's already a constant here.
+    pub fn set(&mut self, name: Identifier, value: Object) -> Result<(), Object> {
+        self.check_not_constant(&name)?;
         self.store.insert(name, value);
+        Ok(())
+    }
+
+    pub fn set_const(
+        &mut self,
+        name: Identifier,
+        value: Object,
+        position: Position,
+    ) -> Result<(), Object> {
+        self.check_not_constant(&name)?;
+        self.constants.insert(name.clone(), position);
+        self.store.insert(name, value);
+        Ok(())
+    }
+
+    fn check_not_constant(&self, name: &Identifier) -> Result<(), Object> {
+        match self.constants.get(name) {
+            Some(position) => Err(new_error_with_kind(
+                ErrorKind::Assignment,
+                format!("cannot redeclare constant {name}, declared at {position}"),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Trace for RefCell<Environment> {
+    fn trace(&self, visit: &mut dyn FnMut(Address)) -> bool {
+        let Ok(env) = self.try_borrow() else {
+            return false;
+        };
+        env.store
+            .values()
+            .for_each(|value| gc::trace_object(value, visit));
+        if let Some(outer) = &env.outer {
+            visit(gc::address(outer));
+        }
+        true
+    }
+
+    fn clear(&self) {
+        if let Ok(mut env) = self.try_borrow_mut() {
+            env.store.clear();
+            env.outer = None;
+        }
     }
 }
 
@@ -230,4 +642,35 @@ mod tests {
         assert_eq!(calculate_hash(&hello1), calculate_hash(&hello2));
         assert_eq!(calculate_hash(&diff1), calculate_hash(&diff2));
     }
+
+    #[test]
+    #[allow(clippy::mutable_key_type)]
+    fn test_hash_hashkey() {
+        let pairs = (0..100).map(|i| (Object::Integer(i), Object::String(i.to_string())));
+        let forwards = Object::Hash(pairs.clone().collect());
+        let backwards = Object::Hash(pairs.rev().collect());
+
+        assert_eq!(forwards, backwards);
+        assert_eq!(calculate_hash(&forwards), calculate_hash(&backwards));
+        assert_ne!(forwards, Object::Hash(HashMap::new()));
+        assert_ne!(
+            calculate_hash(&forwards),
+            calculate_hash(&Object::Hash(HashMap::new()))
+        );
+    }
+
+    #[test]
+    fn test_equality_is_not_by_hash() {
+        // Every one of these used to hash the same, and so compared equal.
+        let function = |body: &str| {
+            let body = Statement::Expression(Expression::Identifier(Identifier(body.into())));
+            Object::Function(Rc::new([]), Rc::new(body), Environment::new().into_env())
+        };
+        let (f, g) = (function("x"), function("x"));
+        assert_eq!(f, f.clone());
+        assert_ne!(f, g);
+        assert_ne!(Object::Hash(HashMap::new()), f);
+        assert_ne!(Object::Null, Object::Integer(0));
+        assert_ne!(Object::Integer(1), Object::Boolean(true));
+    }
 }
@@ -0,0 +1,173 @@
+use super::{
+    builtins::is_wrong_arg_amount,
+    object::{new_error_with_kind, Env, ErrorKind, Object},
+    Eval,
+};
+use crate::parser::ast::{modify::modify_expression, Expression, Identifier, Literal};
+
+/// Evaluates `quote(expression)`, which returns the expression itself rather
+/// than its value. Any `unquote(...)` calls inside it are evaluated and
+/// their results spliced back into the quoted expression.
+pub fn quote(args: &[Expression], env: Env) -> Object {
+    if let Some(error) = is_wrong_arg_amount(args.len(), 1) {
+        return error;
+    }
+    match eval_unquote_calls(args[0].clone(), env) {
+        Ok(node) => Object::Quote(node),
+        Err(e) => e,
+    }
+}
+
+fn eval_unquote_calls(quoted: Expression, env: Env) -> Result<Expression, Object> {
+    let mut error = None;
+    let node = modify_expression(quoted, &mut |expression| {
+        let Expression::Call(function, args) = &expression else {
+            return expression;
+        };
+        if error.is_some() || !is_call_to(function, "unquote") {
+            return expression;
+        }
+        if let Some(e) = is_wrong_arg_amount(args.len(), 1) {
+            error = Some(e);
+            return expression;
+        }
+
+        let unquoted = args[0].eval(env.clone()).unwrap_or(Object::Null);
+        match object_to_expression(unquoted) {
+            Ok(node) => node,
+            Err(e) => {
+                error = Some(e);
+                expression
+            }
+        }
+    });
+
+    match error {
+        Some(e) => Err(e),
+        None => Ok(node),
+    }
+}
+
+pub fn is_call_to(function: &Expression, name: &str) -> bool {
+    matches!(function, Expression::Identifier(Identifier(ident)) if ident == name)
+}
+
+/// Turns the value of an `unquote` back into an expression that evaluates
+/// to it.
+fn object_to_expression(object: Object) -> Result<Expression, Object> {
+    let literal = match object {
+        Object::Quote(node) => return Ok(node),
+        Object::Integer(int) => Literal::Integer(int),
+        Object::BigInteger(int) => Literal::BigInteger(int.to_string()),
+        Object::Float(float) => Literal::Float(float),
+        Object::Boolean(bool) => Literal::Boolean(bool),
+        Object::String(string) => Literal::String(string),
+        Object::Null => Literal::Null,
+        Object::Array(elements) => {
+            let elements = elements
+                .into_iter()
+                .map(object_to_expression)
+                .collect::<Result<_, _>>()?;
+            return Ok(Expression::Array(elements));
+        }
+        Object::Hash(map) => {
+            let pairs = map
+                .into_iter()
+                .map(|(key, value)| Ok((object_to_expression(key)?, object_to_expression(value)?)))
+                .collect::<Result<_, Object>>()?;
+            return Ok(Expression::Hash(pairs));
+        }
+        error @ Object::Error(_) => return Err(error),
+        other => {
+            return Err(new_error_with_kind(
+                ErrorKind::Type,
+                format!("cannot unquote {other}"),
+            ))
+        }
+    };
+    Ok(Expression::Literal(literal))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{evaluator::object::Environment, lexer::Lexer, parser::Parser};
+
+    fn test_eval(input: &str) -> Option<Object> {
+        let program = Parser::new(Lexer::new(input)).parse_program();
+        program.eval(Environment::new().into_env())
+    }
+
+    fn assert_quote(input: &str, expected: &str) {
+        match test_eval(input) {
+            Some(Object::Quote(node)) => assert_eq!(node.to_string(), expected, "{input}"),
+            other => panic!("expected a Quote from {input}, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_quote() {
+        let inputs = vec![
+            ("quote(5)", "5"),
+            ("quote(5 + 8)", "(5 + 8)"),
+            ("quote(foobar)", "foobar"),
+            ("quote(foobar + barfoo)", "(foobar + barfoo)"),
+        ];
+
+        for (input, expected) in inputs {
+            assert_quote(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_quote_unquote() {
+        let inputs = vec![
+            ("quote(unquote(4))", "4"),
+            ("quote(unquote(4 + 4))", "8"),
+            ("quote(8 + unquote(4 + 4))", "(8 + 8)"),
+            ("quote(unquote(4 + 4) + 8)", "(8 + 8)"),
+            ("let foobar = 8; quote(foobar)", "foobar"),
+            ("let foobar = 8; quote(unquote(foobar))", "8"),
+            ("quote(unquote(true))", "true"),
+            ("quote(unquote(true == false))", "false"),
+            ("quote(unquote(quote(4 + 4)))", "(4 + 4)"),
+            (
+                "let quotedInfix = quote(4 + 4); quote(unquote(4 + 4) + unquote(quotedInfix))",
+                "(8 + (4 + 4))",
+            ),
+            (r#"quote(unquote("monkey"))"#, "monkey"),
+            ("quote(unquote(1.5 * 2.0))", "3.0"),
+            ("quote(unquote(null))", "null"),
+            ("quote(unquote(-5))", "-5"),
+            (
+                "quote(unquote(9223372036854775807 + 1))",
+                "9223372036854775808",
+            ),
+            ("quote(unquote([1, quote(x)]))", "[1, x]"),
+        ];
+
+        for (input, expected) in inputs {
+            assert_quote(input, expected);
+        }
+    }
+
+    #[test]
+    fn test_quote_errors() {
+        let inputs = vec![
+            ("quote(1, 2)", "wrong number of arguments. got=2, want=1"),
+            (
+                "quote(unquote())",
+                "wrong number of arguments. got=0, want=1",
+            ),
+            ("quote(unquote(missing))", "identifier not found: missing"),
+            ("quote(unquote(fn(x) { x }))", "cannot unquote FUNCTION"),
+        ];
+
+        for (input, expected) in inputs {
+            match test_eval(input) {
+                Some(Object::Error(error)) => assert_eq!(error.message, expected),
+                other => panic!("expected an error from {input}, got {other:?}"),
+            }
+        }
+    }
+}
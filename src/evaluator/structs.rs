@@ -0,0 +1,225 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use super::{
+    gc::{self, Address, Trace},
+    object::{new_error_with_kind, ErrorKind, Object},
+};
+use crate::parser::ast::Identifier;
+
+/// A type declared with `struct Name { fields }`, along with the methods
+/// attached to it by `impl` blocks.
+#[derive(Debug)]
+pub struct StructType {
+    pub name: Identifier,
+    pub fields: Vec<Identifier>,
+    methods: RefCell<HashMap<Identifier, Object>>,
+}
+
+impl StructType {
+    pub fn new(name: Identifier, fields: Vec<Identifier>) -> Self {
+        StructType {
+            name,
+            fields,
+            methods: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn method(&self, name: &str) -> Option<Object> {
+        self.methods
+            .borrow()
+            .get(&Identifier(name.to_string()))
+            .cloned()
+    }
+
+    /// Attaches a method, replacing any earlier one with the same name. A
+    /// method can't share a name with a field, since `value.name(...)`
+    /// would be ambiguous.
+    pub fn define_method(&self, name: Identifier, function: Object) -> Result<(), Object> {
+        if self.field_index(&name.0).is_some() {
+            return Err(new_error_with_kind(
+                ErrorKind::Name,
+                format!(
+                    "method {name} conflicts with field {name} of struct {}",
+                    self.name
+                ),
+            ));
+        }
+        self.methods.borrow_mut().insert(name, function);
+        Ok(())
+    }
+
+    fn field_index(&self, field: &str) -> Option<usize> {
+        self.fields.iter().position(|f| f.0 == field)
+    }
+
+    fn unknown_field(&self, field: &str) -> Object {
+        new_error_with_kind(
+            ErrorKind::Key,
+            format!("unknown field {field} for struct {}", self.name),
+        )
+    }
+
+    pub fn inspect(&self) -> String {
+        let fields = self
+            .fields
+            .iter()
+            .map(|f| f.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("struct {} {{ {fields} }}", self.name)
+    }
+}
+
+impl Trace for StructType {
+    fn trace(&self, visit: &mut dyn FnMut(Address)) -> bool {
+        let Ok(methods) = self.methods.try_borrow() else {
+            return false;
+        };
+        methods
+            .values()
+            .for_each(|method| gc::trace_object(method, visit));
+        true
+    }
+
+    fn clear(&self) {
+        if let Ok(mut methods) = self.methods.try_borrow_mut() {
+            methods.clear();
+        }
+    }
+}
+
+/// A value built from a `StructType`. Instances are shared, so assigning to
+/// a field is visible through every binding that holds the instance.
+#[derive(Debug)]
+pub struct StructInstance {
+    pub ty: Rc<StructType>,
+    fields: RefCell<Vec<Object>>,
+}
+
+impl StructInstance {
+    /// Builds an instance from `name: value` pairs, which must name every
+    /// field of `ty` exactly once.
+    pub fn new(ty: Rc<StructType>, values: Vec<(Identifier, Object)>) -> Result<Self, Object> {
+        let mut fields = vec![None; ty.fields.len()];
+        for (field, value) in values {
+            match ty.field_index(&field.0) {
+                Some(i) => fields[i] = Some(value),
+                None => return Err(ty.unknown_field(&field.0)),
+            }
+        }
+
+        let missing = ty
+            .fields
+            .iter()
+            .zip(&fields)
+            .filter(|(_, value)| value.is_none())
+            .map(|(field, _)| field.to_string())
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            return Err(new_error_with_kind(
+                ErrorKind::Argument,
+                format!(
+                    "missing fields for struct {}: {}",
+                    ty.name,
+                    missing.join(", ")
+                ),
+            ));
+        }
+
+        let fields = fields.into_iter().flatten().collect();
+        Ok(StructInstance {
+            ty,
+            fields: RefCell::new(fields),
+        })
+    }
+
+    pub fn get(&self, field: &str) -> Result<Object, Object> {
+        match self.ty.field_index(field) {
+            Some(i) => Ok(self.fields.borrow()[i].clone()),
+            None => Err(self.ty.unknown_field(field)),
+        }
+    }
+
+    pub fn set(&self, field: &str, value: Object) -> Result<(), Object> {
+        match self.ty.field_index(field) {
+            Some(i) => {
+                self.fields.borrow_mut()[i] = value;
+                Ok(())
+            }
+            None => Err(self.ty.unknown_field(field)),
+        }
+    }
+
+    pub fn fields(&self) -> Vec<(Identifier, Object)> {
+        self.ty
+            .fields
+            .iter()
+            .cloned()
+            .zip(self.fields.borrow().iter().cloned())
+            .collect()
+    }
+
+    /// Shows the instance with its fields. An instance that holds itself,
+    /// directly or through others, shows as `Name { ... }` where it recurs.
+    pub fn inspect(&self) -> String {
+        let address = self as *const Self;
+        if PRINTING.with(|printing| printing.borrow().contains(&address)) {
+            return format!("{} {{ ... }}", self.ty.name);
+        }
+
+        PRINTING.with(|printing| printing.borrow_mut().push(address));
+        let fields = self
+            .fields()
+            .iter()
+            .map(|(field, value)| format!("{field}: {}", value.inspect()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        PRINTING.with(|printing| printing.borrow_mut().pop());
+        format!("{} {{ {fields} }}", self.ty.name)
+    }
+
+    /// Whether both are instances of the same type with equal fields. A pair
+    /// of instances that recurs while comparing them is taken to be equal,
+    /// so instances that hold themselves can be compared.
+    pub fn equals(&self, other: &Self) -> bool {
+        if !Rc::ptr_eq(&self.ty, &other.ty) {
+            return false;
+        }
+        let pair = (self as *const Self, other as *const Self);
+        if COMPARING.with(|comparing| comparing.borrow().contains(&pair)) {
+            return true;
+        }
+
+        COMPARING.with(|comparing| comparing.borrow_mut().push(pair));
+        let equal = *self.fields.borrow() == *other.fields.borrow();
+        COMPARING.with(|comparing| comparing.borrow_mut().pop());
+        equal
+    }
+}
+
+thread_local! {
+    /// The instances `inspect` is in the middle of showing.
+    static PRINTING: RefCell<Vec<*const StructInstance>> = const { RefCell::new(Vec::new()) };
+    /// The pairs of instances `equals` is in the middle of comparing.
+    static COMPARING: RefCell<Vec<(*const StructInstance, *const StructInstance)>> =
+        const { RefCell::new(Vec::new()) };
+}
+
+impl Trace for StructInstance {
+    fn trace(&self, visit: &mut dyn FnMut(Address)) -> bool {
+        let Ok(fields) = self.fields.try_borrow() else {
+            return false;
+        };
+        visit(gc::address(&self.ty));
+        fields
+            .iter()
+            .for_each(|field| gc::trace_object(field, visit));
+        true
+    }
+
+    fn clear(&self) {
+        if let Ok(mut fields) = self.fields.try_borrow_mut() {
+            fields.clear();
+        }
+    }
+}
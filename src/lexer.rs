@@ -1,45 +1,60 @@
 pub mod token;
 
-use token::{Token, TokenKind};
+use token::{Comment, Position, StringPart, Token, TokenKind};
+
+/// Sentinel returned once the whole input has been consumed.
+const EOF_CHAR: char = '\0';
 
 #[derive(Debug)]
 pub struct Lexer {
-    input: String,
+    input: Vec<char>,
     position: usize,
     read_position: usize,
-    character: u8,
+    character: char,
+    /// The position of `character` in the source.
+    line: usize,
+    column: usize,
 }
 
 impl Lexer {
     pub fn new(input: &str) -> Self {
         let mut lexer = Self {
-            input: input.to_string(),
+            input: input.chars().collect(),
             position: 0,
             read_position: 0,
-            character: 0,
+            character: EOF_CHAR,
+            line: 1,
+            column: 0,
         };
         lexer.read_char();
         lexer
     }
 
     fn read_char(&mut self) {
-        if self.read_position >= self.input.len() {
-            self.character = 0;
+        if self.character == '\n' {
+            self.line += 1;
+            self.column = 1;
         } else {
-            self.character = self.input.as_bytes()[self.read_position];
+            self.column += 1;
         }
+        self.character = self.char_at(self.read_position);
         self.position = self.read_position;
         self.read_position += 1;
     }
 
-    fn peek_char(&self) -> u8 {
-        if self.read_position >= self.input.len() {
-            return 0;
-        }
-        return self.input.as_bytes()[self.read_position];
+    fn peek_char(&self) -> char {
+        self.char_at(self.read_position)
+    }
+
+    fn char_at(&self, position: usize) -> char {
+        self.input.get(position).copied().unwrap_or(EOF_CHAR)
     }
 
-    fn if_peeked(&mut self, to_match: u8, matched: TokenKind, default: TokenKind) -> TokenKind {
+    fn slice(&self, start: usize) -> String {
+        self.input[start..self.position].iter().collect()
+    }
+
+    fn if_peeked(&mut self, to_match: char, matched: TokenKind, default: TokenKind) -> TokenKind {
         if self.peek_char() == to_match {
             self.read_char();
             return matched;
@@ -47,87 +62,310 @@ impl Lexer {
         default
     }
 
-    fn read_identifier(&mut self) -> &str {
+    fn read_identifier(&mut self) -> String {
         let start = self.position;
-        while is_letter(self.character) {
+        while is_letter(self.character) || is_number(self.character) {
             self.read_char();
         }
-        &self.input[start..self.position]
+        self.slice(start)
     }
 
-    fn read_number(&mut self) -> i64 {
-        let start = self.position;
+    fn read_digits(&mut self) {
         while is_number(self.character) {
             self.read_char();
         }
-        let number = &self.input[start..self.position];
-        number.parse().unwrap()
     }
 
-    fn read_string(&mut self) -> String {
-        let position = self.position + 1;
+    fn read_number(&mut self) -> TokenKind {
+        let start = self.position;
+        let mut is_float = false;
+        self.read_digits();
+
+        if self.character == '.' && is_number(self.peek_char()) {
+            is_float = true;
+            self.read_char();
+            self.read_digits();
+        }
+
+        if matches!(self.character, 'e' | 'E') {
+            let after_sign = match self.peek_char() {
+                '+' | '-' => self.char_at(self.read_position + 1),
+                c => c,
+            };
+            if is_number(after_sign) {
+                is_float = true;
+                self.read_char();
+                if matches!(self.character, '+' | '-') {
+                    self.read_char();
+                }
+                self.read_digits();
+            }
+        }
+
+        let number = self.slice(start);
+        if is_float {
+            return TokenKind::Float(number.parse().unwrap());
+        }
+        match number.parse() {
+            Ok(int) => TokenKind::Int(int),
+            Err(_) => TokenKind::BigInt(number),
+        }
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.position >= self.input.len()
+    }
+
+    /// Reads a string literal, splitting it into literal and code parts if it
+    /// contains any `${...}` interpolations.
+    fn read_string(&mut self) -> Result<TokenKind, String> {
+        let mut parts = Vec::new();
+        let mut string = String::new();
+        let mut error = None;
         loop {
             self.read_char();
-            if self.character == b'"' || self.character == 0 {
-                break;
+            match self.character {
+                '"' => break,
+                EOF_CHAR if self.is_at_end() => return Err("unterminated string".to_string()),
+                '$' if self.peek_char() == '{' => {
+                    self.read_char();
+                    parts.push(StringPart::Literal(std::mem::take(&mut string)));
+                    parts.push(StringPart::Code(self.read_interpolation()?));
+                }
+                // Keep going after a bad escape so the rest of the string
+                // isn't lexed as code.
+                '\\' => match self.read_escape_sequence() {
+                    Ok(escaped) => string.push(escaped),
+                    Err(e) => {
+                        error.get_or_insert(e);
+                    }
+                },
+                c => string.push(c),
             }
         }
-        self.input[position..self.position].to_string()
+
+        if let Some(e) = error {
+            return Err(e);
+        }
+        if parts.is_empty() {
+            return Ok(TokenKind::String(string));
+        }
+        parts.push(StringPart::Literal(string));
+        parts.retain(|part| part != &StringPart::Literal(String::new()));
+        Ok(TokenKind::InterpolatedString(parts))
+    }
+
+    /// Reads the source code between `${` and its matching `}`, skipping over
+    /// nested braces and strings.
+    fn read_interpolation(&mut self) -> Result<String, String> {
+        let start = self.read_position;
+        let mut depth = 0;
+        loop {
+            self.read_char();
+            match self.character {
+                EOF_CHAR if self.is_at_end() => {
+                    return Err("unterminated string interpolation".to_string())
+                }
+                '{' => depth += 1,
+                '}' if depth == 0 => break,
+                '}' => depth -= 1,
+                '"' => {
+                    // Any other error is reported when the code gets parsed.
+                    let nested = self.read_string();
+                    if nested.is_err() && self.is_at_end() {
+                        return Err("unterminated string interpolation".to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(self.slice(start))
+    }
+
+    fn read_escape_sequence(&mut self) -> Result<char, String> {
+        self.read_char();
+        let escaped = match self.character {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            '"' => '"',
+            '$' => '$',
+            '\\' => '\\',
+            'u' => return self.read_unicode_escape(),
+            EOF_CHAR => return Err("unterminated string".to_string()),
+            c => return Err(format!("invalid escape sequence: \\{c}")),
+        };
+        Ok(escaped)
+    }
+
+    fn read_unicode_escape(&mut self) -> Result<char, String> {
+        if self.peek_char() != '{' {
+            return Err("expected '{' after \\u".to_string());
+        }
+        self.read_char();
+
+        let start = self.read_position;
+        while self.peek_char().is_ascii_hexdigit() {
+            self.read_char();
+        }
+        let digits = self.input[start..self.read_position]
+            .iter()
+            .collect::<String>();
+        if self.peek_char() != '}' {
+            return Err(format!("unterminated unicode escape: \\u{{{digits}"));
+        }
+        self.read_char();
+
+        u32::from_str_radix(&digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| format!("invalid unicode escape: \\u{{{digits}}}"))
     }
 
     pub fn next_token(&mut self) -> Token {
-        use TokenKind::*;
+        let comments = match self.skip_trivia() {
+            Ok(comments) => comments,
+            Err((comments, reason)) => {
+                return Token::with_comments(TokenKind::Illegal(reason), comments)
+                    .at(self.current_position())
+            }
+        };
+        let position = self.current_position();
+        Token::with_comments(self.read_token_kind(), comments).at(position)
+    }
+
+    fn current_position(&self) -> Position {
+        Position {
+            line: self.line,
+            column: self.column,
+        }
+    }
 
-        self.skip_whitespace();
+    fn read_token_kind(&mut self) -> TokenKind {
+        use TokenKind::*;
 
         let token_kind = match self.character {
-            0 => Eof,
-            b'+' => Plus,
-            b'-' => Minus,
-            b'*' => Asterisk,
-            b'/' => Slash,
-            b'<' => LessThan,
-            b'>' => GreaterThan,
-            b',' => Comma,
-            b':' => Colon,
-            b';' => Semicolon,
-            b'(' => Lparen,
-            b')' => Rparen,
-            b'[' => Lbracket,
-            b']' => Rbracket,
-            b'{' => Lbrace,
-            b'}' => Rbrace,
-            b'"' => String(self.read_string()),
-            b'=' => self.if_peeked(b'=', Equal, Assign),
-            b'!' => self.if_peeked(b'=', NotEqual, Bang),
-            c if is_letter(c) => {
-                let literal = self.read_identifier();
-                let kind = TokenKind::from_letters(literal);
-                return Token::new(kind);
+            EOF_CHAR => Eof,
+            '+' => Plus,
+            '-' => Minus,
+            '*' => Asterisk,
+            '/' => Slash,
+            '%' => Percent,
+            '<' => LessThan,
+            '>' => self.if_peeked('>', Compose, GreaterThan),
+            '|' => self.if_peeked('>', Pipeline, Bar),
+            ',' => Comma,
+            ':' => Colon,
+            '.' if self.peek_char() == '.' && self.char_at(self.read_position + 1) == '.' => {
+                self.read_char();
+                self.read_char();
+                Ellipsis
             }
-            c if is_number(c) => {
-                let number = self.read_number();
-                return Token::new(Int(number));
+            '.' => Dot,
+            ';' => Semicolon,
+            '(' => Lparen,
+            ')' => Rparen,
+            '[' => Lbracket,
+            ']' => Rbracket,
+            '{' => Lbrace,
+            '}' => Rbrace,
+            '"' => self.read_string().unwrap_or_else(Illegal),
+            '=' if self.peek_char() == '>' => {
+                self.read_char();
+                FatArrow
             }
-            _ => return Token::new(Illegal),
+            '=' => self.if_peeked('=', Equal, Assign),
+            '!' => self.if_peeked('=', NotEqual, Bang),
+            '?' => {
+                let kind = match self.peek_char() {
+                    '?' => NullCoalesce,
+                    '.' => OptionalDot,
+                    '[' => OptionalLbracket,
+                    _ => Illegal("?".to_string()),
+                };
+                if !matches!(kind, Illegal(_)) {
+                    self.read_char();
+                }
+                kind
+            }
+            c if is_letter(c) => return TokenKind::from_letters(&self.read_identifier()),
+            c if is_number(c) => return self.read_number(),
+            c => Illegal(c.to_string()),
         };
 
         self.read_char();
-        Token::new(token_kind)
+        token_kind
+    }
+
+    /// Skips whitespace and comments, returning the comments so they can be
+    /// attached to the token that follows them.
+    fn skip_trivia(&mut self) -> Result<Vec<Comment>, (Vec<Comment>, String)> {
+        let mut comments = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match (self.character, self.peek_char()) {
+                ('/', '/') => comments.push(self.read_line_comment()),
+                ('/', '*') => match self.read_block_comment() {
+                    Some(comment) => comments.push(comment),
+                    None => return Err((comments, "unterminated block comment".to_string())),
+                },
+                _ => return Ok(comments),
+            }
+        }
+    }
+
+    fn read_line_comment(&mut self) -> Comment {
+        self.read_char();
+        let start = self.read_position;
+        while !matches!(self.peek_char(), '\n' | EOF_CHAR) {
+            self.read_char();
+        }
+        let text = self.input[start..self.read_position].iter().collect();
+        self.read_char();
+        Comment::Line(text)
+    }
+
+    /// Reads a `/* */` comment, which may contain other block comments.
+    /// Returns `None` if the input ends before the comment is closed.
+    fn read_block_comment(&mut self) -> Option<Comment> {
+        self.read_char();
+        let start = self.read_position;
+        let mut depth = 0;
+        loop {
+            self.read_char();
+            match (self.character, self.peek_char()) {
+                (EOF_CHAR, _) if self.is_at_end() => return None,
+                ('/', '*') => {
+                    self.read_char();
+                    depth += 1;
+                }
+                ('*', '/') if depth == 0 => break,
+                ('*', '/') => {
+                    self.read_char();
+                    depth -= 1;
+                }
+                _ => {}
+            }
+        }
+        let text = self.slice(start);
+        self.read_char();
+        self.read_char();
+        Some(Comment::Block(text))
     }
 
     fn skip_whitespace(&mut self) {
-        while self.character.is_ascii_whitespace() {
+        while self.character.is_whitespace() {
             self.read_char()
         }
     }
 }
 
-fn is_letter(character: u8) -> bool {
-    character.is_ascii_alphabetic() || character == b'_'
+fn is_letter(character: char) -> bool {
+    character.is_alphabetic() || character == '_'
 }
 
-fn is_number(character: u8) -> bool {
+fn is_number(character: char) -> bool {
     character.is_ascii_digit()
 }
 
@@ -150,10 +388,12 @@ mod tests {
 
     #[test]
     fn test_basic_tokens() {
-        let input = "=+(){},;";
+        let input = "=+%...(){},;";
         let expected = vec![
             TokenKind::Assign,
             TokenKind::Plus,
+            TokenKind::Percent,
+            TokenKind::Ellipsis,
             TokenKind::Lparen,
             TokenKind::Rparen,
             TokenKind::Lbrace,
@@ -229,7 +469,7 @@ let add = fn(x, y) {
 };
 
 let result = add(five, ten);
-!-/*5;
+!-/ *5;
 5 < 10 > 5;
 
 if (5 < 10) {
@@ -340,6 +580,341 @@ if (5 < 10) {
         test_next_token(input, &expected);
     }
 
+    #[test]
+    fn test_numbers() {
+        let input = "2.75 1e-3 2.5E+2 10 7e 9223372036854775808";
+        let expected = vec![
+            TokenKind::Float(2.75),
+            TokenKind::Float(0.001),
+            TokenKind::Float(250.0),
+            TokenKind::Int(10),
+            TokenKind::Int(7),
+            TokenKind::Ident("e".into()),
+            TokenKind::BigInt("9223372036854775808".into()),
+            TokenKind::Eof,
+        ];
+
+        test_next_token(input, &expected);
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        let input = r#""a\nb" "tab\there" "say \"hi\"" "back\\slash" "\u{1F600}\u{e9}""#;
+        let expected = vec![
+            TokenKind::String("a\nb".into()),
+            TokenKind::String("tab\there".into()),
+            TokenKind::String("say \"hi\"".into()),
+            TokenKind::String("back\\slash".into()),
+            TokenKind::String("😀é".into()),
+            TokenKind::Eof,
+        ];
+
+        test_next_token(input, &expected);
+    }
+
+    #[test]
+    fn test_interpolated_strings() {
+        let input = r#""total: ${count * 2}!" "${a}${b}" "${ {"k": "}"}["k"] }" "\${no}""#;
+        let expected = vec![
+            TokenKind::InterpolatedString(vec![
+                StringPart::Literal("total: ".into()),
+                StringPart::Code("count * 2".into()),
+                StringPart::Literal("!".into()),
+            ]),
+            TokenKind::InterpolatedString(vec![
+                StringPart::Code("a".into()),
+                StringPart::Code("b".into()),
+            ]),
+            TokenKind::InterpolatedString(vec![StringPart::Code(r#" {"k": "}"}["k"] "#.into())]),
+            TokenKind::String("${no}".into()),
+            TokenKind::Eof,
+        ];
+
+        test_next_token(input, &expected);
+    }
+
+    #[test]
+    fn test_invalid_strings() {
+        let input = r#""bad \q escape" "\u{110000}" "\u{zz}" 1 "${x" "never closed"#;
+        let expected = vec![
+            TokenKind::Illegal("invalid escape sequence: \\q".into()),
+            TokenKind::Illegal("invalid unicode escape: \\u{110000}".into()),
+            TokenKind::Illegal("unterminated unicode escape: \\u{".into()),
+            TokenKind::Int(1),
+            TokenKind::Illegal("unterminated string interpolation".into()),
+            TokenKind::Eof,
+        ];
+
+        test_next_token(input, &expected);
+        test_next_token(
+            r#""never closed"#,
+            &[TokenKind::Illegal("unterminated string".into())],
+        );
+    }
+
+    #[test]
+    fn test_unicode() {
+        let input = r#"let café = "héllo wörld"; naïve2 # "日本""#;
+        let expected = vec![
+            TokenKind::Let,
+            TokenKind::Ident("café".into()),
+            TokenKind::Assign,
+            TokenKind::String("héllo wörld".into()),
+            TokenKind::Semicolon,
+            TokenKind::Ident("naïve2".into()),
+            TokenKind::Illegal("#".into()),
+            TokenKind::String("日本".into()),
+            TokenKind::Eof,
+        ];
+
+        test_next_token(input, &expected);
+    }
+
+    #[test]
+    fn test_null_operators() {
+        let input = r#"null ?? a?["b"]?.c ? x"#;
+        let expected = vec![
+            TokenKind::Null,
+            TokenKind::NullCoalesce,
+            TokenKind::Ident("a".into()),
+            TokenKind::OptionalLbracket,
+            TokenKind::String("b".into()),
+            TokenKind::Rbracket,
+            TokenKind::OptionalDot,
+            TokenKind::Ident("c".into()),
+            TokenKind::Illegal("?".into()),
+            TokenKind::Ident("x".into()),
+            TokenKind::Eof,
+        ];
+
+        test_next_token(input, &expected);
+    }
+
+    #[test]
+    fn test_match_tokens() {
+        let input = "match (x) { 0 => a, _ => b }";
+        let expected = vec![
+            TokenKind::Match,
+            TokenKind::Lparen,
+            TokenKind::Ident("x".into()),
+            TokenKind::Rparen,
+            TokenKind::Lbrace,
+            TokenKind::Int(0),
+            TokenKind::FatArrow,
+            TokenKind::Ident("a".into()),
+            TokenKind::Comma,
+            TokenKind::Ident("_".into()),
+            TokenKind::FatArrow,
+            TokenKind::Ident("b".into()),
+            TokenKind::Rbrace,
+            TokenKind::Eof,
+        ];
+
+        test_next_token(input, &expected);
+    }
+
+    #[test]
+    fn test_exception_tokens() {
+        let input = "try { throw e; } catch (e) { } finally { }";
+        let expected = vec![
+            TokenKind::Try,
+            TokenKind::Lbrace,
+            TokenKind::Throw,
+            TokenKind::Ident("e".into()),
+            TokenKind::Semicolon,
+            TokenKind::Rbrace,
+            TokenKind::Catch,
+            TokenKind::Lparen,
+            TokenKind::Ident("e".into()),
+            TokenKind::Rparen,
+            TokenKind::Lbrace,
+            TokenKind::Rbrace,
+            TokenKind::Finally,
+            TokenKind::Lbrace,
+            TokenKind::Rbrace,
+            TokenKind::Eof,
+        ];
+
+        test_next_token(input, &expected);
+    }
+
+    #[test]
+    fn test_generator_tokens() {
+        let input = "fn*() { yield 1; } for (x in xs) {}";
+        let expected = vec![
+            TokenKind::Function,
+            TokenKind::Asterisk,
+            TokenKind::Lparen,
+            TokenKind::Rparen,
+            TokenKind::Lbrace,
+            TokenKind::Yield,
+            TokenKind::Int(1),
+            TokenKind::Semicolon,
+            TokenKind::Rbrace,
+            TokenKind::For,
+            TokenKind::Lparen,
+            TokenKind::Ident("x".into()),
+            TokenKind::In,
+            TokenKind::Ident("xs".into()),
+            TokenKind::Rparen,
+            TokenKind::Lbrace,
+            TokenKind::Rbrace,
+            TokenKind::Eof,
+        ];
+
+        test_next_token(input, &expected);
+    }
+
+    #[test]
+    fn test_module_tokens() {
+        let input = r#"import "lib.mk" as lib; export let x = lib.y;"#;
+        let expected = vec![
+            TokenKind::Import,
+            TokenKind::String("lib.mk".into()),
+            TokenKind::As,
+            TokenKind::Ident("lib".into()),
+            TokenKind::Semicolon,
+            TokenKind::Export,
+            TokenKind::Let,
+            TokenKind::Ident("x".into()),
+            TokenKind::Assign,
+            TokenKind::Ident("lib".into()),
+            TokenKind::Dot,
+            TokenKind::Ident("y".into()),
+            TokenKind::Semicolon,
+            TokenKind::Eof,
+        ];
+
+        test_next_token(input, &expected);
+    }
+
+    #[test]
+    fn test_pipeline_tokens() {
+        let input = "x |> f >> g > h | y ||";
+        let expected = vec![
+            TokenKind::Ident("x".into()),
+            TokenKind::Pipeline,
+            TokenKind::Ident("f".into()),
+            TokenKind::Compose,
+            TokenKind::Ident("g".into()),
+            TokenKind::GreaterThan,
+            TokenKind::Ident("h".into()),
+            TokenKind::Bar,
+            TokenKind::Ident("y".into()),
+            TokenKind::Bar,
+            TokenKind::Bar,
+            TokenKind::Eof,
+        ];
+
+        test_next_token(input, &expected);
+    }
+
+    #[test]
+    fn test_struct_tokens() {
+        let input = "struct Point { x } impl Point {} enum E {} p.x = 1;";
+        let expected = vec![
+            TokenKind::Struct,
+            TokenKind::Ident("Point".into()),
+            TokenKind::Lbrace,
+            TokenKind::Ident("x".into()),
+            TokenKind::Rbrace,
+            TokenKind::Impl,
+            TokenKind::Ident("Point".into()),
+            TokenKind::Lbrace,
+            TokenKind::Rbrace,
+            TokenKind::Enum,
+            TokenKind::Ident("E".into()),
+            TokenKind::Lbrace,
+            TokenKind::Rbrace,
+            TokenKind::Ident("p".into()),
+            TokenKind::Dot,
+            TokenKind::Ident("x".into()),
+            TokenKind::Assign,
+            TokenKind::Int(1),
+            TokenKind::Semicolon,
+            TokenKind::Eof,
+        ];
+
+        test_next_token(input, &expected);
+    }
+
+    #[test]
+    fn test_token_positions() {
+        let input = "const x = 1;\n  /* a\ncomment */ y\n\"two\nlines\" z";
+        let expected = vec![
+            (TokenKind::Const, 1, 1),
+            (TokenKind::Ident("x".into()), 1, 7),
+            (TokenKind::Assign, 1, 9),
+            (TokenKind::Int(1), 1, 11),
+            (TokenKind::Semicolon, 1, 12),
+            (TokenKind::Ident("y".into()), 3, 12),
+            (TokenKind::String("two\nlines".into()), 4, 1),
+            (TokenKind::Ident("z".into()), 5, 8),
+            (TokenKind::Eof, 5, 9),
+        ];
+
+        let mut lexer = Lexer::new(input);
+        for (kind, line, column) in expected {
+            let token = lexer.next_token();
+            assert_eq!(token.kind, kind);
+            assert_eq!(token.position, Position { line, column }, "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn test_comments_are_skipped() {
+        let input = r#"let x = 5; // the answer / 8
+/* a /* nested */ block */ x / 2 /**/
+// trailing"#;
+        let expected = vec![
+            TokenKind::Let,
+            TokenKind::Ident("x".into()),
+            TokenKind::Assign,
+            TokenKind::Int(5),
+            TokenKind::Semicolon,
+            TokenKind::Ident("x".into()),
+            TokenKind::Slash,
+            TokenKind::Int(2),
+            TokenKind::Eof,
+        ];
+
+        test_next_token(input, &expected);
+    }
+
+    #[test]
+    fn test_comments_are_attached_as_trivia() {
+        let input = "1 // one\n/* two */ // three\n2 /* four";
+        let mut lexer = Lexer::new(input);
+
+        let token = lexer.next_token();
+        assert_eq!(token.kind, TokenKind::Int(1));
+        assert!(token.comments.is_empty());
+
+        let token = lexer.next_token();
+        assert_eq!(token.kind, TokenKind::Int(2));
+        assert_eq!(
+            token.comments,
+            vec![
+                Comment::Line(" one".into()),
+                Comment::Block(" two ".into()),
+                Comment::Line(" three".into()),
+            ]
+        );
+        let round_trip = token
+            .comments
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(round_trip, vec!["// one", "/* two */", "// three"]);
+
+        let token = lexer.next_token();
+        assert_eq!(
+            token.kind,
+            TokenKind::Illegal("unterminated block comment".into())
+        );
+        assert_eq!(lexer.next_token().kind, TokenKind::Eof);
+    }
+
     #[test]
     fn text_arrays() {
         let input = "[1, 2];";
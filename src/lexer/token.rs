@@ -1,20 +1,35 @@
 use std::fmt::Display;
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+/// A piece of an interpolated string literal: either plain text or the source
+/// of an expression found between `${` and `}`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StringPart {
+    Literal(String),
+    Code(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum TokenKind {
-    Illegal,
+    /// A character or sequence the lexer couldn't make sense of, along with
+    /// the reason why.
+    Illegal(String),
     Eof,
 
     Ident(String),
     Int(i64),
+    BigInt(String),
+    Float(f64),
     String(String),
+    InterpolatedString(Vec<StringPart>),
 
     Assign,
+    FatArrow,
     Plus,
     Minus,
     Bang,
     Asterisk,
     Slash,
+    Percent,
 
     If,
     Else,
@@ -27,8 +42,20 @@ pub enum TokenKind {
     LessThan,
     GreaterThan,
 
+    /// `|`, which encloses the parameters of a short lambda.
+    Bar,
+    /// `|>`
+    Pipeline,
+    /// `>>`
+    Compose,
+    NullCoalesce,
+    Dot,
+    OptionalDot,
+    OptionalLbracket,
+
     Comma,
     Colon,
+    Ellipsis,
     Semicolon,
 
     Lparen,
@@ -39,19 +66,53 @@ pub enum TokenKind {
     Rbrace,
 
     Function,
+    Macro,
+    Struct,
+    Impl,
+    Enum,
     Let,
+    Null,
+    Match,
+    Const,
+    Import,
+    Export,
+    As,
+    Throw,
+    Try,
+    Catch,
+    Finally,
+    For,
+    In,
+    Yield,
 }
 
 impl TokenKind {
     pub fn from_letters(literal: &str) -> Self {
         match literal {
             "fn" => TokenKind::Function,
+            "macro" => TokenKind::Macro,
+            "struct" => TokenKind::Struct,
+            "impl" => TokenKind::Impl,
+            "enum" => TokenKind::Enum,
             "let" => TokenKind::Let,
             "true" => TokenKind::True,
             "false" => TokenKind::False,
             "if" => TokenKind::If,
             "else" => TokenKind::Else,
             "return" => TokenKind::Return,
+            "null" => TokenKind::Null,
+            "match" => TokenKind::Match,
+            "const" => TokenKind::Const,
+            "import" => TokenKind::Import,
+            "export" => TokenKind::Export,
+            "as" => TokenKind::As,
+            "throw" => TokenKind::Throw,
+            "try" => TokenKind::Try,
+            "catch" => TokenKind::Catch,
+            "finally" => TokenKind::Finally,
+            "for" => TokenKind::For,
+            "in" => TokenKind::In,
+            "yield" => TokenKind::Yield,
             _ => TokenKind::Ident(literal.to_string()),
         }
     }
@@ -60,15 +121,18 @@ impl TokenKind {
 impl Display for TokenKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let matched = match self {
-            TokenKind::Illegal => "ILLEGAL",
+            TokenKind::Illegal(_) => "ILLEGAL",
             TokenKind::Eof => "EOF",
             TokenKind::Ident(_) => "IDENT",
-            TokenKind::Int(_) => "INT",
+            TokenKind::Int(_) | TokenKind::BigInt(_) => "INT",
+            TokenKind::Float(_) => "FLOAT",
             TokenKind::String(_) => "STRING",
+            TokenKind::InterpolatedString(_) => "INTERPOLATED_STRING",
             TokenKind::Assign => "=",
             TokenKind::Plus => "+",
             TokenKind::Comma => ",",
             TokenKind::Colon => ":",
+            TokenKind::Ellipsis => "...",
             TokenKind::Semicolon => ";",
             TokenKind::Lparen => "(",
             TokenKind::Rparen => ")",
@@ -77,11 +141,16 @@ impl Display for TokenKind {
             TokenKind::Lbrace => "{",
             TokenKind::Rbrace => "}",
             TokenKind::Function => "FUNCTION",
+            TokenKind::Macro => "MACRO",
+            TokenKind::Struct => "STRUCT",
+            TokenKind::Impl => "IMPL",
+            TokenKind::Enum => "ENUM",
             TokenKind::Let => "LET",
             TokenKind::Minus => "-",
             TokenKind::Bang => "!",
             TokenKind::Asterisk => "*",
             TokenKind::Slash => "/",
+            TokenKind::Percent => "%",
             TokenKind::LessThan => "<",
             TokenKind::GreaterThan => ">",
             TokenKind::Equal => "==",
@@ -91,18 +160,84 @@ impl Display for TokenKind {
             TokenKind::Return => "RETURN",
             TokenKind::If => "IF",
             TokenKind::Else => "ELSE",
+            TokenKind::Null => "NULL",
+            TokenKind::Match => "MATCH",
+            TokenKind::Const => "CONST",
+            TokenKind::Import => "IMPORT",
+            TokenKind::Export => "EXPORT",
+            TokenKind::As => "AS",
+            TokenKind::Throw => "THROW",
+            TokenKind::Try => "TRY",
+            TokenKind::Catch => "CATCH",
+            TokenKind::Finally => "FINALLY",
+            TokenKind::For => "FOR",
+            TokenKind::In => "IN",
+            TokenKind::Yield => "YIELD",
+            TokenKind::FatArrow => "=>",
+            TokenKind::Bar => "|",
+            TokenKind::Pipeline => "|>",
+            TokenKind::Compose => ">>",
+            TokenKind::NullCoalesce => "??",
+            TokenKind::Dot => ".",
+            TokenKind::OptionalDot => "?.",
+            TokenKind::OptionalLbracket => "?[",
         };
         write!(f, "{matched}")
     }
 }
 
+/// A comment the lexer skipped over. The text excludes the delimiters, so
+/// `Display` gives back the original source.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Comment {
+    Line(String),
+    Block(String),
+}
+
+impl Display for Comment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Comment::Line(text) => write!(f, "//{text}"),
+            Comment::Block(text) => write!(f, "/*{text}*/"),
+        }
+    }
+}
+
+/// Where a token starts in the source, counting lines and columns from 1.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
 #[derive(Debug)]
 pub struct Token {
     pub kind: TokenKind,
+    /// Comments found between the previous token and this one.
+    pub comments: Vec<Comment>,
+    pub position: Position,
 }
 
 impl Token {
     pub fn new(kind: TokenKind) -> Self {
-        Self { kind }
+        Self::with_comments(kind, Vec::new())
+    }
+
+    pub fn with_comments(kind: TokenKind, comments: Vec<Comment>) -> Self {
+        Self {
+            kind,
+            comments,
+            position: Position::default(),
+        }
+    }
+
+    pub fn at(self, position: Position) -> Self {
+        Self { position, ..self }
     }
 }
@@ -1,4 +1,4 @@
 mod evaluator;
-mod lexer;
-mod parser;
+pub mod lexer;
+pub mod parser;
 pub mod repl;
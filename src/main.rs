@@ -1,8 +1,15 @@
-use std::io;
+use std::{env, io, path::Path};
 
 use monkey::repl;
 
 fn main() {
+    if let Some(path) = env::args().nth(1) {
+        if let Err(e) = repl::run_file(Path::new(&path)) {
+            eprintln!("could not run {path}: {e}");
+        }
+        return;
+    }
+
     println!("Hello! This is the Monkey programming language!");
     println!("Feel free to type in commands");
 
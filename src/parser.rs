@@ -1,15 +1,24 @@
 pub mod ast;
 
-use ast::{Expression, Identifier, Literal, Program, Statement};
+use std::collections::HashMap;
+
+use ast::{
+    CatchClause, EnumVariant, Expression, Identifier, Literal, MatchArm, Pattern, Program,
+    Statement,
+};
 
 use crate::lexer::{
-    token::{Token, TokenKind},
+    token::{Position, StringPart, Token, TokenKind},
     Lexer,
 };
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
 pub enum Precedence {
     Lowest,
+    Assign,
+    Pipeline,
+    Compose,
+    Coalesce,
     Equals,
     LessGreater,
     Sum,
@@ -30,8 +39,15 @@ impl From<&TokenKind> for Precedence {
             TokenKind::Minus => Precedence::Sum,
             TokenKind::Slash => Precedence::Product,
             TokenKind::Asterisk => Precedence::Product,
+            TokenKind::Percent => Precedence::Product,
             TokenKind::Lparen => Precedence::Call,
             TokenKind::Lbracket => Precedence::Index,
+            TokenKind::NullCoalesce => Precedence::Coalesce,
+            TokenKind::Assign => Precedence::Assign,
+            TokenKind::Pipeline => Precedence::Pipeline,
+            TokenKind::Compose => Precedence::Compose,
+            TokenKind::OptionalLbracket => Precedence::Index,
+            TokenKind::Dot | TokenKind::OptionalDot => Precedence::Index,
             _ => Precedence::Lowest,
         }
     }
@@ -43,15 +59,39 @@ pub struct Parser {
     current_token: Token,
     peeked_token: Token,
     errors: Vec<String>,
+    warnings: Vec<String>,
+    /// The constants declared in each enclosing block, innermost last, so
+    /// redeclarations in the same scope are caught before running anything.
+    constants: Vec<HashMap<Identifier, Position>>,
+    /// The variants of every enum declared so far, so matches over them can
+    /// be checked for exhaustiveness and bare unit variants in patterns are
+    /// told apart from bindings.
+    enums: HashMap<Identifier, Vec<EnumVariant>>,
+    /// Set while parsing a match guard outside of any brackets, where
+    /// `(x) => ...` would otherwise take the arm's `=>` as the start of an
+    /// arrow function.
+    in_match_guard: bool,
+    /// Set while parsing the body of a `fn*`, the only place `yield` is
+    /// allowed.
+    in_generator: bool,
+    /// Set while parsing a `try` expression, where a `yield` couldn't
+    /// suspend the generator without being caught as an error.
+    in_try: bool,
 }
 
 impl Parser {
     pub fn new(lexer: Lexer) -> Self {
         let mut parser = Self {
             lexer,
-            current_token: Token::new(TokenKind::Illegal),
-            peeked_token: Token::new(TokenKind::Illegal),
+            current_token: Token::new(TokenKind::Eof),
+            peeked_token: Token::new(TokenKind::Eof),
             errors: Vec::new(),
+            warnings: Vec::new(),
+            constants: vec![HashMap::new()],
+            enums: HashMap::new(),
+            in_match_guard: false,
+            in_generator: false,
+            in_try: false,
         };
 
         parser.next_token();
@@ -75,7 +115,16 @@ impl Parser {
     fn parse_statement(&mut self) -> Option<Statement> {
         let statement_result = match self.current_token.kind {
             TokenKind::Let => self.parse_let_statement(),
+            TokenKind::Const => self.parse_const_statement(),
+            TokenKind::Import => self.parse_import_statement(),
+            TokenKind::Export => self.parse_export_statement(),
+            TokenKind::Struct => self.parse_struct_statement(),
+            TokenKind::Impl => self.parse_impl_statement(),
+            TokenKind::Enum => self.parse_enum_statement(),
             TokenKind::Return => self.parse_return_statement(),
+            TokenKind::Throw => self.parse_throw_statement(),
+            TokenKind::Yield => self.parse_yield_statement(),
+            TokenKind::For => self.parse_for_statement(),
             _ => self.parse_expression_statement(Precedence::Lowest),
         };
 
@@ -92,6 +141,12 @@ impl Parser {
         &self.errors
     }
 
+    /// Problems that don't stop the program from running, like match
+    /// expressions that might not handle every value.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
     fn next_token(&mut self) {
         let previously_peeked = std::mem::replace(&mut self.peeked_token, self.lexer.next_token());
         self.current_token = previously_peeked;
@@ -109,7 +164,7 @@ impl Parser {
         if !self.peek_token_is(kind) {
             return Err(format!(
                 "expected next token to be {kind}, got: {:?}",
-                self.peeked_token
+                self.peeked_token.kind
             ));
         }
 
@@ -118,12 +173,42 @@ impl Parser {
     }
 
     fn parse_let_statement(&mut self) -> Result<Statement, String> {
-        let TokenKind::Ident(name) = &self.peeked_token.kind else {
-            return Err(format!("expected TokenKind to be Identifier(_), got: {:?}", &self.peeked_token.kind));
-        };
-        let name = name.clone();
+        self.next_token();
+        let (pattern, value) = self.parse_let_statement_rest()?;
+        for ident in pattern.identifiers() {
+            self.check_not_constant(ident)?;
+        }
+        Ok(Statement::Let(pattern, value))
+    }
+
+    /// Parses `pattern = value;` starting on the pattern.
+    fn parse_let_statement_rest(&mut self) -> Result<(Pattern, Expression), String> {
+        let pattern = self.parse_pattern()?;
+        self.expect_peek(&TokenKind::Assign)?;
+        self.next_token();
+
+        let value = self.parse_expression(Precedence::Lowest)?;
+        if self.peek_token_is(&TokenKind::Semicolon) {
+            self.next_token();
+        }
+        Ok((pattern, value))
+    }
 
+    fn parse_const_statement(&mut self) -> Result<Statement, String> {
         self.next_token();
+        let TokenKind::Ident(name) = &self.current_token.kind else {
+            let error = format!(
+                "expected a name after const, got: {}",
+                self.current_token.kind
+            );
+            // Skip what looks like the rest of a destructuring `let`, so it
+            // isn't read as further statements.
+            self.parse_let_statement_rest()?;
+            return Err(error);
+        };
+        let name = Identifier(name.clone());
+        let position = self.current_token.position;
+
         self.expect_peek(&TokenKind::Assign)?;
         self.next_token();
 
@@ -132,7 +217,287 @@ impl Parser {
             self.next_token();
         }
 
-        Ok(Statement::Let(Identifier(name), value))
+        self.check_not_constant(&name)?;
+        if let Some(scope) = self.constants.last_mut() {
+            scope.insert(name.clone(), position);
+        }
+        Ok(Statement::Const(name, value, position))
+    }
+
+    fn parse_import_statement(&mut self) -> Result<Statement, String> {
+        self.next_token();
+        let TokenKind::String(path) = &self.current_token.kind else {
+            return Err(format!(
+                "expected a module path after import, got: {}",
+                self.current_token.kind
+            ));
+        };
+        let path = path.clone();
+
+        self.expect_peek(&TokenKind::As)?;
+        let name = self.expect_identifier()?;
+        if self.peek_token_is(&TokenKind::Semicolon) {
+            self.next_token();
+        }
+
+        self.check_not_constant(&name)?;
+        Ok(Statement::Import(path, name))
+    }
+
+    fn parse_export_statement(&mut self) -> Result<Statement, String> {
+        if self.constants.len() > 1 {
+            return Err("export is only allowed at the top level of a module".to_string());
+        }
+
+        self.next_token();
+        let statement = match self.current_token.kind {
+            TokenKind::Let => self.parse_let_statement()?,
+            TokenKind::Const => self.parse_const_statement()?,
+            _ => {
+                return Err(format!(
+                    "expected let or const after export, got: {}",
+                    self.current_token.kind
+                ))
+            }
+        };
+        Ok(Statement::Export(Box::new(statement)))
+    }
+
+    fn parse_struct_statement(&mut self) -> Result<Statement, String> {
+        let name = self.expect_identifier()?;
+        self.expect_peek(&TokenKind::Lbrace)?;
+
+        let mut fields: Vec<Identifier> = Vec::new();
+        while !self.peek_token_is(&TokenKind::Rbrace) {
+            let field = self.expect_identifier()?;
+            if fields.contains(&field) {
+                return Err(format!("duplicate field {field} in struct {name}"));
+            }
+            fields.push(field);
+            if !self.peek_token_is(&TokenKind::Rbrace) {
+                self.expect_peek(&TokenKind::Comma)?;
+            }
+        }
+        self.expect_peek(&TokenKind::Rbrace)?;
+
+        self.check_not_constant(&name)?;
+        Ok(Statement::Struct(name, fields))
+    }
+
+    fn parse_impl_statement(&mut self) -> Result<Statement, String> {
+        let name = self.expect_identifier()?;
+        self.expect_peek(&TokenKind::Lbrace)?;
+
+        let mut methods: Vec<(Identifier, Expression)> = Vec::new();
+        while !self.peek_token_is(&TokenKind::Rbrace) {
+            self.expect_peek(&TokenKind::Function)?;
+            let generator = self.peek_token_is(&TokenKind::Asterisk);
+            if generator {
+                self.next_token();
+            }
+            let method = self.expect_identifier()?;
+            if methods.iter().any(|(existing, _)| existing == &method) {
+                return Err(format!("duplicate method {method} in impl {name}"));
+            }
+            let function = if generator {
+                self.parse_generator_expression()?
+            } else {
+                self.parse_function_expression()?
+            };
+            methods.push((method, function));
+        }
+        self.expect_peek(&TokenKind::Rbrace)?;
+
+        Ok(Statement::Impl(name, methods))
+    }
+
+    fn parse_enum_statement(&mut self) -> Result<Statement, String> {
+        let name = self.expect_identifier()?;
+        self.expect_peek(&TokenKind::Lbrace)?;
+
+        let mut variants: Vec<EnumVariant> = Vec::new();
+        while !self.peek_token_is(&TokenKind::Rbrace) {
+            let variant = self.expect_identifier()?;
+            if variants.iter().any(|existing| existing.name == variant) {
+                return Err(format!("duplicate variant {variant} in enum {name}"));
+            }
+
+            let fields = if self.peek_token_is(&TokenKind::Lparen) {
+                self.next_token();
+                let mut fields = Vec::new();
+                while !self.peek_token_is(&TokenKind::Rparen) {
+                    let field = self.expect_identifier()?;
+                    fields.push(field);
+                    if !self.peek_token_is(&TokenKind::Rparen) {
+                        self.expect_peek(&TokenKind::Comma)?;
+                    }
+                }
+                self.expect_peek(&TokenKind::Rparen)?;
+                Some(fields)
+            } else {
+                None
+            };
+            variants.push(EnumVariant {
+                name: variant,
+                fields,
+            });
+
+            if !self.peek_token_is(&TokenKind::Rbrace) {
+                self.expect_peek(&TokenKind::Comma)?;
+            }
+        }
+        self.expect_peek(&TokenKind::Rbrace)?;
+
+        self.check_not_constant(&name)?;
+        for variant in &variants {
+            self.check_not_constant(&variant.name)?;
+        }
+        self.enums.insert(name.clone(), variants.clone());
+        Ok(Statement::Enum(name, variants))
+    }
+
+    /// Fails if `name` is a constant of the current block. Statements check
+    /// this once they've been parsed in full, so a failed one doesn't leave
+    /// the rest of itself behind to be misread as another statement.
+    fn check_not_constant(&self, name: &Identifier) -> Result<(), String> {
+        match self.constants.last().and_then(|scope| scope.get(name)) {
+            Some(position) => Err(format!(
+                "cannot redeclare constant {name}, declared at {position}"
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn parse_pattern(&mut self) -> Result<Pattern, String> {
+        let literal = match &self.current_token.kind {
+            TokenKind::Ident(name) if name == "_" => return Ok(Pattern::Wildcard),
+            TokenKind::Ident(_)
+                if self.peek_token_is(&TokenKind::Lparen)
+                    || self.peek_token_is(&TokenKind::Dot) =>
+            {
+                return self.parse_variant_pattern()
+            }
+            TokenKind::Ident(name) if self.is_unit_variant(name) => {
+                return Ok(Pattern::Variant(None, Identifier(name.clone()), None))
+            }
+            TokenKind::Ident(name) => return Ok(Pattern::Identifier(Identifier(name.clone()))),
+            TokenKind::Lbracket => return self.parse_array_pattern(),
+            TokenKind::Lbrace => return self.parse_hash_pattern(),
+            TokenKind::Int(int) => Literal::Integer(*int),
+            TokenKind::BigInt(digits) => Literal::BigInteger(digits.clone()),
+            TokenKind::Float(float) => Literal::Float(*float),
+            TokenKind::String(string) => Literal::String(string.clone()),
+            TokenKind::True => Literal::Boolean(true),
+            TokenKind::False => Literal::Boolean(false),
+            TokenKind::Null => Literal::Null,
+            TokenKind::Minus => {
+                self.next_token();
+                match &self.current_token.kind {
+                    TokenKind::Int(int) => Literal::Integer(-int),
+                    TokenKind::BigInt(digits) => Literal::BigInteger(format!("-{digits}")),
+                    TokenKind::Float(float) => Literal::Float(-float),
+                    kind => return Err(format!("expected a number after '-', got: {kind}")),
+                }
+            }
+            kind => return Err(format!("expected a pattern, got: {kind}")),
+        };
+        Ok(Pattern::Literal(literal))
+    }
+
+    /// Whether `name` is a variant without fields of an enum declared so far,
+    /// which a bare name in a pattern matches rather than binds.
+    fn is_unit_variant(&self, name: &str) -> bool {
+        self.enums
+            .values()
+            .flatten()
+            .any(|variant| variant.name.0 == name && variant.fields.is_none())
+    }
+
+    /// Parses `Variant(patterns)`, `Enum.Variant(patterns)` or `Enum.Variant`,
+    /// starting on the first name.
+    fn parse_variant_pattern(&mut self) -> Result<Pattern, String> {
+        let TokenKind::Ident(first) = &self.current_token.kind else {
+            unreachable!("variant patterns start with a name")
+        };
+        let mut enum_name = None;
+        let mut name = Identifier(first.clone());
+        if self.peek_token_is(&TokenKind::Dot) {
+            self.next_token();
+            let variant = self.expect_identifier()?;
+            enum_name = Some(name);
+            name = variant;
+        }
+
+        let mut payload = None;
+        if self.peek_token_is(&TokenKind::Lparen) {
+            self.next_token();
+            let mut patterns = Vec::new();
+            while !self.peek_token_is(&TokenKind::Rparen) {
+                self.next_token();
+                patterns.push(self.parse_pattern()?);
+                if !self.peek_token_is(&TokenKind::Rparen) {
+                    self.expect_peek(&TokenKind::Comma)?;
+                }
+            }
+            self.expect_peek(&TokenKind::Rparen)?;
+            payload = Some(patterns);
+        }
+        Ok(Pattern::Variant(enum_name, name, payload))
+    }
+
+    fn parse_array_pattern(&mut self) -> Result<Pattern, String> {
+        let mut elements = Vec::new();
+        let mut rest = None;
+        while !self.peek_token_is(&TokenKind::Rbracket) {
+            self.next_token();
+            if self.current_token_is(&TokenKind::Ellipsis) {
+                let ident = self.expect_identifier()?;
+                rest = Some(ident);
+                break;
+            }
+
+            elements.push(self.parse_pattern()?);
+            if !self.peek_token_is(&TokenKind::Rbracket) {
+                self.expect_peek(&TokenKind::Comma)?;
+            }
+        }
+        self.expect_peek(&TokenKind::Rbracket)?;
+        Ok(Pattern::Array(elements, rest))
+    }
+
+    fn parse_hash_pattern(&mut self) -> Result<Pattern, String> {
+        let mut entries = Vec::new();
+        while !self.peek_token_is(&TokenKind::Rbrace) {
+            self.next_token();
+            let key = match &self.current_token.kind {
+                TokenKind::Ident(name) => Literal::String(name.clone()),
+                TokenKind::String(string) => Literal::String(string.clone()),
+                TokenKind::Int(int) => Literal::Integer(*int),
+                TokenKind::True => Literal::Boolean(true),
+                TokenKind::False => Literal::Boolean(false),
+                kind => return Err(format!("expected a hash pattern key, got: {kind}")),
+            };
+
+            let pattern = match (
+                &self.current_token.kind,
+                self.peek_token_is(&TokenKind::Colon),
+            ) {
+                (_, true) => {
+                    self.next_token();
+                    self.next_token();
+                    self.parse_pattern()?
+                }
+                (TokenKind::Ident(name), false) => Pattern::Identifier(Identifier(name.clone())),
+                (kind, false) => return Err(format!("expected ':' after hash pattern key {kind}")),
+            };
+            entries.push((key, pattern));
+
+            if !self.peek_token_is(&TokenKind::Rbrace) {
+                self.expect_peek(&TokenKind::Comma)?;
+            }
+        }
+        self.expect_peek(&TokenKind::Rbrace)?;
+        Ok(Pattern::Hash(entries))
     }
 
     fn parse_return_statement(&mut self) -> Result<Statement, String> {
@@ -146,43 +511,96 @@ impl Parser {
         Ok(Statement::Return(return_value))
     }
 
-    fn parse_block_statement(&mut self) -> Result<Statement, String> {
-        let mut statements = Vec::new();
+    fn parse_throw_statement(&mut self) -> Result<Statement, String> {
         self.next_token();
 
-        while !self.current_token_is(&TokenKind::Rbrace) && !self.current_token_is(&TokenKind::Eof)
-        {
-            if let Some(statement) = self.parse_statement() {
-                statements.push(statement);
-            }
+        let thrown = self.parse_expression(Precedence::Lowest)?;
+        if self.peek_token_is(&TokenKind::Semicolon) {
+            self.next_token();
+        }
+
+        Ok(Statement::Throw(thrown))
+    }
+
+    fn parse_yield_statement(&mut self) -> Result<Statement, String> {
+        if !self.in_generator {
+            return Err("yield outside of a generator function".to_string());
+        }
+        if self.in_try {
+            return Err("yield inside of a try, catch or finally block".to_string());
+        }
+        self.next_token();
+
+        let value = self.parse_expression(Precedence::Lowest)?;
+        if self.peek_token_is(&TokenKind::Semicolon) {
             self.next_token();
         }
-        Ok(Statement::Block(statements))
+
+        Ok(Statement::Yield(value))
+    }
+
+    fn parse_for_statement(&mut self) -> Result<Statement, String> {
+        self.expect_peek(&TokenKind::Lparen)?;
+        self.next_token();
+        let pattern = self.parse_pattern()?;
+        self.expect_peek(&TokenKind::In)?;
+        self.next_token();
+        let iterable = self.parse_expression(Precedence::Lowest)?;
+        self.expect_peek(&TokenKind::Rparen)?;
+        self.expect_peek(&TokenKind::Lbrace)?;
+        let body = self.parse_block_statement()?;
+
+        Ok(Statement::For(pattern, iterable, Box::new(body)))
+    }
+
+    fn parse_block_statement(&mut self) -> Result<Statement, String> {
+        self.in_brackets(|parser| {
+            let mut statements = Vec::new();
+            parser.next_token();
+
+            parser.in_new_scope(|parser| {
+                while !parser.current_token_is(&TokenKind::Rbrace)
+                    && !parser.current_token_is(&TokenKind::Eof)
+                {
+                    if let Some(statement) = parser.parse_statement() {
+                        statements.push(statement);
+                    }
+                    parser.next_token();
+                }
+            });
+            Ok(Statement::Block(statements))
+        })
     }
 
-    fn parse_function_params(&mut self) -> Result<Vec<Expression>, String> {
-        let mut identifiers = Vec::new();
+    fn parse_function_params(&mut self) -> Result<Vec<Pattern>, String> {
+        let mut params = Vec::new();
         if self.peek_token_is(&TokenKind::Rparen) {
             self.next_token();
-            return Ok(identifiers);
+            return Ok(params);
         }
 
-        identifiers.push(self.parse_identifier()?);
+        self.next_token();
+        params.push(self.parse_pattern()?);
         while self.peek_token_is(&TokenKind::Comma) {
             self.next_token();
-            identifiers.push(self.parse_identifier()?);
+            self.next_token();
+            params.push(self.parse_pattern()?);
         }
         self.expect_peek(&TokenKind::Rparen)?;
 
-        Ok(identifiers)
+        Ok(params)
     }
 
-    fn parse_identifier(&mut self) -> Result<Expression, String> {
+    /// Moves on to the next token, which must be a name, and returns it.
+    fn expect_identifier(&mut self) -> Result<Identifier, String> {
         self.next_token();
         let TokenKind::Ident(value) = &self.current_token.kind else {
-            return Err(format!("Expected an identifier. Got {:?}", self.current_token));
+            return Err(format!(
+                "Expected an identifier. Got {}",
+                self.current_token.kind
+            ));
         };
-        Ok(Expression::Identifier(Identifier(value.clone())))
+        Ok(Identifier(value.clone()))
     }
 
     fn parse_expression_statement(&mut self, precedence: Precedence) -> Result<Statement, String> {
@@ -195,6 +613,9 @@ impl Parser {
     }
 
     fn parse_expression(&mut self, precedence: Precedence) -> Result<Expression, String> {
+        if let TokenKind::Illegal(reason) = &self.current_token.kind {
+            return Err(format!("illegal token: {reason}"));
+        }
         if !Parser::has_parse_prefix_fn(&self.current_token.kind) {
             return Err(format!(
                 "Expected a prefix. Got: {}",
@@ -221,14 +642,22 @@ impl Parser {
             kind,
             TokenKind::Ident(_)
                 | TokenKind::Int(_)
+                | TokenKind::BigInt(_)
+                | TokenKind::Float(_)
                 | TokenKind::String(_)
+                | TokenKind::InterpolatedString(_)
                 | TokenKind::True
                 | TokenKind::False
+                | TokenKind::Null
                 | TokenKind::Bang
                 | TokenKind::Minus
                 | TokenKind::Lparen
+                | TokenKind::Bar
                 | TokenKind::If
+                | TokenKind::Match
+                | TokenKind::Try
                 | TokenKind::Function
+                | TokenKind::Macro
                 | TokenKind::Lbracket
                 | TokenKind::Lbrace
         )
@@ -241,39 +670,58 @@ impl Parser {
                 | TokenKind::Minus
                 | TokenKind::Asterisk
                 | TokenKind::Slash
+                | TokenKind::Percent
                 | TokenKind::LessThan
                 | TokenKind::GreaterThan
                 | TokenKind::Equal
                 | TokenKind::NotEqual
                 | TokenKind::Lparen
                 | TokenKind::Lbracket
+                | TokenKind::NullCoalesce
+                | TokenKind::OptionalLbracket
+                | TokenKind::Dot
+                | TokenKind::OptionalDot
+                | TokenKind::Assign
+                | TokenKind::Pipeline
+                | TokenKind::Compose
         )
     }
 
     fn parse_prefix(&mut self) -> Result<Expression, String> {
         let expr = match &self.current_token.kind {
+            TokenKind::Ident(value) if self.peek_token_is(&TokenKind::Lbrace) => {
+                let name = Identifier(value.clone());
+                self.parse_struct_literal(name)?
+            }
             TokenKind::Ident(value) => Expression::Identifier(Identifier(value.clone())),
             TokenKind::Int(value) => Expression::Literal(Literal::Integer(*value)),
+            TokenKind::BigInt(digits) => Expression::Literal(Literal::BigInteger(digits.clone())),
+            TokenKind::Float(value) => Expression::Literal(Literal::Float(*value)),
             TokenKind::String(string) => Expression::Literal(Literal::String(string.clone())),
+            TokenKind::InterpolatedString(parts) => Parser::parse_interpolated_string(parts)?,
             TokenKind::True => Expression::Literal(Literal::Boolean(true)),
             TokenKind::False => Expression::Literal(Literal::Boolean(false)),
+            TokenKind::Null => Expression::Literal(Literal::Null),
             TokenKind::Minus | TokenKind::Bang => {
                 let token = self.current_token.kind.clone();
                 self.next_token();
                 Expression::Prefix(token, Box::new(self.parse_expression(Precedence::Prefix)?))
             }
-            TokenKind::Lparen => {
+            TokenKind::Lparen => self.parse_grouped_expression()?,
+            TokenKind::Bar => self.parse_bar_lambda()?,
+            TokenKind::If => self.parse_if_expression()?,
+            TokenKind::Match => self.parse_match_expression()?,
+            TokenKind::Try => self.parse_try_expression()?,
+            TokenKind::Function if self.peek_token_is(&TokenKind::Asterisk) => {
                 self.next_token();
-                let expression = self.parse_expression(Precedence::Lowest);
-                self.expect_peek(&TokenKind::Rparen)?;
-                return expression;
+                self.parse_generator_expression()?
             }
-            TokenKind::If => self.parse_if_expression()?,
             TokenKind::Function => self.parse_function_expression()?,
+            TokenKind::Macro => self.parse_macro_expression()?,
             TokenKind::Lbracket => {
                 Expression::Array(self.parse_expression_list(&TokenKind::Rbracket)?)
             }
-            TokenKind::Lbrace => self.parse_hash_expression()?,
+            TokenKind::Lbrace => self.in_brackets(Self::parse_hash_expression)?,
             _ => unimplemented!(),
         };
 
@@ -287,10 +735,56 @@ impl Parser {
                 Expression::Call(Box::new(left), right)
             }
             TokenKind::Lbracket => {
+                self.in_brackets(|parser| parser.parse_index_expression(left))?
+            }
+            TokenKind::OptionalLbracket => {
                 self.next_token();
-                let index = self.parse_expression(Precedence::Lowest)?;
+                let index =
+                    self.in_brackets(|parser| parser.parse_expression(Precedence::Lowest))?;
                 self.expect_peek(&TokenKind::Rbracket)?;
-                Expression::Index(Box::new(left), Box::new(index))
+                Expression::OptionalIndex(Box::new(left), Box::new(index))
+            }
+            TokenKind::Dot => {
+                let name = self.expect_identifier()?;
+                Expression::Field(Box::new(left), name)
+            }
+            TokenKind::Assign => {
+                if !matches!(left, Expression::Identifier(_) | Expression::Field(_, _)) {
+                    return Err(format!("invalid assignment target: {left}"));
+                }
+                // A constant can't be shadowed in its own block, so assigning
+                // to its name there can only mean the constant.
+                if let Expression::Identifier(name) = &left {
+                    if let Some(position) = self.constants.last().and_then(|scope| scope.get(name))
+                    {
+                        return Err(format!(
+                            "cannot assign to constant {name}, declared at {position}"
+                        ));
+                    }
+                }
+                self.next_token();
+                // Parsing the value at the lowest precedence makes
+                // `a.x = b.y = 1` assign right to left.
+                let value = self.parse_expression(Precedence::Lowest)?;
+                Expression::Assign(Box::new(left), Box::new(value))
+            }
+            TokenKind::Pipeline => {
+                self.next_token();
+                let right = self.parse_expression(Precedence::Pipeline)?;
+                // `x |> f(a)` is just another way to write `f(x, a)`, and
+                // `x |> f` is `f(x)`.
+                match right {
+                    Expression::Call(function, mut args) => {
+                        args.insert(0, left);
+                        Expression::Call(function, args)
+                    }
+                    function => Expression::Call(Box::new(function), vec![left]),
+                }
+            }
+            TokenKind::OptionalDot => {
+                let Identifier(name) = self.expect_identifier()?;
+                let index = Expression::Literal(Literal::String(name));
+                Expression::OptionalIndex(Box::new(left), Box::new(index))
             }
             _ => {
                 let token = self.current_token.kind.clone();
@@ -305,6 +799,29 @@ impl Parser {
         Ok(expression)
     }
 
+    fn parse_index_expression(&mut self, left: Expression) -> Result<Expression, String> {
+        self.next_token();
+        let mut start = None;
+        if !self.current_token_is(&TokenKind::Colon) {
+            let index = self.parse_expression(Precedence::Lowest)?;
+            if !self.peek_token_is(&TokenKind::Colon) {
+                self.expect_peek(&TokenKind::Rbracket)?;
+                return Ok(Expression::Index(Box::new(left), Box::new(index)));
+            }
+            start = Some(Box::new(index));
+            self.next_token();
+        }
+
+        let mut end = None;
+        if !self.peek_token_is(&TokenKind::Rbracket) {
+            self.next_token();
+            end = Some(Box::new(self.parse_expression(Precedence::Lowest)?));
+        }
+        self.expect_peek(&TokenKind::Rbracket)?;
+
+        Ok(Expression::Slice(Box::new(left), start, end))
+    }
+
     fn parse_if_expression(&mut self) -> Result<Expression, String> {
         self.expect_peek(&TokenKind::Lparen)?;
         self.next_token();
@@ -318,9 +835,17 @@ impl Parser {
         let mut alternative = None;
         if self.peek_token_is(&TokenKind::Else) {
             self.next_token();
-            self.expect_peek(&TokenKind::Lbrace)?;
 
-            alternative = Some(Box::new(self.parse_block_statement()?));
+            // `else if` is sugar for an else branch holding a single if
+            // expression, so chains nest to the right.
+            let statement = if self.peek_token_is(&TokenKind::If) {
+                self.next_token();
+                Statement::Expression(self.parse_if_expression()?)
+            } else {
+                self.expect_peek(&TokenKind::Lbrace)?;
+                self.parse_block_statement()?
+            };
+            alternative = Some(Box::new(statement));
         }
 
         Ok(Expression::If(
@@ -330,15 +855,352 @@ impl Parser {
         ))
     }
 
+    fn parse_try_expression(&mut self) -> Result<Expression, String> {
+        let enclosing = std::mem::replace(&mut self.in_try, true);
+        let parsed = self.parse_try_clauses();
+        self.in_try = enclosing;
+        parsed
+    }
+
+    fn parse_try_clauses(&mut self) -> Result<Expression, String> {
+        self.expect_peek(&TokenKind::Lbrace)?;
+        let body = self.parse_block_statement()?;
+
+        let mut catch = None;
+        if self.peek_token_is(&TokenKind::Catch) {
+            self.next_token();
+            let mut binding = None;
+            if self.peek_token_is(&TokenKind::Lparen) {
+                self.next_token();
+                self.next_token();
+                binding = Some(self.parse_pattern()?);
+                self.expect_peek(&TokenKind::Rparen)?;
+            }
+            self.expect_peek(&TokenKind::Lbrace)?;
+            let body = self.parse_block_statement()?;
+            catch = Some(Box::new(CatchClause { binding, body }));
+        }
+
+        let mut finally = None;
+        if self.peek_token_is(&TokenKind::Finally) {
+            self.next_token();
+            self.expect_peek(&TokenKind::Lbrace)?;
+            finally = Some(Box::new(self.parse_block_statement()?));
+        }
+
+        if catch.is_none() && finally.is_none() {
+            return Err(format!(
+                "expected catch or finally after try block, got: {}",
+                self.peeked_token.kind
+            ));
+        }
+
+        Ok(Expression::Try(Box::new(body), catch, finally))
+    }
+
+    fn parse_match_expression(&mut self) -> Result<Expression, String> {
+        self.expect_peek(&TokenKind::Lparen)?;
+        self.next_token();
+        let value = self.parse_expression(Precedence::Lowest)?;
+        self.expect_peek(&TokenKind::Rparen)?;
+        self.expect_peek(&TokenKind::Lbrace)?;
+
+        let mut arms = Vec::new();
+        while !self.peek_token_is(&TokenKind::Rbrace) {
+            self.next_token();
+            arms.push(self.parse_match_arm()?);
+            if !self.peek_token_is(&TokenKind::Rbrace) {
+                self.expect_peek(&TokenKind::Comma)?;
+            }
+        }
+        self.expect_peek(&TokenKind::Rbrace)?;
+
+        self.check_match_exhaustiveness(&arms);
+        Ok(Expression::Match(Box::new(value), arms))
+    }
+
+    fn parse_match_arm(&mut self) -> Result<MatchArm, String> {
+        let pattern = self.parse_pattern()?;
+
+        // The names the pattern binds shadow any constants of the same name.
+        let (guard, body) = self.in_new_scope(|parser| {
+            let mut guard = None;
+            if parser.peek_token_is(&TokenKind::If) {
+                parser.next_token();
+                parser.next_token();
+                parser.in_match_guard = true;
+                let parsed = parser.parse_expression(Precedence::Lowest);
+                parser.in_match_guard = false;
+                guard = Some(parsed?);
+            }
+
+            parser.expect_peek(&TokenKind::FatArrow)?;
+            parser.next_token();
+            let body = if parser.current_token_is(&TokenKind::Lbrace) {
+                parser.parse_block_statement()?
+            } else {
+                Statement::Expression(parser.parse_expression(Precedence::Lowest)?)
+            };
+            Ok::<_, String>((guard, body))
+        })?;
+
+        Ok(MatchArm {
+            pattern,
+            guard,
+            body,
+        })
+    }
+
+    /// Values are dynamically typed, so a match is only known to be
+    /// exhaustive if it has a catch-all arm, covers both booleans or covers
+    /// every variant of an enum declared earlier in the program.
+    fn check_match_exhaustiveness(&mut self, arms: &[MatchArm]) {
+        let catch_all = arms.iter().position(|arm| arm.is_catch_all());
+        if let Some(position) = catch_all {
+            for arm in &arms[position + 1..] {
+                self.warnings
+                    .push(format!("unreachable match arm: {}", arm.pattern));
+            }
+            return;
+        }
+
+        let covers_boolean = |value| {
+            arms.iter().any(|arm| {
+                arm.guard.is_none() && arm.pattern == Pattern::Literal(Literal::Boolean(value))
+            })
+        };
+        let covers_booleans = covers_boolean(true) && covers_boolean(false);
+        if !(covers_booleans || self.covers_enum(arms)) {
+            self.warnings.push(
+                "match expression may not be exhaustive, consider adding a `_` arm".to_string(),
+            );
+        }
+    }
+
+    fn covers_enum(&self, arms: &[MatchArm]) -> bool {
+        let covered = |enum_name: &Identifier, variant: &Identifier| {
+            arms.iter().any(|arm| match &arm.pattern {
+                Pattern::Variant(qualifier, name, payload) if arm.guard.is_none() => {
+                    name == variant
+                        && qualifier.as_ref().is_none_or(|q| q == enum_name)
+                        && payload.iter().flatten().all(|pattern| {
+                            matches!(pattern, Pattern::Wildcard | Pattern::Identifier(_))
+                        })
+                }
+                _ => false,
+            })
+        };
+        self.enums.iter().any(|(enum_name, variants)| {
+            variants
+                .iter()
+                .all(|variant| covered(enum_name, &variant.name))
+        })
+    }
+
     fn parse_function_expression(&mut self) -> Result<Expression, String> {
         self.expect_peek(&TokenKind::Lparen)?;
         let params = self.parse_function_params()?;
         self.expect_peek(&TokenKind::Lbrace)?;
-        let body = self.parse_block_statement()?;
+        let body = self.parse_function_body(false, Self::parse_block_statement)?;
 
         Ok(Expression::Function(params, Box::new(body)))
     }
 
+    /// Parses `(params) { body }` after `fn*`.
+    fn parse_generator_expression(&mut self) -> Result<Expression, String> {
+        self.expect_peek(&TokenKind::Lparen)?;
+        let params = self.parse_function_params()?;
+        self.expect_peek(&TokenKind::Lbrace)?;
+        let body = self.parse_function_body(true, Self::parse_block_statement)?;
+
+        Ok(Expression::Generator(params, Box::new(body)))
+    }
+
+    /// Parses the body of a function with `parse`, noting whether it's a
+    /// generator so `yield` is only accepted where it belongs.
+    fn parse_function_body(
+        &mut self,
+        generator: bool,
+        parse: fn(&mut Self) -> Result<Statement, String>,
+    ) -> Result<Statement, String> {
+        let enclosing = std::mem::replace(&mut self.in_generator, generator);
+        let enclosing_try = std::mem::replace(&mut self.in_try, false);
+        let body = parse(self);
+        self.in_generator = enclosing;
+        self.in_try = enclosing_try;
+        body
+    }
+
+    /// Parses `(expression)`, or the parameters of an arrow function like
+    /// `(x, y) => x + y`, which can only be told apart by the `=>` after the
+    /// closing parenthesis.
+    fn parse_grouped_expression(&mut self) -> Result<Expression, String> {
+        let mut expressions = self.parse_expression_list(&TokenKind::Rparen)?;
+        if self.peek_token_is(&TokenKind::FatArrow) && !self.in_match_guard {
+            let params = expressions
+                .into_iter()
+                .map(Self::expression_to_pattern)
+                .collect::<Result<Vec<_>, _>>()?;
+            self.next_token();
+            return self.parse_lambda_body(params);
+        }
+        if expressions.len() != 1 {
+            self.expect_peek(&TokenKind::FatArrow)?;
+        }
+        Ok(expressions.remove(0))
+    }
+
+    /// Reinterprets an arrow function parameter, which was parsed as an
+    /// expression before the `=>` showed up, as the pattern it's written like.
+    fn expression_to_pattern(expression: Expression) -> Result<Pattern, String> {
+        let pattern = match expression {
+            Expression::Identifier(ident) if ident.0 == "_" => Pattern::Wildcard,
+            Expression::Identifier(ident) => Pattern::Identifier(ident),
+            Expression::Literal(literal) => Pattern::Literal(literal),
+            Expression::Prefix(TokenKind::Minus, right) => match *right {
+                Expression::Literal(Literal::Integer(int)) => {
+                    Pattern::Literal(Literal::Integer(-int))
+                }
+                Expression::Literal(Literal::BigInteger(digits)) => {
+                    Pattern::Literal(Literal::BigInteger(format!("-{digits}")))
+                }
+                Expression::Literal(Literal::Float(float)) => {
+                    Pattern::Literal(Literal::Float(-float))
+                }
+                right => return Err(format!("invalid parameter: (-{right})")),
+            },
+            Expression::Array(elements) => Pattern::Array(
+                elements
+                    .into_iter()
+                    .map(Self::expression_to_pattern)
+                    .collect::<Result<_, _>>()?,
+                None,
+            ),
+            Expression::Hash(pairs) => {
+                let mut entries = Vec::with_capacity(pairs.len());
+                for (key, value) in pairs {
+                    // As in hash patterns, a bare name is a string key.
+                    let key = match key {
+                        Expression::Identifier(Identifier(name)) => Literal::String(name),
+                        Expression::Literal(literal) => literal,
+                        key => return Err(format!("invalid hash pattern key: {key}")),
+                    };
+                    entries.push((key, Self::expression_to_pattern(value)?));
+                }
+                Pattern::Hash(entries)
+            }
+            Expression::Call(function, args) => {
+                let (enum_name, name) = match *function {
+                    Expression::Identifier(name) => (None, name),
+                    Expression::Field(left, name) => match *left {
+                        Expression::Identifier(enum_name) => (Some(enum_name), name),
+                        left => return Err(format!("invalid parameter: {left}")),
+                    },
+                    function => return Err(format!("invalid parameter: {function}")),
+                };
+                let payload = args
+                    .into_iter()
+                    .map(Self::expression_to_pattern)
+                    .collect::<Result<_, _>>()?;
+                Pattern::Variant(enum_name, name, Some(payload))
+            }
+            Expression::Field(left, name) => match *left {
+                Expression::Identifier(enum_name) => Pattern::Variant(Some(enum_name), name, None),
+                left => return Err(format!("invalid parameter: ({left}.{name})")),
+            },
+            expression => return Err(format!("invalid parameter: {expression}")),
+        };
+        Ok(pattern)
+    }
+
+    /// Parses `|params| body`. A `||` lexes as two bars, so it works for
+    /// lambdas without parameters too.
+    fn parse_bar_lambda(&mut self) -> Result<Expression, String> {
+        let mut params = Vec::new();
+        while !self.peek_token_is(&TokenKind::Bar) {
+            self.next_token();
+            params.push(self.parse_pattern()?);
+            if !self.peek_token_is(&TokenKind::Bar) {
+                self.expect_peek(&TokenKind::Comma)?;
+            }
+        }
+        self.expect_peek(&TokenKind::Bar)?;
+        self.parse_lambda_body(params)
+    }
+
+    /// Parses what follows the parameters of a short lambda: either a block,
+    /// or a single expression that becomes the whole body, so the lambda is
+    /// the same function as one written with `fn`.
+    fn parse_lambda_body(&mut self, params: Vec<Pattern>) -> Result<Expression, String> {
+        self.next_token();
+        let body = self.parse_function_body(false, |parser| {
+            if parser.current_token_is(&TokenKind::Lbrace) {
+                return parser.parse_block_statement();
+            }
+            // The parameters shadow any constants of the same name.
+            let body = parser.in_new_scope(|parser| parser.parse_expression(Precedence::Lowest))?;
+            Ok(Statement::Block(vec![Statement::Expression(body)]))
+        })?;
+        Ok(Expression::Function(params, Box::new(body)))
+    }
+
+    fn parse_macro_expression(&mut self) -> Result<Expression, String> {
+        let Expression::Function(params, body) = self.parse_function_expression()? else {
+            unreachable!("parse_function_expression only returns functions")
+        };
+        Ok(Expression::Macro(params, body))
+    }
+
+    fn parse_interpolated_string(parts: &[StringPart]) -> Result<Expression, String> {
+        let mut expressions = Vec::with_capacity(parts.len());
+        for part in parts {
+            let expression = match part {
+                StringPart::Literal(string) => Expression::Literal(Literal::String(string.clone())),
+                StringPart::Code(code) => {
+                    let mut parser = Parser::new(Lexer::new(code));
+                    if parser.current_token_is(&TokenKind::Eof) {
+                        return Err("empty string interpolation".to_string());
+                    }
+                    let expression = parser.parse_expression(Precedence::Lowest)?;
+                    parser.expect_peek(&TokenKind::Eof)?;
+                    expression
+                }
+            };
+            expressions.push(expression);
+        }
+        Ok(Expression::Interpolated(expressions))
+    }
+
+    /// Parses `Name { field: value, other }`, where a bare field name is
+    /// short for `field: field`.
+    fn parse_struct_literal(&mut self, name: Identifier) -> Result<Expression, String> {
+        self.next_token();
+
+        let mut fields: Vec<(Identifier, Expression)> = Vec::new();
+        while !self.peek_token_is(&TokenKind::Rbrace) {
+            let field = self.expect_identifier()?;
+            if fields.iter().any(|(existing, _)| existing == &field) {
+                return Err(format!("duplicate field {field} in {name} literal"));
+            }
+
+            let value = if self.peek_token_is(&TokenKind::Colon) {
+                self.next_token();
+                self.next_token();
+                self.parse_expression(Precedence::Lowest)?
+            } else {
+                Expression::Identifier(field.clone())
+            };
+            fields.push((field, value));
+
+            if !self.peek_token_is(&TokenKind::Rbrace) {
+                self.expect_peek(&TokenKind::Comma)?;
+            }
+        }
+        self.expect_peek(&TokenKind::Rbrace)?;
+
+        Ok(Expression::StructLiteral(name, fields))
+    }
+
     fn parse_hash_expression(&mut self) -> Result<Expression, String> {
         let mut pairs: Vec<(Expression, Expression)> = Vec::new();
         while !self.peek_token_is(&TokenKind::Rbrace) {
@@ -359,23 +1221,47 @@ impl Parser {
     }
 
     fn parse_expression_list(&mut self, end: &TokenKind) -> Result<Vec<Expression>, String> {
-        let mut list = Vec::new();
-        if self.peek_token_is(end) {
-            self.next_token();
-            return Ok(list);
-        }
+        self.in_brackets(|parser| {
+            let mut list = Vec::new();
+            if parser.peek_token_is(end) {
+                parser.next_token();
+                return Ok(list);
+            }
 
-        self.next_token();
-        list.push(self.parse_expression(Precedence::Lowest)?);
+            parser.next_token();
+            list.push(parser.parse_expression(Precedence::Lowest)?);
 
-        while self.peek_token_is(&TokenKind::Comma) {
-            self.next_token();
-            self.next_token();
-            list.push(self.parse_expression(Precedence::Lowest)?);
-        }
+            while parser.peek_token_is(&TokenKind::Comma) {
+                parser.next_token();
+                parser.next_token();
+                list.push(parser.parse_expression(Precedence::Lowest)?);
+            }
+
+            parser.expect_peek(end)?;
+            Ok(list)
+        })
+    }
 
-        self.expect_peek(end)?;
-        Ok(list)
+    /// Parses something enclosed in brackets with `parse`. A `=>` inside
+    /// them can't end a match guard, so arrow functions are allowed there
+    /// even within one.
+    fn in_brackets<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let enclosing = std::mem::replace(&mut self.in_match_guard, false);
+        let parsed = parse(self);
+        self.in_match_guard = enclosing;
+        parsed
+    }
+
+    /// Parses with `parse` in a scope of its own, where constants declared
+    /// in enclosing scopes can be shadowed.
+    fn in_new_scope<T>(&mut self, parse: impl FnOnce(&mut Self) -> T) -> T {
+        self.constants.push(HashMap::new());
+        let parsed = parse(self);
+        self.constants.pop();
+        parsed
     }
 
     fn peek_precedence(&self) -> Precedence {
@@ -452,65 +1338,516 @@ mod tests {
                 return false;
             }
 
-            true
+            true
+        }
+    }
+
+    fn test_literal_expression<T: TestExpression + ?Sized>(
+        expression: &Expression,
+        value: &T,
+    ) -> bool {
+        value.test_expression(expression)
+    }
+
+    fn test_infix_expression<T: TestExpression + ?Sized>(
+        infix: &Expression,
+        left: &T,
+        operator: &str,
+        right: &T,
+    ) -> bool {
+        let Expression::Infix(left_expression, op, right_expresssion) = infix else {
+            eprintln!("expression is not Infix(_,_,_). Got: {:?}", infix);
+            return false;
+        };
+
+        if !left.test_expression(left_expression) {
+            return false;
+        };
+
+        if op.to_string() != operator {
+            eprintln!("expected operator '{operator}'. Got: {:?}", op);
+            return false;
+        }
+
+        if !right.test_expression(right_expresssion) {
+            return false;
+        }
+
+        true
+    }
+
+    #[test]
+    fn test_let_statements() {
+        let inputs: Vec<(&str, &str, &dyn TestExpression)> = vec![
+            ("let x = 5;", "x", &5),
+            ("let y = true;", "y", &true),
+            ("let foobar = y;", "foobar", &"y"),
+        ];
+
+        for input in inputs {
+            let mut parser = Parser::new(Lexer::new(input.0));
+            let program = parser.parse_program();
+
+            assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+            assert_eq!(program.statements.len(), 1, "{:?}", program.statements);
+
+            let Statement::Let(Pattern::Identifier(ident), value) = &program.statements[0] else {
+                panic!("expected a LetStatement(_,_). Got {:?}", program.statements[0]);
+            };
+
+            assert_eq!(&ident.0, input.1);
+            assert!(test_literal_expression(value, input.2));
+        }
+    }
+
+    #[test]
+    fn test_const_statements() {
+        let input = "const answer = 42;\nconst name = \"monkey\";";
+        let mut parser = Parser::new(Lexer::new(input));
+        let program = parser.parse_program();
+
+        assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+        assert_eq!(program.statements.len(), 2);
+
+        let Statement::Const(name, value, position) = &program.statements[1] else {
+            panic!("expected a ConstStatement. Got {:?}", program.statements[1]);
+        };
+        assert_eq!(name.0, "name");
+        assert_eq!(value.to_string(), "monkey");
+        assert_eq!(position, &Position { line: 2, column: 7 });
+        assert_eq!(
+            program.to_string(),
+            "const answer = 42;const name = monkey;"
+        );
+
+        // Shadowing in a nested block is fine, and so is a const shadowing a
+        // plain let.
+        let allowed = vec![
+            "const x = 1; if (true) { const x = 2; }",
+            "const x = 1; let f = fn() { let x = 2; x };",
+            "let x = 1; const x = 2;",
+        ];
+        for input in allowed {
+            let mut parser = Parser::new(Lexer::new(input));
+            parser.parse_program();
+            assert!(parser.errors().is_empty(), "{input}: {:?}", parser.errors());
+        }
+    }
+
+    #[test]
+    fn test_const_redeclaration_errors() {
+        let inputs = vec![
+            (
+                "const x = 1;\nconst x = 2;",
+                "cannot redeclare constant x, declared at line 1, column 7",
+            ),
+            (
+                "const x = 1; let x = 2;",
+                "cannot redeclare constant x, declared at line 1, column 7",
+            ),
+            (
+                "const x = 1;\nlet [a, x] = [1, 2];",
+                "cannot redeclare constant x, declared at line 1, column 7",
+            ),
+            (
+                "if (true) {\n  const limit = 1;\n  let {limit} = config;\n}",
+                "cannot redeclare constant limit, declared at line 2, column 9",
+            ),
+            ("const [a] = x;", "expected a name after const, got: ["),
+            (
+                "const x = 1;\nx = 2;",
+                "cannot assign to constant x, declared at line 1, column 7",
+            ),
+            (
+                "const x = 1; import \"x.mk\" as x;",
+                "cannot redeclare constant x, declared at line 1, column 7",
+            ),
+            (
+                "const x = 1; struct x { a, b }",
+                "cannot redeclare constant x, declared at line 1, column 7",
+            ),
+            (
+                "const x = 1; enum E { A(a, b), x }",
+                "cannot redeclare constant x, declared at line 1, column 7",
+            ),
+        ];
+
+        // Nothing after the redeclaration is mistaken for another statement.
+        for (input, expected) in inputs {
+            let mut parser = Parser::new(Lexer::new(input));
+            parser.parse_program();
+            assert_eq!(parser.errors(), [expected], "{input}");
+        }
+
+        // Parameters and match arm bindings shadow constants.
+        let inputs = [
+            "const x = 1; let f = fn(x) { x = 2 };",
+            "const x = 1; let f = |x| x = 2;",
+            "const x = 1; match (2) { x => x = 3 }",
+            "const x = 1; match ([2]) { [x] if x > 1 => { x = 3 } }",
+        ];
+        for input in inputs {
+            let mut parser = Parser::new(Lexer::new(input));
+            parser.parse_program();
+            assert!(parser.errors().is_empty(), "{input}: {:?}", parser.errors());
+        }
+    }
+
+    #[test]
+    fn test_structs() {
+        let input = "struct Point { x, y }";
+        let mut parser = Parser::new(Lexer::new(input));
+        let program = parser.parse_program();
+
+        assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+        let Statement::Struct(name, fields) = &program.statements[0] else {
+            panic!(
+                "expected a StructStatement. Got {:?}",
+                program.statements[0]
+            );
+        };
+        assert_eq!(name.0, "Point");
+        assert_eq!(
+            fields,
+            &vec![Identifier("x".into()), Identifier("y".into())]
+        );
+
+        let inputs = vec![
+            ("struct Point { x, y }", "struct Point { x, y }"),
+            ("struct Empty {}", "struct Empty {  }"),
+            ("Point { x: 1, y: 2 + 3 }", "Point { x: 1, y: (2 + 3) }"),
+            ("Point { x, y: 2 }", "Point { x: x, y: 2 }"),
+            ("p.x + p.y * 2", "((p.x) + ((p.y) * 2))"),
+            ("p.x = 1 + 2", "((p.x) = (1 + 2))"),
+            ("a.b = c.d = 1", "((a.b) = ((c.d) = 1))"),
+            ("x = p.y = x + 1", "(x = ((p.y) = (x + 1)))"),
+            ("l.to.y = -p.x", "(((l.to).y) = (-(p.x)))"),
+            ("Point { x: 1, y: 2 }.x", "(Point { x: 1, y: 2 }.x)"),
+        ];
+        for (input, expected) in inputs {
+            let mut parser = Parser::new(Lexer::new(input));
+            let program = parser.parse_program();
+            assert!(parser.errors().is_empty(), "{input}: {:?}", parser.errors());
+            assert_eq!(program.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_impl_statements() {
+        let input =
+            "impl Point { fn sum(self) { self.x + self.y } fn new(x, y) { Point { x, y } } }";
+        let mut parser = Parser::new(Lexer::new(input));
+        let program = parser.parse_program();
+
+        assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+        let Statement::Impl(name, methods) = &program.statements[0] else {
+            panic!("expected an ImplStatement. Got {:?}", program.statements[0]);
+        };
+        assert_eq!(name.0, "Point");
+        let names: Vec<_> = methods.iter().map(|(name, _)| name.0.as_str()).collect();
+        assert_eq!(names, vec!["sum", "new"]);
+        assert_eq!(
+            program.to_string(),
+            "impl Point { fn sum(self) ((self.x) + (self.y)) fn new(x, y) Point { x: x, y: y } }"
+        );
+
+        let inputs = vec![
+            ("arr.push(1).len()", "((arr.push)(1).len)()"),
+            ("a.b(c).d", "((a.b)(c).d)"),
+        ];
+        for (input, expected) in inputs {
+            let mut parser = Parser::new(Lexer::new(input));
+            let program = parser.parse_program();
+            assert!(parser.errors().is_empty(), "{input}: {:?}", parser.errors());
+            assert_eq!(program.to_string(), expected);
+        }
+
+        let errors = vec![
+            (
+                "impl Point { fn f() { 1 } fn f() { 2 } }",
+                "duplicate method f in impl Point",
+            ),
+            (
+                "impl Point { let f = 1; }",
+                "expected next token to be FUNCTION, got: Let",
+            ),
+        ];
+        for (input, expected) in errors {
+            let mut parser = Parser::new(Lexer::new(input));
+            parser.parse_program();
+            assert_eq!(
+                parser.errors().first().map(String::as_str),
+                Some(expected),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_enum_statements() {
+        let input = "enum Shape { Circle(r), Rect(w, h), Empty }";
+        let mut parser = Parser::new(Lexer::new(input));
+        let program = parser.parse_program();
+
+        assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+        let Statement::Enum(name, variants) = &program.statements[0] else {
+            panic!("expected an EnumStatement. Got {:?}", program.statements[0]);
+        };
+        assert_eq!(name.0, "Shape");
+        assert_eq!(
+            variants[1],
+            EnumVariant {
+                name: Identifier("Rect".into()),
+                fields: Some(vec![Identifier("w".into()), Identifier("h".into())]),
+            }
+        );
+        assert_eq!(variants[2].fields, None);
+        assert_eq!(program.to_string(), input);
+
+        let inputs = vec![
+            (
+                "match (s) { Circle(r) => r, Shape.Rect(w, _) => w, Shape.Empty => 0 }",
+                "match (s) { Circle(r) => r, Shape.Rect(w, _) => w, Shape.Empty => 0 }",
+            ),
+            ("let Leaf([a, b]) = t;", "let Leaf([a, b]) = t;"),
+            ("fn(Some(x)) { x }", "fn(Some(x)) x"),
+        ];
+        for (input, expected) in inputs {
+            let mut parser = Parser::new(Lexer::new(input));
+            let program = parser.parse_program();
+            assert!(parser.errors().is_empty(), "{input}: {:?}", parser.errors());
+            assert_eq!(program.to_string(), expected);
+        }
+
+        let errors = vec![
+            ("enum E { A, A(x) }", "duplicate variant A in enum E"),
+            (
+                "const A = 1; enum E { A }",
+                "cannot redeclare constant A, declared at line 1, column 7",
+            ),
+            ("enum E { A(1) }", "Expected an identifier. Got INT"),
+        ];
+        for (input, expected) in errors {
+            let mut parser = Parser::new(Lexer::new(input));
+            parser.parse_program();
+            assert_eq!(
+                parser.errors().first().map(String::as_str),
+                Some(expected),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_enum_match_exhaustiveness() {
+        let shape = "enum Shape { Circle(r), Rect(w, h), Empty }\n";
+        let not_exhaustive = "match expression may not be exhaustive, consider adding a `_` arm";
+        let inputs = vec![
+            (
+                "match (s) { Circle(r) => 1, Rect(w, h) => 2, Shape.Empty => 3 }",
+                vec![],
+            ),
+            (
+                "match (s) { Shape.Circle => 1, Shape.Rect => 2, Shape.Empty => 3 }",
+                vec![],
+            ),
+            (
+                "match (s) { Circle(r) => 1, Rect(w, h) => 2, Empty => 3 }",
+                vec![],
+            ),
+            (
+                "match (s) { Circle(r) => 1, Rect(w, h) => 2 }",
+                vec![not_exhaustive],
+            ),
+            (
+                "match (s) { Circle(0) => 1, Rect(w, h) => 2, Shape.Empty => 3 }",
+                vec![not_exhaustive],
+            ),
+            (
+                "match (s) { Circle(r) if r > 0 => 1, Rect(w, h) => 2, Shape.Empty => 3 }",
+                vec![not_exhaustive],
+            ),
+            (
+                "match (s) { Other.Circle(r) => 1, Rect(w, h) => 2, Shape.Empty => 3 }",
+                vec![not_exhaustive],
+            ),
+        ];
+
+        for (input, expected) in inputs {
+            let mut parser = Parser::new(Lexer::new(&format!("{shape}{input}")));
+            parser.parse_program();
+            assert!(parser.errors().is_empty(), "{input}: {:?}", parser.errors());
+            assert_eq!(parser.warnings(), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_struct_errors() {
+        let inputs = vec![
+            ("struct Point { x, x }", "duplicate field x in struct Point"),
+            ("Point { x: 1, x: 2 }", "duplicate field x in Point literal"),
+            ("f() = 1", "invalid assignment target: f()"),
+            ("a[0] = 1", "invalid assignment target: (a[0])"),
+            (
+                "const P = 1; struct P { x }",
+                "cannot redeclare constant P, declared at line 1, column 7",
+            ),
+        ];
+
+        for (input, expected) in inputs {
+            let mut parser = Parser::new(Lexer::new(input));
+            parser.parse_program();
+            assert_eq!(
+                parser.errors().first().map(String::as_str),
+                Some(expected),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_modules() {
+        let inputs = vec![
+            (
+                r#"import "lib/math.mk" as math;"#,
+                r#"import "lib/math.mk" as math;"#,
+            ),
+            ("export let x = 1;", "export let x = 1;"),
+            ("export let {a, b} = h;", "export let {a, b} = h;"),
+            ("math.sqrt(x)", "(math.sqrt)(x)"),
+            ("a.b.c + 1", "(((a.b).c) + 1)"),
+        ];
+
+        for (input, expected) in inputs {
+            let mut parser = Parser::new(Lexer::new(input));
+            let program = parser.parse_program();
+            assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+            assert_eq!(program.to_string(), expected);
+        }
+
+        let errors = vec![
+            (
+                "import math;",
+                "expected a module path after import, got: IDENT",
+            ),
+            (
+                r#"import "m.mk";"#,
+                "expected next token to be AS, got: Semicolon",
+            ),
+            (
+                "if (x) { export let y = 1; }",
+                "export is only allowed at the top level of a module",
+            ),
+            (
+                "export fn() {};",
+                "expected let or const after export, got: FUNCTION",
+            ),
+            (
+                "export const [a] = 1;",
+                "expected a name after const, got: [",
+            ),
+            (
+                r#"const m = 1; import "m.mk" as m;"#,
+                "cannot redeclare constant m, declared at line 1, column 7",
+            ),
+        ];
+
+        for (input, expected) in errors {
+            let mut parser = Parser::new(Lexer::new(input));
+            parser.parse_program();
+            assert_eq!(
+                parser.errors().first().map(String::as_str),
+                Some(expected),
+                "{input}"
+            );
         }
     }
 
-    fn test_literal_expression<T: TestExpression + ?Sized>(
-        expression: &Expression,
-        value: &T,
-    ) -> bool {
-        value.test_expression(expression)
-    }
+    #[test]
+    fn test_macro_expression() {
+        let input = "macro(x, y) { x + y; }";
+        let mut parser = Parser::new(Lexer::new(input));
+        let program = parser.parse_program();
 
-    fn test_infix_expression<T: TestExpression + ?Sized>(
-        infix: &Expression,
-        left: &T,
-        operator: &str,
-        right: &T,
-    ) -> bool {
-        let Expression::Infix(left_expression, op, right_expresssion) = infix else {
-            eprintln!("expression is not Infix(_,_,_). Got: {:?}", infix);
-            return false;
-        };
+        assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+        assert_eq!(program.statements.len(), 1);
 
-        if !left.test_expression(left_expression) {
-            return false;
+        let Statement::Expression(Expression::Macro(params, body)) = &program.statements[0] else {
+            panic!("expected a Macro(_, _). Got {:?}", program.statements[0]);
         };
 
-        if op.to_string() != operator {
-            eprintln!("expected operator '{operator}'. Got: {:?}", op);
-            return false;
-        }
-
-        if !right.test_expression(right_expresssion) {
-            return false;
-        }
+        assert_eq!(params.len(), 2);
+        assert_eq!(params[0].to_string(), "x");
+        assert_eq!(params[1].to_string(), "y");
 
-        true
+        let Statement::Block(statements) = &**body else {
+            panic!("expected a BlockStatement. Got {:?}", body);
+        };
+        assert_eq!(statements.len(), 1);
+        let Statement::Expression(expression) = &statements[0] else {
+            panic!("expected an ExpressionStatement. Got {:?}", statements[0]);
+        };
+        assert!(test_infix_expression(expression, &"x", "+", &"y"));
     }
 
     #[test]
-    fn test_let_statements() {
-        let inputs: Vec<(&str, &str, &dyn TestExpression)> = vec![
-            ("let x = 5;", "x", &5),
-            ("let y = true;", "y", &true),
-            ("let foobar = y;", "foobar", &"y"),
+    fn test_destructuring_patterns() {
+        let inputs: Vec<(&str, &str)> = vec![
+            ("let [a, b] = x;", "let [a, b] = x;"),
+            ("let [a, ...rest] = x;", "let [a, ...rest] = x;"),
+            ("let [...all] = x;", "let [...all] = x;"),
+            ("let [] = x;", "let [] = x;"),
+            ("let [[a, b], c] = x;", "let [[a, b], c] = x;"),
+            ("let {name, age} = x;", "let {name, age} = x;"),
+            (
+                r#"let {"name": n, age: [a]} = x;"#,
+                "let {name: n, age: [a]} = x;",
+            ),
+            (
+                "let {1: one, true: yes} = x;",
+                "let {1: one, true: yes} = x;",
+            ),
+            ("fn([a, b], {c}) { a }", "fn([a, b], {c}) a"),
         ];
 
         for input in inputs {
             let mut parser = Parser::new(Lexer::new(input.0));
             let program = parser.parse_program();
 
-            assert!(parser.errors().is_empty(), "{:?}", parser.errors());
-            assert_eq!(program.statements.len(), 1, "{:?}", program.statements);
+            assert!(
+                parser.errors().is_empty(),
+                "{}: {:?}",
+                input.0,
+                parser.errors()
+            );
+            assert_eq!(program.to_string(), input.1);
+        }
+    }
 
-            let Statement::Let(ident, value) = &program.statements[0] else {
-                panic!("expected a LetStatement(_,_). Got {:?}", program.statements[0]);
-            };
+    #[test]
+    fn test_destructuring_pattern_errors() {
+        let inputs: Vec<(&str, &str)> = vec![
+            ("let + = x;", "expected a pattern, got: +"),
+            ("let -a = x;", "expected a number after '-', got: IDENT"),
+            (
+                "let [a, ...rest, b] = x;",
+                "expected next token to be ], got: Comma",
+            ),
+            (
+                r#"let {"name"} = x;"#,
+                "expected ':' after hash pattern key STRING",
+            ),
+            (
+                "let [a b] = x;",
+                "expected next token to be ,, got: Ident(\"b\")",
+            ),
+        ];
 
-            assert_eq!(&ident.0, input.1);
-            assert!(test_literal_expression(value, input.2));
+        for input in inputs {
+            let mut parser = Parser::new(Lexer::new(input.0));
+            parser.parse_program();
+
+            assert_eq!(parser.errors().first().map(String::as_str), Some(input.1));
         }
     }
 
@@ -579,6 +1916,24 @@ mod tests {
         assert!(test_literal_expression(ident, &5))
     }
 
+    #[test]
+    fn test_float_expressions() {
+        let input = "3.5;";
+        let mut parser = Parser::new(Lexer::new(input));
+        let program = parser.parse_program();
+
+        assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+
+        let Statement::Expression(expression) = &program.statements[0] else {
+            panic!(
+                "expected an ExpressionStatement. Got {}",
+                program.statements[0]
+            );
+        };
+
+        assert_eq!(expression, &Expression::Literal(Literal::Float(3.5)));
+    }
+
     #[test]
     fn test_parsing_prefix_expressions() {
         let inputs: Vec<(&str, &str, &dyn TestExpression)> = vec![
@@ -618,6 +1973,7 @@ mod tests {
             ("5 - 5;", &5, "-", &5),
             ("5 * 5;", &5, "*", &5),
             ("5 / 5;", &5, "/", &5),
+            ("5 % 5;", &5, "%", &5),
             ("5 < 5;", &5, "<", &5),
             ("5 > 5;", &5, ">", &5),
             ("5 == 5;", &5, "==", &5),
@@ -654,6 +2010,7 @@ mod tests {
             ("a + b - c", "((a + b) - c)"),
             ("a * b * c", "((a * b) * c)"),
             ("a * b / c", "((a * b) / c)"),
+            ("a + b % c", "(a + (b % c))"),
             ("a + b / c", "(a + (b / c))"),
             ("a + b * c + d / e - f", "(((a + (b * c)) + (d / e)) - f)"),
             ("3 + 4; -5 * 5", "(3 + 4)((-5) * 5)"),
@@ -685,6 +2042,28 @@ mod tests {
                 "add(a * b[2], b[1], 2 * [1, 2][1])",
                 "add((a * (b[2])), (b[1]), (2 * ([1, 2][1])))",
             ),
+            ("a ?? b ?? c", "((a ?? b) ?? c)"),
+            ("a == b ?? c + d", "((a == b) ?? (c + d))"),
+            (
+                r#"config?["db"]?.port ?? 5432"#,
+                "(((config?[db])?[port]) ?? 5432)",
+            ),
+            ("a?[b][c]", "((a?[b])[c])"),
+            ("a[1:3]", "(a[1:3])"),
+            ("a[:-1]", "(a[:(-1)])"),
+            ("a[2:]", "(a[2:])"),
+            ("a[:]", "(a[:])"),
+            ("a[b + 1:][0]", "((a[(b + 1):])[0])"),
+            ("null", "null"),
+            ("x |> f(a)", "f(x, a)"),
+            ("x |> f", "f(x)"),
+            ("x |> f() |> g(1)", "g(f(x), 1)"),
+            ("a + 1 |> f(b * 2)", "f((a + 1), (b * 2))"),
+            ("x |> f >> g", "(f >> g)(x)"),
+            ("f >> g >> h", "((f >> g) >> h)"),
+            ("x |> f ?? g", "(f ?? g)(x)"),
+            ("a.b = x |> f", "((a.b) = f(x))"),
+            ("xs |> p.scale(2)", "(p.scale)(xs, 2)"),
         ];
 
         for input in inputs {
@@ -798,6 +2177,143 @@ mod tests {
         assert!(test_literal_expression(alterative_expression, &"y"));
     }
 
+    #[test]
+    fn test_else_if_expression() {
+        let input = "if (x < y) { x } else if (x > y) { y } else { 0 }";
+        let mut parser = Parser::new(Lexer::new(input));
+        let program = parser.parse_program();
+
+        assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+        assert_eq!(program.statements.len(), 1);
+
+        let Statement::Expression(Expression::If(_, _, Some(alternative))) = &program.statements[0]
+        else {
+            panic!(
+                "expected an If with an else branch. Got {:?}",
+                program.statements[0]
+            );
+        };
+
+        let Statement::Expression(Expression::If(condition, consequence, Some(last))) =
+            &**alternative
+        else {
+            panic!("expected a nested If(_, _, _). Got {:?}", alternative);
+        };
+
+        assert!(test_infix_expression(condition, &"x", ">", &"y"));
+        assert_eq!(consequence.to_string(), "y");
+        assert_eq!(last.to_string(), "0");
+
+        let mut parser = Parser::new(Lexer::new("if (x) { 1 } else 2"));
+        parser.parse_program();
+        assert_eq!(
+            parser.errors().first().map(String::as_str),
+            Some("expected next token to be {, got: Int(2)")
+        );
+    }
+
+    #[test]
+    fn test_try_expression() {
+        let inputs = vec![
+            (
+                r#"try { risky(); } catch (e) { puts(e); }"#,
+                "try risky() catch (e) puts(e)",
+            ),
+            (
+                r#"try { a } catch { b } finally { c }"#,
+                "try a catch b finally c",
+            ),
+            ("try { a } finally { c }", "try a finally c"),
+            (
+                r#"try { a } catch ({"message": m}) { m }"#,
+                "try a catch ({message: m}) m",
+            ),
+            (r#"throw "oops";"#, "throw oops;"),
+            ("throw {\"kind\": k};", "throw {kind: k};"),
+        ];
+
+        for (input, expected) in inputs {
+            let mut parser = Parser::new(Lexer::new(input));
+            let program = parser.parse_program();
+            assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+            assert_eq!(program.to_string(), expected);
+        }
+
+        let mut parser = Parser::new(Lexer::new("try { a } 1"));
+        parser.parse_program();
+        assert_eq!(
+            parser.errors().first().map(String::as_str),
+            Some("expected catch or finally after try block, got: INT")
+        );
+    }
+
+    #[test]
+    fn test_match_expression() {
+        let input = r#"match (value) {
+            0 => "zero",
+            [x, y] => x + y,
+            {"type": "add", "n": n} if n > 0 => { let m = n; m },
+            "s" => null,
+            _ => -1
+        }"#;
+        let mut parser = Parser::new(Lexer::new(input));
+        let program = parser.parse_program();
+
+        assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+        assert!(parser.warnings().is_empty(), "{:?}", parser.warnings());
+
+        let Statement::Expression(Expression::Match(value, arms)) = &program.statements[0] else {
+            panic!(
+                "expected a Match expression. Got {:?}",
+                program.statements[0]
+            );
+        };
+
+        assert!(test_literal_expression(value, &"value"));
+        assert_eq!(arms.len(), 5);
+        assert_eq!(arms[0].pattern, Pattern::Literal(Literal::Integer(0)));
+        assert_eq!(arms[1].to_string(), "[x, y] => (x + y)");
+        assert_eq!(
+            arms[2].to_string(),
+            "{type: \"add\", n} if (n > 0) => let m = n;m"
+        );
+        assert_eq!(arms[3].to_string(), "\"s\" => null");
+        assert_eq!(arms[4].to_string(), "_ => (-1)");
+    }
+
+    #[test]
+    fn test_match_exhaustiveness_warnings() {
+        let inputs: Vec<(&str, Vec<&str>)> = vec![
+            ("match (x) { _ => 1 }", vec![]),
+            ("match (x) { y => y }", vec![]),
+            ("match (x) { true => 1, false => 0 }", vec![]),
+            (
+                "match (x) { 1 => 1 }",
+                vec!["match expression may not be exhaustive, consider adding a `_` arm"],
+            ),
+            (
+                "match (x) { true => 1, false if y => 0 }",
+                vec!["match expression may not be exhaustive, consider adding a `_` arm"],
+            ),
+            (
+                "match (x) { y if y > 1 => 1 }",
+                vec!["match expression may not be exhaustive, consider adding a `_` arm"],
+            ),
+            (
+                "match (x) { _ => 1, 2 => 2, [a] => 3 }",
+                vec!["unreachable match arm: 2", "unreachable match arm: [a]"],
+            ),
+        ];
+
+        for input in inputs {
+            let mut parser = Parser::new(Lexer::new(input.0));
+            parser.parse_program();
+
+            assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+            assert_eq!(parser.warnings(), input.1, "{}", input.0);
+        }
+    }
+
     #[test]
     fn test_function_parsing() {
         let input = "fn(x, y) { x + y }";
@@ -817,8 +2333,8 @@ mod tests {
         };
 
         assert_eq!(params.len(), 2, "{:?}", params);
-        assert!(test_literal_expression(&params[0], &"x"));
-        assert!(test_literal_expression(&params[1], &"y"));
+        assert_eq!(params[0], Pattern::Identifier(Identifier("x".into())));
+        assert_eq!(params[1], Pattern::Identifier(Identifier("y".into())));
 
         let Statement::Block(block) = &**body else {
             panic!("expected a BlockStatement. Got {:?}", body);
@@ -831,6 +2347,155 @@ mod tests {
         assert!(test_infix_expression(infix, &"x", "+", &"y"));
     }
 
+    #[test]
+    fn test_generators() {
+        let inputs = vec![
+            ("fn*(x) { yield x; }", "fn*(x) yield x;"),
+            ("fn* () { yield 1 }", "fn*() yield 1;"),
+            ("for (x in xs) { puts(x); }", "for (x in xs) puts(x)"),
+            ("for ([a, _] in f(1)) { }", "for ([a, _] in f(1)) "),
+            (
+                "fn*() { for (x in xs) { if (x) { yield x; } } }",
+                "fn*() for (x in xs) if x yield x;",
+            ),
+            (
+                "impl Tree { fn* walk(self) { yield self; } }",
+                "impl Tree { fn* walk(self) yield self; }",
+            ),
+            (
+                "try { fn*() { yield 1; } } catch { 0 }",
+                "try fn*() yield 1; catch 0",
+            ),
+        ];
+        for (input, expected) in inputs {
+            let mut parser = Parser::new(Lexer::new(input));
+            let program = parser.parse_program();
+            assert!(parser.errors().is_empty(), "{input}: {:?}", parser.errors());
+            assert_eq!(program.to_string(), expected);
+        }
+
+        let errors = vec![
+            ("yield 1;", "yield outside of a generator function"),
+            ("fn() { yield 1; }", "yield outside of a generator function"),
+            (
+                "fn*() { fn() { yield 1; } }",
+                "yield outside of a generator function",
+            ),
+            (
+                "fn*() { |x| { yield x; } }",
+                "yield outside of a generator function",
+            ),
+            (
+                "fn*() { try { yield 1; } catch (e) { 0 } }",
+                "yield inside of a try, catch or finally block",
+            ),
+            (
+                "fn*() { try { 0 } catch { yield 1; } }",
+                "yield inside of a try, catch or finally block",
+            ),
+            (
+                "fn*() { try { 0 } finally { yield 1; } }",
+                "yield inside of a try, catch or finally block",
+            ),
+            (
+                "for x in xs { }",
+                "expected next token to be (, got: Ident(\"x\")",
+            ),
+            (
+                "for (x of xs) { }",
+                "expected next token to be IN, got: Ident(\"of\")",
+            ),
+        ];
+        for (input, expected) in errors {
+            let mut parser = Parser::new(Lexer::new(input));
+            parser.parse_program();
+            assert_eq!(
+                parser.errors().first().map(String::as_str),
+                Some(expected),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_short_lambdas() {
+        let parse = |input: &str| {
+            let mut parser = Parser::new(Lexer::new(input));
+            let program = parser.parse_program();
+            assert!(parser.errors().is_empty(), "{input}: {:?}", parser.errors());
+            program.statements
+        };
+
+        // Each short form parses to exactly the same function as the long one.
+        let inputs = vec![
+            ("|x| x * 2", "fn(x) { x * 2 }"),
+            ("(x) => x * 2", "fn(x) { x * 2 }"),
+            ("|a, b| a + b", "fn(a, b) { a + b }"),
+            ("(a, b) => a + b", "fn(a, b) { a + b }"),
+            ("|| 1", "fn() { 1 }"),
+            ("() => 1", "fn() { 1 }"),
+            ("|x| { let y = x; y }", "fn(x) { let y = x; y }"),
+            ("(x) => { let y = x; y }", "fn(x) { let y = x; y }"),
+            ("|[a, ...rest], {name}| a", "fn([a, ...rest], {name}) { a }"),
+            (
+                "([a, _], {name: n, \"k\": 1}, -1) => a",
+                "fn([a, _], {name: n, \"k\": 1}, -1) { a }",
+            ),
+            (
+                "(Some(x), Shape.Empty, Shape.Rect(w, h)) => x",
+                "fn(Some(x), Shape.Empty, Shape.Rect(w, h)) { x }",
+            ),
+            ("|x| |y| x + y", "fn(x) { fn(y) { x + y } }"),
+            ("(x) => (y) => x + y", "fn(x) { fn(y) { x + y } }"),
+        ];
+        for (short, long) in inputs {
+            assert_eq!(parse(short), parse(long), "{short}");
+        }
+
+        let inputs = vec![
+            ("map(xs, |x| x + 1)", "map(xs, fn(x) (x + 1))"),
+            ("xs |> map((x) => x * 2)", "map(xs, fn(x) (x * 2))"),
+            ("let f = |x| x + 1 >> g;", "let f = fn(x) ((x + 1) >> g);"),
+            ("(|x| x)(1)", "fn(x) x(1)"),
+            ("(a + b) * c", "((a + b) * c)"),
+            // A guard in parentheses is still a guard.
+            (
+                "match (v) { n if (n > 0) => n, _ => 0 }",
+                "match (v) { n if (n > 0) => n, _ => 0 }",
+            ),
+            // Inside brackets in a guard, `=>` can only start an arrow function.
+            (
+                "match (v) { n if ap((x) => x == n, 1) => n, _ => 0 }",
+                "match (v) { n if ap(fn(x) (x == n), 1) => n, _ => 0 }",
+            ),
+            (
+                "match (v) { n if [(x) => x][0](n) => n, _ => 0 }",
+                "match (v) { n if ([fn(x) x][0])(n) => n, _ => 0 }",
+            ),
+        ];
+        for (input, expected) in inputs {
+            let statements = parse(input);
+            assert_eq!(Program { statements }.to_string(), expected);
+        }
+
+        let errors = vec![
+            ("(a, b)", "expected next token to be =>, got: Eof"),
+            ("()", "expected next token to be =>, got: Eof"),
+            ("(a + 1) => a", "invalid parameter: (a + 1)"),
+            ("(f(1)(2)) => 1", "invalid parameter: f(1)"),
+            ("|x y", "expected next token to be ,, got: Ident(\"y\")"),
+        ];
+        for (input, expected) in errors {
+            let mut parser = Parser::new(Lexer::new(input));
+            parser.parse_program();
+            assert_eq!(
+                parser.errors().first().map(String::as_str),
+                Some(expected),
+                "{input}"
+            );
+        }
+    }
+
     #[test]
     fn test_function_param_parsing() {
         let inputs: Vec<(&str, Vec<&str>)> = vec![
@@ -856,7 +2521,7 @@ mod tests {
 
             assert_eq!(params.len(), input.1.len(), "{:?}", params);
             for (i, param) in input.1.iter().enumerate() {
-                assert!(test_literal_expression(&params[i], param));
+                assert_eq!(&params[i].to_string(), param);
             }
         }
     }
@@ -917,7 +2582,7 @@ mod tests {
 
         assert_eq!(parser.errors().len(), 0, "{:?}", parser.errors());
 
-        let Some(Statement::Expression(expression)) =  program.statements.get(0) else {
+        let Some(Statement::Expression(expression)) = program.statements.first() else {
             panic!("Expected an Expression Statement. Got {:?}", program.statements[0]);
         };
 
@@ -939,7 +2604,7 @@ mod tests {
 
         assert_eq!(parser.errors().len(), 0, "{:?}", parser.errors());
 
-        let Some(Statement::Expression(expression)) =  program.statements.get(0) else {
+        let Some(Statement::Expression(expression)) = program.statements.first() else {
             panic!("Expected an Expression Statement. Got {:?}", program.statements[0]);
         };
 
@@ -951,6 +2616,67 @@ mod tests {
         test_infix_expression(index, &1, "+", &1);
     }
 
+    #[test]
+    fn test_interpolated_string_expression() {
+        let input = r#""total: ${count * 2}, ${"nested ${x}"}""#;
+
+        let mut parser = Parser::new(Lexer::new(input));
+        let program = parser.parse_program();
+
+        assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+
+        let Statement::Expression(Expression::Interpolated(parts)) = &program.statements[0] else {
+            panic!(
+                "Expected an Interpolated Expression. Got {:?}",
+                program.statements[0]
+            );
+        };
+
+        assert_eq!(parts.len(), 4, "{:?}", parts);
+        assert_eq!(
+            parts[0],
+            Expression::Literal(Literal::String("total: ".into()))
+        );
+        assert_eq!(parts[1].to_string(), "(count * 2)");
+        assert_eq!(parts[2], Expression::Literal(Literal::String(", ".into())));
+        assert_eq!(parts[3].to_string(), "nested ${x}");
+    }
+
+    #[test]
+    fn test_interpolated_string_errors() {
+        let inputs: Vec<(&str, &str)> = vec![
+            (r#""${}""#, "empty string interpolation"),
+            (r#""${1 2}""#, "expected next token to be EOF, got: Int(2)"),
+            (r#""${ + }""#, "Expected a prefix. Got: +"),
+        ];
+
+        for input in inputs {
+            let mut parser = Parser::new(Lexer::new(input.0));
+            parser.parse_program();
+
+            assert_eq!(parser.errors().first().map(String::as_str), Some(input.1));
+        }
+    }
+
+    #[test]
+    fn test_illegal_token_errors() {
+        let inputs: Vec<(&str, &str)> = vec![
+            (
+                r#"let s = "unterminated"#,
+                "illegal token: unterminated string",
+            ),
+            (r#""\x""#, "illegal token: invalid escape sequence: \\x"),
+            ("let x = #;", "illegal token: #"),
+        ];
+
+        for input in inputs {
+            let mut parser = Parser::new(Lexer::new(input.0));
+            parser.parse_program();
+
+            assert_eq!(parser.errors().first().map(String::as_str), Some(input.1));
+        }
+    }
+
     #[test]
     fn test_parsing_empty_map() {
         let input = "{}";
@@ -959,7 +2685,7 @@ mod tests {
 
         assert_eq!(parser.errors().len(), 0, "{:?}", parser.errors());
 
-        let Some(Statement::Expression(expression)) =  program.statements.get(0) else {
+        let Some(Statement::Expression(expression)) = program.statements.first() else {
             panic!("Expected an Expression Statement. Got {:?}", program.statements[0]);
         };
 
@@ -978,7 +2704,7 @@ mod tests {
 
         assert_eq!(parser.errors().len(), 0, "{:?}", parser.errors());
 
-        let Some(Statement::Expression(expression)) =  program.statements.get(0) else {
+        let Some(Statement::Expression(expression)) = program.statements.first() else {
             panic!("Expected an Expression Statement. Got {:?}", program.statements[0]);
         };
 
@@ -1010,7 +2736,7 @@ mod tests {
 
         assert_eq!(parser.errors().len(), 0, "{:?}", parser.errors());
 
-        let Some(Statement::Expression(expression)) =  program.statements.get(0) else {
+        let Some(Statement::Expression(expression)) = program.statements.first() else {
             panic!("Expected an Expression Statement. Got {:?}", program.statements[0]);
         };
 
@@ -1,6 +1,12 @@
+pub mod fold;
+pub mod modify;
+pub mod source;
+pub mod visit;
+pub mod visit_mut;
+
 use std::fmt::Display;
 
-use crate::lexer::token::TokenKind;
+use crate::lexer::token::{Position, TokenKind};
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Identifier(pub String);
@@ -11,10 +17,29 @@ impl Display for Identifier {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Statement {
-    Let(Identifier, Expression),
+    Let(Pattern, Expression),
+    /// `const name = value;`, keeping where the name was declared so
+    /// redeclarations can point back to it.
+    Const(Identifier, Expression, Position),
+    /// `import "path" as name;`
+    Import(String, Identifier),
+    /// `export` in front of a top level `let` or `const`.
+    Export(Box<Statement>),
+    /// `struct Name { field, ... }`
+    Struct(Identifier, Vec<Identifier>),
+    /// `impl Name { fn method(self, ...) { ... } ... }`, where each method is
+    /// kept as the function expression it defines.
+    Impl(Identifier, Vec<(Identifier, Expression)>),
+    /// `enum Name { Variant(field, ...), Unit, ... }`
+    Enum(Identifier, Vec<EnumVariant>),
     Return(Expression),
+    Throw(Expression),
+    /// `yield value;`, which can only appear in the body of a generator.
+    Yield(Expression),
+    /// `for (pattern in iterable) { body }`
+    For(Pattern, Expression, Box<Statement>),
     Expression(Expression),
     Block(Vec<Statement>),
 }
@@ -22,8 +47,43 @@ pub enum Statement {
 impl Display for Statement {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let matched = match self {
-            Statement::Let(ident, value) => format!("let {ident} = {value};"),
+            Statement::Let(pattern, value) => format!("let {pattern} = {value};"),
+            Statement::Const(name, value, _) => format!("const {name} = {value};"),
+            Statement::Import(path, name) => format!("import {path:?} as {name};"),
+            Statement::Export(statement) => format!("export {statement}"),
+            Statement::Struct(name, fields) => {
+                format!("struct {name} {{ {} }}", join_identifiers(fields))
+            }
+            Statement::Impl(name, methods) => {
+                let methods = methods
+                    .iter()
+                    .map(|(method, function)| match function {
+                        Expression::Function(params, body) => {
+                            format!("fn {method}({}) {body}", join_patterns(params))
+                        }
+                        Expression::Generator(params, body) => {
+                            format!("fn* {method}({}) {body}", join_patterns(params))
+                        }
+                        function => format!("{method}: {function}"),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                format!("impl {name} {{ {methods} }}")
+            }
+            Statement::Enum(name, variants) => {
+                let variants = variants
+                    .iter()
+                    .map(|variant| variant.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("enum {name} {{ {variants} }}")
+            }
             Statement::Return(value) => format!("return {value};"),
+            Statement::Throw(value) => format!("throw {value};"),
+            Statement::Yield(value) => format!("yield {value};"),
+            Statement::For(pattern, iterable, body) => {
+                format!("for ({pattern} in {iterable}) {body}")
+            }
             Statement::Expression(value) => value.to_string(),
             Statement::Block(statements) => {
                 let mut buf = String::new();
@@ -37,38 +97,195 @@ impl Display for Statement {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// One case of an enum. Variants declared with parentheses are built by
+/// calling them, unit variants written without are values on their own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumVariant {
+    pub name: Identifier,
+    pub fields: Option<Vec<Identifier>>,
+}
+
+impl Display for EnumVariant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(fields) = &self.fields {
+            write!(f, "({})", join_identifiers(fields))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Literal {
     Integer(i64),
+    /// An integer literal too large for an `i64`, kept as its decimal digits.
+    BigInteger(String),
+    Float(f64),
     Boolean(bool),
     String(String),
+    Null,
 }
 
 impl Display for Literal {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let matched = match self {
             Literal::Integer(int) => int.to_string(),
+            Literal::BigInteger(digits) => digits.clone(),
+            Literal::Float(float) => format!("{float:?}"),
             Literal::Boolean(bool) => bool.to_string(),
             Literal::String(string) => string.clone(),
+            Literal::Null => "null".to_string(),
         };
         write!(f, "{}", matched)
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// The left hand side of a binding, which can pull values out of arrays and
+/// hashes as well as bind a whole value to a name.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    /// `_`, which matches anything without binding it.
+    Wildcard,
+    Identifier(Identifier),
+    /// Only matches values equal to the literal.
+    Literal(Literal),
+    /// `[a, b, ...rest]`. Without a rest binding the array length has to
+    /// match exactly.
+    Array(Vec<Pattern>, Option<Identifier>),
+    /// `{name, "key": pattern}`, where a bare name is short for `"name": name`.
+    Hash(Vec<(Literal, Pattern)>),
+    /// `Variant(a, b)` or `Enum.Variant(a, b)`, matching a value built from
+    /// that enum variant. Without parentheses the payload isn't checked, which
+    /// is how unit variants are matched, since a bare name is a binding.
+    Variant(Option<Identifier>, Identifier, Option<Vec<Pattern>>),
+}
+
+impl Statement {
+    /// The names a top level statement makes visible to importing modules.
+    pub fn exported_identifiers(&self) -> Vec<&Identifier> {
+        match self {
+            Statement::Export(statement) => match &**statement {
+                Statement::Let(pattern, _) => pattern.identifiers(),
+                Statement::Const(name, _, _) => vec![name],
+                _ => Vec::new(),
+            },
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl Pattern {
+    /// Every name the pattern binds, in order.
+    pub fn identifiers(&self) -> Vec<&Identifier> {
+        match self {
+            Pattern::Wildcard | Pattern::Literal(_) => Vec::new(),
+            Pattern::Identifier(ident) => vec![ident],
+            Pattern::Array(elements, rest) => elements
+                .iter()
+                .flat_map(Pattern::identifiers)
+                .chain(rest.as_ref())
+                .collect(),
+            Pattern::Hash(entries) => entries
+                .iter()
+                .flat_map(|(_, pattern)| pattern.identifiers())
+                .collect(),
+            Pattern::Variant(_, _, payload) => payload
+                .iter()
+                .flatten()
+                .flat_map(Pattern::identifiers)
+                .collect(),
+        }
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pattern::Wildcard => write!(f, "_"),
+            Pattern::Identifier(ident) => write!(f, "{ident}"),
+            Pattern::Literal(Literal::String(string)) => write!(f, "{string:?}"),
+            Pattern::Literal(literal) => write!(f, "{literal}"),
+            Pattern::Array(elements, rest) => {
+                let mut elements = elements.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+                if let Some(rest) = rest {
+                    elements.push(format!("...{rest}"));
+                }
+                write!(f, "[{}]", elements.join(", "))
+            }
+            Pattern::Hash(entries) => {
+                let entries = entries
+                    .iter()
+                    .map(|(key, pattern)| match (key, pattern) {
+                        (Literal::String(key), Pattern::Identifier(ident)) if key == &ident.0 => {
+                            key.clone()
+                        }
+                        _ => format!("{key}: {pattern}"),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(f, "{{{entries}}}")
+            }
+            Pattern::Variant(enum_name, name, payload) => {
+                if let Some(enum_name) = enum_name {
+                    write!(f, "{enum_name}.")?;
+                }
+                write!(f, "{name}")?;
+                if let Some(payload) = payload {
+                    let payload = payload.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+                    write!(f, "({})", payload.join(", "))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Expression {
     Identifier(Identifier),
     Literal(Literal),
     Array(Vec<Expression>),
     Hash(Vec<(Expression, Expression)>),
+    /// A string with `${...}` interpolations. Plain text parts are string
+    /// literals, everything else gets formatted with `inspect`.
+    Interpolated(Vec<Expression>),
 
     If(Box<Expression>, Box<Statement>, Option<Box<Statement>>),
-    Function(Vec<Expression>, Box<Statement>),
+    Match(Box<Expression>, Vec<MatchArm>),
+    /// `try { } catch (e) { } finally { }`, where at least one of the catch
+    /// and finally clauses is present.
+    Try(
+        Box<Statement>,
+        Option<Box<CatchClause>>,
+        Option<Box<Statement>>,
+    ),
+    Function(Vec<Pattern>, Box<Statement>),
+    /// `fn*(params) { body }`, a function whose calls return a generator
+    /// that runs the body up to each `yield`.
+    Generator(Vec<Pattern>, Box<Statement>),
+    /// `macro(params) { body }`. Only meaningful when bound with a top level
+    /// `let`, which macro expansion picks up before evaluation.
+    Macro(Vec<Pattern>, Box<Statement>),
     Call(Box<Expression>, Vec<Expression>),
+    /// `Name { field: value, ... }`, constructing an instance of a struct.
+    StructLiteral(Identifier, Vec<(Identifier, Expression)>),
 
     Prefix(TokenKind, Box<Expression>),
     Infix(Box<Expression>, TokenKind, Box<Expression>),
     Index(Box<Expression>, Box<Expression>),
+    /// `left.name`
+    Field(Box<Expression>, Identifier),
+    /// `target = value`, where the target is a variable or a field.
+    Assign(Box<Expression>, Box<Expression>),
+    /// `left?[index]` and `left?.name`, which evaluate to null instead of
+    /// indexing when `left` is null.
+    OptionalIndex(Box<Expression>, Box<Expression>),
+    /// `left[start:end]`, where either bound can be left out.
+    Slice(
+        Box<Expression>,
+        Option<Box<Expression>>,
+        Option<Box<Expression>>,
+    ),
 }
 
 impl Display for Expression {
@@ -85,6 +302,13 @@ impl Display for Expression {
                     .join(", ");
                 format!("{{{pairs}}}")
             }
+            Expression::Interpolated(parts) => parts
+                .iter()
+                .map(|part| match part {
+                    Expression::Literal(Literal::String(string)) => string.clone(),
+                    expr => format!("${{{expr}}}"),
+                })
+                .collect(),
             Expression::If(condition, consequence, maybe_alterative) => {
                 let mut buf = format!("if {condition} {consequence}");
                 if let Some(alternative) = maybe_alterative {
@@ -93,15 +317,55 @@ impl Display for Expression {
                 }
                 buf
             }
+            Expression::Match(value, arms) => {
+                let arms = arms
+                    .iter()
+                    .map(|arm| arm.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("match ({value}) {{ {arms} }}")
+            }
+            Expression::Try(body, catch, finally) => {
+                let mut buf = format!("try {body}");
+                if let Some(catch) = catch {
+                    buf.push_str(&format!(" {catch}"));
+                }
+                if let Some(finally) = finally {
+                    buf.push_str(&format!(" finally {finally}"));
+                }
+                buf
+            }
             Expression::Function(params, body) => {
-                format!("fn({}) {body}", join_expressions(params, ", "))
+                format!("fn({}) {body}", join_patterns(params))
+            }
+            Expression::Generator(params, body) => {
+                format!("fn*({}) {body}", join_patterns(params))
+            }
+            Expression::Macro(params, body) => {
+                format!("macro({}) {body}", join_patterns(params))
             }
             Expression::Call(ident, args) => {
                 format!("{ident}({})", join_expressions(args, ", "))
             }
             Expression::Prefix(token, expr) => format!("({token}{expr})"),
             Expression::Infix(left, token, right) => format!("({left} {token} {right})"),
+            Expression::StructLiteral(name, fields) => {
+                let fields = fields
+                    .iter()
+                    .map(|(field, value)| format!("{field}: {value}"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("{name} {{ {fields} }}")
+            }
             Expression::Index(left, index) => format!("({left}[{index}])"),
+            Expression::Field(left, name) => format!("({left}.{name})"),
+            Expression::Assign(target, value) => format!("({target} = {value})"),
+            Expression::OptionalIndex(left, index) => format!("({left}?[{index}])"),
+            Expression::Slice(left, start, end) => {
+                let start = start.as_ref().map(|s| s.to_string()).unwrap_or_default();
+                let end = end.as_ref().map(|e| e.to_string()).unwrap_or_default();
+                format!("({left}[{start}:{end}])")
+            }
         };
         write!(f, "{matched}")
     }
@@ -115,6 +379,65 @@ fn join_expressions(expressions: &[Expression], pattern: &str) -> String {
         .join(pattern)
 }
 
+/// A single `pattern if guard => body` arm of a match expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Expression>,
+    pub body: Statement,
+}
+
+impl MatchArm {
+    /// Whether the arm matches every possible value.
+    pub fn is_catch_all(&self) -> bool {
+        self.guard.is_none() && matches!(self.pattern, Pattern::Wildcard | Pattern::Identifier(_))
+    }
+}
+
+impl Display for MatchArm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.pattern)?;
+        if let Some(guard) = &self.guard {
+            write!(f, " if {guard}")?;
+        }
+        write!(f, " => {}", self.body)
+    }
+}
+
+/// The `catch (binding) { body }` clause of a try expression. The binding
+/// can be left out when the error itself isn't needed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatchClause {
+    pub binding: Option<Pattern>,
+    pub body: Statement,
+}
+
+impl Display for CatchClause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.binding {
+            Some(binding) => write!(f, "catch ({binding}) {}", self.body),
+            None => write!(f, "catch {}", self.body),
+        }
+    }
+}
+
+fn join_identifiers(identifiers: &[Identifier]) -> String {
+    identifiers
+        .iter()
+        .map(|identifier| identifier.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn join_patterns(patterns: &[Pattern]) -> String {
+    patterns
+        .iter()
+        .map(|pattern| pattern.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[derive(Debug, Default)]
 pub struct Program {
     pub statements: Vec<Statement>,
 }
@@ -0,0 +1,373 @@
+//! Owned traversal that rebuilds the AST. Every `fold_*` method takes a
+//! node by value and returns its replacement, which can be any node of the
+//! same type. The defaults rebuild the node unchanged from its folded
+//! children, so a [`Fold`] that overrides nothing is the identity.
+
+use super::{
+    CatchClause, EnumVariant, Expression, Identifier, Literal, MatchArm, Pattern, Program,
+    Statement,
+};
+
+pub trait Fold {
+    fn fold_program(&mut self, program: Program) -> Program {
+        walk_program(self, program)
+    }
+
+    fn fold_statement(&mut self, statement: Statement) -> Statement {
+        walk_statement(self, statement)
+    }
+
+    fn fold_expression(&mut self, expression: Expression) -> Expression {
+        walk_expression(self, expression)
+    }
+
+    fn fold_pattern(&mut self, pattern: Pattern) -> Pattern {
+        walk_pattern(self, pattern)
+    }
+
+    fn fold_match_arm(&mut self, arm: MatchArm) -> MatchArm {
+        walk_match_arm(self, arm)
+    }
+
+    fn fold_catch_clause(&mut self, catch: CatchClause) -> CatchClause {
+        walk_catch_clause(self, catch)
+    }
+
+    fn fold_identifier(&mut self, identifier: Identifier) -> Identifier {
+        identifier
+    }
+
+    fn fold_literal(&mut self, literal: Literal) -> Literal {
+        literal
+    }
+}
+
+pub fn walk_program<F: Fold + ?Sized>(folder: &mut F, program: Program) -> Program {
+    Program {
+        statements: fold_statements(folder, program.statements),
+    }
+}
+
+pub fn walk_statement<F: Fold + ?Sized>(folder: &mut F, statement: Statement) -> Statement {
+    match statement {
+        Statement::Let(pattern, value) => {
+            Statement::Let(folder.fold_pattern(pattern), folder.fold_expression(value))
+        }
+        Statement::Const(name, value, position) => Statement::Const(
+            folder.fold_identifier(name),
+            folder.fold_expression(value),
+            position,
+        ),
+        Statement::Import(path, name) => Statement::Import(path, folder.fold_identifier(name)),
+        Statement::Export(statement) => {
+            Statement::Export(Box::new(folder.fold_statement(*statement)))
+        }
+        Statement::Struct(name, fields) => Statement::Struct(
+            folder.fold_identifier(name),
+            fields
+                .into_iter()
+                .map(|field| folder.fold_identifier(field))
+                .collect(),
+        ),
+        Statement::Impl(name, methods) => Statement::Impl(
+            folder.fold_identifier(name),
+            methods
+                .into_iter()
+                .map(|(method, function)| {
+                    (
+                        folder.fold_identifier(method),
+                        folder.fold_expression(function),
+                    )
+                })
+                .collect(),
+        ),
+        Statement::Enum(name, variants) => Statement::Enum(
+            folder.fold_identifier(name),
+            variants
+                .into_iter()
+                .map(|variant| EnumVariant {
+                    name: folder.fold_identifier(variant.name),
+                    fields: variant.fields.map(|fields| {
+                        fields
+                            .into_iter()
+                            .map(|field| folder.fold_identifier(field))
+                            .collect()
+                    }),
+                })
+                .collect(),
+        ),
+        Statement::Return(value) => Statement::Return(folder.fold_expression(value)),
+        Statement::Throw(value) => Statement::Throw(folder.fold_expression(value)),
+        Statement::Yield(value) => Statement::Yield(folder.fold_expression(value)),
+        Statement::For(pattern, iterable, body) => Statement::For(
+            folder.fold_pattern(pattern),
+            folder.fold_expression(iterable),
+            Box::new(folder.fold_statement(*body)),
+        ),
+        Statement::Expression(value) => Statement::Expression(folder.fold_expression(value)),
+        Statement::Block(statements) => Statement::Block(fold_statements(folder, statements)),
+    }
+}
+
+pub fn walk_expression<F: Fold + ?Sized>(folder: &mut F, expression: Expression) -> Expression {
+    match expression {
+        Expression::Identifier(ident) => Expression::Identifier(folder.fold_identifier(ident)),
+        Expression::Literal(literal) => Expression::Literal(folder.fold_literal(literal)),
+        Expression::Array(elements) => Expression::Array(fold_expressions(folder, elements)),
+        Expression::Hash(pairs) => Expression::Hash(
+            pairs
+                .into_iter()
+                .map(|(key, value)| (folder.fold_expression(key), folder.fold_expression(value)))
+                .collect(),
+        ),
+        Expression::Interpolated(parts) => {
+            Expression::Interpolated(fold_expressions(folder, parts))
+        }
+        Expression::If(condition, consequence, alternative) => Expression::If(
+            fold_boxed(folder, *condition),
+            Box::new(folder.fold_statement(*consequence)),
+            alternative.map(|alternative| Box::new(folder.fold_statement(*alternative))),
+        ),
+        Expression::Match(value, arms) => Expression::Match(
+            fold_boxed(folder, *value),
+            arms.into_iter()
+                .map(|arm| folder.fold_match_arm(arm))
+                .collect(),
+        ),
+        Expression::Try(body, catch, finally) => Expression::Try(
+            Box::new(folder.fold_statement(*body)),
+            catch.map(|catch| Box::new(folder.fold_catch_clause(*catch))),
+            finally.map(|finally| Box::new(folder.fold_statement(*finally))),
+        ),
+        Expression::Function(params, body) => Expression::Function(
+            fold_patterns(folder, params),
+            Box::new(folder.fold_statement(*body)),
+        ),
+        Expression::Generator(params, body) => Expression::Generator(
+            fold_patterns(folder, params),
+            Box::new(folder.fold_statement(*body)),
+        ),
+        Expression::Macro(params, body) => Expression::Macro(
+            fold_patterns(folder, params),
+            Box::new(folder.fold_statement(*body)),
+        ),
+        Expression::Call(function, args) => Expression::Call(
+            fold_boxed(folder, *function),
+            fold_expressions(folder, args),
+        ),
+        Expression::StructLiteral(name, fields) => Expression::StructLiteral(
+            folder.fold_identifier(name),
+            fields
+                .into_iter()
+                .map(|(field, value)| {
+                    (folder.fold_identifier(field), folder.fold_expression(value))
+                })
+                .collect(),
+        ),
+        Expression::Prefix(operator, right) => {
+            Expression::Prefix(operator, fold_boxed(folder, *right))
+        }
+        Expression::Infix(left, operator, right) => Expression::Infix(
+            fold_boxed(folder, *left),
+            operator,
+            fold_boxed(folder, *right),
+        ),
+        Expression::Index(left, index) => {
+            Expression::Index(fold_boxed(folder, *left), fold_boxed(folder, *index))
+        }
+        Expression::Field(left, name) => {
+            Expression::Field(fold_boxed(folder, *left), folder.fold_identifier(name))
+        }
+        Expression::Assign(target, value) => {
+            Expression::Assign(fold_boxed(folder, *target), fold_boxed(folder, *value))
+        }
+        Expression::OptionalIndex(left, index) => {
+            Expression::OptionalIndex(fold_boxed(folder, *left), fold_boxed(folder, *index))
+        }
+        Expression::Slice(left, start, end) => Expression::Slice(
+            fold_boxed(folder, *left),
+            start.map(|start| fold_boxed(folder, *start)),
+            end.map(|end| fold_boxed(folder, *end)),
+        ),
+    }
+}
+
+pub fn walk_pattern<F: Fold + ?Sized>(folder: &mut F, pattern: Pattern) -> Pattern {
+    match pattern {
+        Pattern::Wildcard => Pattern::Wildcard,
+        Pattern::Identifier(ident) => Pattern::Identifier(folder.fold_identifier(ident)),
+        Pattern::Literal(literal) => Pattern::Literal(folder.fold_literal(literal)),
+        Pattern::Array(elements, rest) => Pattern::Array(
+            fold_patterns(folder, elements),
+            rest.map(|rest| folder.fold_identifier(rest)),
+        ),
+        Pattern::Hash(entries) => Pattern::Hash(
+            entries
+                .into_iter()
+                .map(|(key, pattern)| (folder.fold_literal(key), folder.fold_pattern(pattern)))
+                .collect(),
+        ),
+        Pattern::Variant(enum_name, name, payload) => Pattern::Variant(
+            enum_name.map(|enum_name| folder.fold_identifier(enum_name)),
+            folder.fold_identifier(name),
+            payload.map(|payload| fold_patterns(folder, payload)),
+        ),
+    }
+}
+
+pub fn walk_match_arm<F: Fold + ?Sized>(folder: &mut F, arm: MatchArm) -> MatchArm {
+    MatchArm {
+        pattern: folder.fold_pattern(arm.pattern),
+        guard: arm.guard.map(|guard| folder.fold_expression(guard)),
+        body: folder.fold_statement(arm.body),
+    }
+}
+
+pub fn walk_catch_clause<F: Fold + ?Sized>(folder: &mut F, catch: CatchClause) -> CatchClause {
+    CatchClause {
+        binding: catch.binding.map(|binding| folder.fold_pattern(binding)),
+        body: folder.fold_statement(catch.body),
+    }
+}
+
+fn fold_boxed<F: Fold + ?Sized>(folder: &mut F, expression: Expression) -> Box<Expression> {
+    Box::new(folder.fold_expression(expression))
+}
+
+fn fold_expressions<F: Fold + ?Sized>(
+    folder: &mut F,
+    expressions: Vec<Expression>,
+) -> Vec<Expression> {
+    expressions
+        .into_iter()
+        .map(|expression| folder.fold_expression(expression))
+        .collect()
+}
+
+fn fold_statements<F: Fold + ?Sized>(folder: &mut F, statements: Vec<Statement>) -> Vec<Statement> {
+    statements
+        .into_iter()
+        .map(|statement| folder.fold_statement(statement))
+        .collect()
+}
+
+fn fold_patterns<F: Fold + ?Sized>(folder: &mut F, patterns: Vec<Pattern>) -> Vec<Pattern> {
+    patterns
+        .into_iter()
+        .map(|pattern| folder.fold_pattern(pattern))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexer::Lexer, parser::Parser};
+
+    /// Touches every kind of node the parser can produce.
+    const EVERY_NODE: &str = r#"
+let [first, {"key": value, shorthand}, _, 1, ...rest] = source;
+const limit = 10;
+import "lib.mk" as lib;
+export let exported = lib.value;
+let add = fn(a, [b, c]) { return a + b * c; };
+let m = macro(x) { quote(unquote(x) + 1) };
+let text = "sum: ${add(1, [2, 3])} and ${-limit}";
+let data = {"list": [1, 2.5, true, null, "s"], 99999999999999999999: !false};
+if (first < limit) { data["list"][0] } else if (first > 1) { data?["x"] } else { rest[1:-1] };
+match (value) { 0 => "zero", [h, ...t] if h > 0 => { h }, _ => null };
+try { throw {"message": "x"}; } catch ({message}) { message } finally { puts(text) };
+first ?? value?.field;
+struct Point { x, y }
+let p = Point { x: 1, y };
+p.x = p.y;
+impl Point { fn sum(self) { self.x + self.y } }
+p.sum();
+enum Shape { Circle(r), Empty }
+match (p) { Circle(r) => r, Shape.Empty => 0, Shape.Circle([_]) => 1, _ => 2 };
+let gen = fn*(n) { for ([k, v] in pairs(n)) { yield k; } };
+"#;
+
+    fn parse(input: &str) -> Program {
+        let mut parser = Parser::new(Lexer::new(input));
+        let program = parser.parse_program();
+        assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+        program
+    }
+
+    struct Identity;
+
+    impl Fold for Identity {}
+
+    #[test]
+    fn test_default_fold_is_identity() {
+        let program = parse(EVERY_NODE);
+        let expected = parse(EVERY_NODE);
+
+        let folded = Identity.fold_program(program);
+        assert_eq!(folded.statements, expected.statements);
+        assert_eq!(folded.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_default_fold_is_identity_per_node() {
+        let program = parse(EVERY_NODE);
+        for statement in program.statements {
+            assert_eq!(Identity.fold_statement(statement.clone()), statement);
+            if let Statement::Expression(expression) = &statement {
+                assert_eq!(Identity.fold_expression(expression.clone()), *expression);
+            }
+            if let Statement::Let(pattern, _) = &statement {
+                assert_eq!(Identity.fold_pattern(pattern.clone()), *pattern);
+            }
+        }
+    }
+
+    /// Folds additions of two integer literals into a single literal.
+    struct ConstantFolder;
+
+    impl Fold for ConstantFolder {
+        fn fold_expression(&mut self, expression: Expression) -> Expression {
+            match walk_expression(self, expression) {
+                Expression::Infix(left, crate::lexer::token::TokenKind::Plus, right) => {
+                    match (*left, *right) {
+                        (
+                            Expression::Literal(Literal::Integer(a)),
+                            Expression::Literal(Literal::Integer(b)),
+                        ) => Expression::Literal(Literal::Integer(a + b)),
+                        (left, right) => Expression::Infix(
+                            Box::new(left),
+                            crate::lexer::token::TokenKind::Plus,
+                            Box::new(right),
+                        ),
+                    }
+                }
+                expression => expression,
+            }
+        }
+    }
+
+    #[test]
+    fn test_overridden_fold() {
+        let folded = ConstantFolder.fold_program(parse("let x = 1 + 2 + 3 + y; f(4 + 5)"));
+        assert_eq!(folded.to_string(), "let x = (6 + y);f(9)");
+    }
+
+    struct Renamer;
+
+    impl Fold for Renamer {
+        fn fold_identifier(&mut self, identifier: Identifier) -> Identifier {
+            Identifier(identifier.0.to_uppercase())
+        }
+    }
+
+    #[test]
+    fn test_fold_identifiers_everywhere() {
+        let folded = Renamer.fold_program(parse(
+            "let [a, ...b] = c; const d = fn(e) { e }; import \"m\" as f; match (g) { h => h }",
+        ));
+        assert_eq!(
+            folded.to_string(),
+            "let [A, ...B] = C;const D = fn(E) E;import \"m\" as F;match (G) { H => H }"
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use super::{
+    fold::{walk_expression, Fold},
+    Expression, Program, Statement,
+};
+
+/// Rebuilds `program`, passing every expression in it through `modifier`.
+/// Children are modified before their parents, so the modifier always sees
+/// an expression whose sub-expressions have already been rewritten.
+pub fn modify<F>(program: Program, modifier: &mut F) -> Program
+where
+    F: FnMut(Expression) -> Expression,
+{
+    Modifier(modifier).fold_program(program)
+}
+
+pub fn modify_statement<F>(statement: Statement, modifier: &mut F) -> Statement
+where
+    F: FnMut(Expression) -> Expression,
+{
+    Modifier(modifier).fold_statement(statement)
+}
+
+pub fn modify_expression<F>(expression: Expression, modifier: &mut F) -> Expression
+where
+    F: FnMut(Expression) -> Expression,
+{
+    Modifier(modifier).fold_expression(expression)
+}
+
+struct Modifier<'a, F>(&'a mut F);
+
+impl<F> Fold for Modifier<'_, F>
+where
+    F: FnMut(Expression) -> Expression,
+{
+    fn fold_expression(&mut self, expression: Expression) -> Expression {
+        let expression = walk_expression(self, expression);
+        (self.0)(expression)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        lexer::Lexer,
+        parser::{ast::Literal, Parser},
+    };
+
+    fn parse(input: &str) -> Program {
+        let mut parser = Parser::new(Lexer::new(input));
+        let program = parser.parse_program();
+        assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+        program
+    }
+
+    fn turn_one_into_two(expression: Expression) -> Expression {
+        match expression {
+            Expression::Literal(Literal::Integer(1)) => Expression::Literal(Literal::Integer(2)),
+            expression => expression,
+        }
+    }
+
+    #[test]
+    fn test_modify() {
+        let inputs = vec![
+            "1",
+            "1 + 2",
+            "-1",
+            "[1, 2]",
+            "{1: 1}",
+            "a[1]",
+            "a[1:1]",
+            "a?[1]",
+            "if (1) { 1 } else { 1 }",
+            "fn(a) { 1 }",
+            "macro(a) { 1 }",
+            "f(1)",
+            "return 1;",
+            "throw 1;",
+            "let [a] = 1;",
+            "const a = 1;",
+            "export let a = 1;",
+            "match (1) { x if 1 => 1 }",
+            "try { 1 } catch (e) { 1 } finally { 1 }",
+            r#""one ${1}""#,
+        ];
+
+        for input in inputs {
+            let program = modify(parse(input), &mut turn_one_into_two);
+            let expected = parse(&input.replace('1', "2"));
+            assert_eq!(program.statements, expected.statements, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_modify_visits_children_first() {
+        let mut visited = Vec::new();
+        modify(parse("f(a + b)"), &mut |expression| {
+            visited.push(expression.to_string());
+            expression
+        });
+        assert_eq!(visited, vec!["f", "a", "b", "(a + b)", "f((a + b))"]);
+    }
+}
@@ -0,0 +1,462 @@
+//! Prints the AST back out as Monkey source, adding only the parentheses the
+//! precedence rules need.
+//!
+//! `Display` on the AST parenthesizes every operation and drops braces and
+//! quotes, which is handy for checking how something parsed but hard to
+//! read. This is what's shown when a value holding code, like a function, is
+//! inspected.
+
+use super::{
+    CatchClause, EnumVariant, Expression, Identifier, Literal, MatchArm, Pattern, Statement,
+};
+use crate::{lexer::token::TokenKind, parser::Precedence};
+
+/// `fn(params) { body }`, the source of a function expression.
+pub fn function(params: &[Pattern], body: &Statement) -> String {
+    format!("fn({}) {}", patterns(params), block(body))
+}
+
+/// `fn*(params) { body }`, the source of a generator function.
+pub fn generator(params: &[Pattern], body: &Statement) -> String {
+    format!("fn*({}) {}", patterns(params), block(body))
+}
+
+/// `macro(params) { body }`, the source of a macro expression.
+pub fn macro_definition(params: &[Pattern], body: &Statement) -> String {
+    format!("macro({}) {}", patterns(params), block(body))
+}
+
+pub fn statements(statements: &[Statement]) -> String {
+    let mut buf = Vec::with_capacity(statements.len());
+    for (i, statement) in statements.iter().enumerate() {
+        let mut source = self::statement(statement);
+        // Expression statements run into the next one without a separator.
+        if matches!(statement, Statement::Expression(_)) && i + 1 < statements.len() {
+            source.push(';');
+        }
+        buf.push(source);
+    }
+    buf.join(" ")
+}
+
+pub fn statement(statement: &Statement) -> String {
+    match statement {
+        Statement::Let(pattern, value) => {
+            format!("let {} = {};", self::pattern(pattern), expression(value))
+        }
+        Statement::Const(name, value, _) => format!("const {name} = {};", expression(value)),
+        Statement::Import(path, name) => format!("import {} as {name};", string(path)),
+        Statement::Export(statement) => format!("export {}", self::statement(statement)),
+        Statement::Struct(name, fields) => format!("struct {name} {{ {} }}", identifiers(fields)),
+        Statement::Impl(name, methods) => {
+            let methods = methods
+                .iter()
+                .map(|(method, function)| match function {
+                    Expression::Function(params, body) => {
+                        format!("fn {method}({}) {}", patterns(params), block(body))
+                    }
+                    Expression::Generator(params, body) => {
+                        format!("fn* {method}({}) {}", patterns(params), block(body))
+                    }
+                    function => format!("fn {method} {}", expression(function)),
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            format!("impl {name} {{ {methods} }}")
+        }
+        Statement::Enum(name, variants) => {
+            let variants = variants
+                .iter()
+                .map(EnumVariant::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("enum {name} {{ {variants} }}")
+        }
+        Statement::Return(value) => format!("return {};", expression(value)),
+        Statement::Throw(value) => format!("throw {};", expression(value)),
+        Statement::Yield(value) => format!("yield {};", expression(value)),
+        Statement::For(pattern, iterable, body) => format!(
+            "for ({} in {}) {}",
+            self::pattern(pattern),
+            expression(iterable),
+            block(body)
+        ),
+        Statement::Expression(value) => expression(value),
+        Statement::Block(_) => block(statement),
+    }
+}
+
+/// A statement in braces, as the body of a function or branch.
+fn block(statement: &Statement) -> String {
+    match statement {
+        Statement::Block(statements) if statements.is_empty() => "{ }".to_string(),
+        Statement::Block(statements) => format!("{{ {} }}", self::statements(statements)),
+        statement => format!("{{ {} }}", self::statement(statement)),
+    }
+}
+
+pub fn expression(expression: &Expression) -> String {
+    match expression {
+        Expression::Identifier(ident) => ident.to_string(),
+        Expression::Literal(literal) => self::literal(literal),
+        Expression::Array(elements) => format!("[{}]", expressions(elements)),
+        Expression::Hash(pairs) => {
+            let pairs = pairs
+                .iter()
+                .map(|(key, value)| {
+                    format!("{}: {}", self::expression(key), self::expression(value))
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("{{{pairs}}}")
+        }
+        Expression::Interpolated(parts) => {
+            let parts = parts
+                .iter()
+                .map(|part| match part {
+                    Expression::Literal(Literal::String(string)) => escape(string),
+                    part => format!("${{{}}}", self::expression(part)),
+                })
+                .collect::<String>();
+            format!("\"{parts}\"")
+        }
+        Expression::If(condition, consequence, alternative) => {
+            let mut buf = format!(
+                "if ({}) {}",
+                self::expression(condition),
+                block(consequence)
+            );
+            match alternative.as_deref() {
+                Some(Statement::Expression(chained @ Expression::If(_, _, _))) => {
+                    buf.push_str(&format!(" else {}", self::expression(chained)));
+                }
+                Some(alternative) => buf.push_str(&format!(" else {}", block(alternative))),
+                None => {}
+            }
+            buf
+        }
+        Expression::Match(value, arms) => {
+            let arms = arms.iter().map(arm).collect::<Vec<_>>().join(", ");
+            format!("match ({}) {{ {arms} }}", self::expression(value))
+        }
+        Expression::Try(body, catch, finally) => {
+            let mut buf = format!("try {}", block(body));
+            if let Some(catch) = catch {
+                buf.push(' ');
+                buf.push_str(&catch_clause(catch));
+            }
+            if let Some(finally) = finally {
+                buf.push_str(&format!(" finally {}", block(finally)));
+            }
+            buf
+        }
+        Expression::Function(params, body) => function(params, body),
+        Expression::Generator(params, body) => generator(params, body),
+        Expression::Macro(params, body) => macro_definition(params, body),
+        Expression::Call(function, args) => {
+            format!(
+                "{}({})",
+                operand(function, Precedence::Call),
+                expressions(args)
+            )
+        }
+        Expression::StructLiteral(name, fields) => {
+            let fields = fields
+                .iter()
+                .map(|(field, value)| match value {
+                    Expression::Identifier(ident) if ident == field => field.to_string(),
+                    value => format!("{field}: {}", self::expression(value)),
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("{name} {{ {fields} }}")
+        }
+        Expression::Prefix(operator, right) => {
+            // Nested prefixes are parenthesized so `-(-x)` doesn't print as
+            // `--x`.
+            let right = match **right {
+                Expression::Prefix(_, _) => format!("({})", self::expression(right)),
+                _ => operand(right, Precedence::Prefix),
+            };
+            format!("{operator}{right}")
+        }
+        Expression::Infix(left, operator, right) => {
+            let precedence = Precedence::from(operator);
+            // Every infix operator is left associative.
+            format!(
+                "{} {operator} {}",
+                operand(left, precedence),
+                operand_above(right, precedence)
+            )
+        }
+        Expression::Index(left, index) => {
+            format!(
+                "{}[{}]",
+                operand(left, Precedence::Call),
+                self::expression(index)
+            )
+        }
+        Expression::OptionalIndex(left, index) => {
+            format!(
+                "{}?[{}]",
+                operand(left, Precedence::Call),
+                self::expression(index)
+            )
+        }
+        Expression::Slice(left, start, end) => {
+            let bound = |bound: &Option<Box<Expression>>| {
+                bound.as_deref().map(self::expression).unwrap_or_default()
+            };
+            format!(
+                "{}[{}:{}]",
+                operand(left, Precedence::Call),
+                bound(start),
+                bound(end)
+            )
+        }
+        Expression::Field(left, name) => format!("{}.{name}", operand(left, Precedence::Call)),
+        Expression::Assign(target, value) => {
+            // Assignment is right associative.
+            format!(
+                "{} = {}",
+                operand_above(target, Precedence::Assign),
+                operand(value, Precedence::Assign)
+            )
+        }
+    }
+}
+
+/// How tightly an expression binds, or `None` for expressions that can't be
+/// split up by the operators around them, like literals and anything ending
+/// in a closing bracket.
+fn precedence(expression: &Expression) -> Option<Precedence> {
+    match expression {
+        Expression::Infix(_, operator, _) => Some(Precedence::from(operator)),
+        Expression::Prefix(_, _) => Some(Precedence::Prefix),
+        Expression::Assign(_, _) => Some(Precedence::Assign),
+        // Calls, indexing and field access all chain onto whatever comes
+        // before them, so they only need to bind tighter than prefixes.
+        Expression::Call(_, _)
+        | Expression::Index(_, _)
+        | Expression::OptionalIndex(_, _)
+        | Expression::Slice(_, _, _)
+        | Expression::Field(_, _) => Some(Precedence::Call),
+        _ => None,
+    }
+}
+
+/// An operand that has to bind at least as tightly as `min`.
+fn operand(expression: &Expression, min: Precedence) -> String {
+    match precedence(expression) {
+        Some(precedence) if precedence < min => format!("({})", self::expression(expression)),
+        _ => self::expression(expression),
+    }
+}
+
+/// An operand that has to bind more tightly than `min`.
+fn operand_above(expression: &Expression, min: Precedence) -> String {
+    match precedence(expression) {
+        Some(precedence) if precedence <= min => format!("({})", self::expression(expression)),
+        _ => self::expression(expression),
+    }
+}
+
+fn literal(literal: &Literal) -> String {
+    match literal {
+        Literal::String(string) => self::string(string),
+        literal => literal.to_string(),
+    }
+}
+
+/// A string literal, escaped so the lexer reads back the same string.
+fn string(string: &str) -> String {
+    format!("\"{}\"", escape(string))
+}
+
+fn escape(string: &str) -> String {
+    let mut escaped = String::with_capacity(string.len());
+    let mut chars = string.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            '\0' => escaped.push_str("\\0"),
+            '$' if chars.peek() == Some(&'{') => escaped.push_str("\\$"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+pub fn pattern(pattern: &Pattern) -> String {
+    match pattern {
+        Pattern::Literal(literal) => self::literal(literal),
+        Pattern::Array(elements, rest) => {
+            let mut elements = elements.iter().map(self::pattern).collect::<Vec<_>>();
+            if let Some(rest) = rest {
+                elements.push(format!("...{rest}"));
+            }
+            format!("[{}]", elements.join(", "))
+        }
+        Pattern::Hash(entries) => {
+            let entries = entries
+                .iter()
+                .map(|(key, pattern)| match (key, pattern) {
+                    (Literal::String(key), Pattern::Identifier(ident)) if key == &ident.0 => {
+                        key.clone()
+                    }
+                    (Literal::String(key), pattern) if is_identifier(key) => {
+                        format!("{key}: {}", self::pattern(pattern))
+                    }
+                    (key, pattern) => format!("{}: {}", literal(key), self::pattern(pattern)),
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("{{{entries}}}")
+        }
+        Pattern::Variant(enum_name, name, payload) => {
+            let mut buf = match enum_name {
+                Some(enum_name) => format!("{enum_name}.{name}"),
+                None => name.to_string(),
+            };
+            if let Some(payload) = payload {
+                buf.push_str(&format!("({})", patterns(payload)));
+            }
+            buf
+        }
+        Pattern::Wildcard | Pattern::Identifier(_) => pattern.to_string(),
+    }
+}
+
+/// Whether a hash pattern key can be written as a bare name, which has to
+/// lex as an identifier rather than a keyword.
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    let starts_like_a_name = chars.next().is_some_and(|c| c.is_alphabetic() || c == '_');
+    starts_like_a_name
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+        && matches!(TokenKind::from_letters(key), TokenKind::Ident(_))
+}
+
+fn arm(arm: &MatchArm) -> String {
+    let mut buf = pattern(&arm.pattern);
+    if let Some(guard) = &arm.guard {
+        buf.push_str(&format!(" if {}", expression(guard)));
+    }
+    let body = match &arm.body {
+        // A hash would be read back as a block.
+        Statement::Expression(body @ Expression::Hash(_)) => format!("({})", expression(body)),
+        Statement::Expression(body) => expression(body),
+        body => block(body),
+    };
+    format!("{buf} => {body}")
+}
+
+fn catch_clause(catch: &CatchClause) -> String {
+    match &catch.binding {
+        Some(binding) => format!("catch ({}) {}", pattern(binding), block(&catch.body)),
+        None => format!("catch {}", block(&catch.body)),
+    }
+}
+
+fn expressions(expressions: &[Expression]) -> String {
+    expressions
+        .iter()
+        .map(expression)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn patterns(patterns: &[Pattern]) -> String {
+    patterns.iter().map(pattern).collect::<Vec<_>>().join(", ")
+}
+
+fn identifiers(identifiers: &[Identifier]) -> String {
+    identifiers
+        .iter()
+        .map(Identifier::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        lexer::Lexer,
+        parser::{ast::Program, Parser},
+    };
+
+    fn parse(input: &str) -> Program {
+        let mut parser = Parser::new(Lexer::new(input));
+        let program = parser.parse_program();
+        assert!(parser.errors().is_empty(), "{input}: {:?}", parser.errors());
+        program
+    }
+
+    #[test]
+    fn test_minimal_parentheses() {
+        let inputs = vec![
+            ("(a + b) * c", "(a + b) * c"),
+            ("a + (b * c)", "a + b * c"),
+            ("a - (b - c)", "a - (b - c)"),
+            ("(a - b) - c", "a - b - c"),
+            ("-(a + b)", "-(a + b)"),
+            ("-(-a)", "-(-a)"),
+            ("!(a == b)", "!(a == b)"),
+            ("(-a)[0]", "(-a)[0]"),
+            ("-a[0]", "-a[0]"),
+            ("f(1)(2)[3].x", "f(1)(2)[3].x"),
+            ("(a ?? b) + 1", "(a ?? b) + 1"),
+            ("a.b = c.d = 1", "a.b = c.d = 1"),
+            ("(f >> g)(x)", "(f >> g)(x)"),
+            ("x |> f(1)", "f(x, 1)"),
+            (
+                r#"{"a": [1, 2.5, null], true: "${x + 1} \${y}"}"#,
+                r#"{"a": [1, 2.5, null], true: "${x + 1} \${y}"}"#,
+            ),
+            ("Point { x, y: 1 }.x", "Point { x, y: 1 }.x"),
+            ("s[1:-1]", "s[1:-1]"),
+            ("a?[\"b\"]", "a?[\"b\"]"),
+            ("a?.b", "a?[\"b\"]"),
+        ];
+
+        for (input, expected) in inputs {
+            assert_eq!(statements(&parse(input).statements), expected);
+        }
+    }
+
+    #[test]
+    fn test_source_round_trips() {
+        let input = r#"
+let [first, {"key name": value, shorthand, if_: x}, _, 1, ...rest] = source;
+const limit = 10;
+import "lib \"x\".mk" as lib;
+export let exported = lib.value;
+let add = fn(a, [b, c]) { return a + b * c; };
+let m = macro(x) { quote(unquote(x) + 1) };
+let text = "sum:\n${add(1, [2, 3])} and ${-limit}";
+if (first < limit) { data["list"][0] } else if (first > 1) { data?["x"] } else { rest[1:-1] };
+match (value) { 0 => "zero", [h, ...t] if h > 0 => { h }, Circle(r) => ({"r": r}), _ => null };
+try { throw {"message": "x"}; } catch ({message}) { message } finally { puts(text) };
+try { 1 } catch { 2 };
+struct Point { x, y }
+impl Point { fn sum(self) { self.x + self.y } }
+enum Shape { Circle(r), Empty }
+let p = Point { x: 1, y };
+p.x = p.y;
+let f = |x| x |> g >> h;
+let gen = fn*(n) { for ([k, v] in pairs(n)) { if (k) { yield k; } } yield -n; };
+"#;
+        let program = parse(input);
+        let printed = statements(&program.statements);
+        let reparsed = parse(&printed);
+
+        assert_eq!(reparsed.to_string(), program.to_string(), "{printed}");
+        assert_eq!(statements(&reparsed.statements), printed);
+    }
+}
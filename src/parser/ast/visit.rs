@@ -0,0 +1,308 @@
+//! Read-only traversal of the AST. Implement [`Visitor`] and override the
+//! methods for the nodes you care about; the defaults walk into every child.
+//! Overrides can call the matching `walk_*` function to keep descending.
+
+use super::{CatchClause, Expression, Identifier, Literal, MatchArm, Pattern, Program, Statement};
+
+pub trait Visitor<'ast> {
+    fn visit_program(&mut self, program: &'ast Program) {
+        walk_program(self, program)
+    }
+
+    fn visit_statement(&mut self, statement: &'ast Statement) {
+        walk_statement(self, statement)
+    }
+
+    fn visit_expression(&mut self, expression: &'ast Expression) {
+        walk_expression(self, expression)
+    }
+
+    fn visit_pattern(&mut self, pattern: &'ast Pattern) {
+        walk_pattern(self, pattern)
+    }
+
+    fn visit_match_arm(&mut self, arm: &'ast MatchArm) {
+        walk_match_arm(self, arm)
+    }
+
+    fn visit_catch_clause(&mut self, catch: &'ast CatchClause) {
+        walk_catch_clause(self, catch)
+    }
+
+    fn visit_identifier(&mut self, _identifier: &'ast Identifier) {}
+
+    fn visit_literal(&mut self, _literal: &'ast Literal) {}
+}
+
+pub fn walk_program<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, program: &'ast Program) {
+    for statement in &program.statements {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_statement<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    statement: &'ast Statement,
+) {
+    match statement {
+        Statement::Let(pattern, value) => {
+            visitor.visit_pattern(pattern);
+            visitor.visit_expression(value);
+        }
+        Statement::Const(name, value, _) => {
+            visitor.visit_identifier(name);
+            visitor.visit_expression(value);
+        }
+        Statement::Import(_, name) => visitor.visit_identifier(name),
+        Statement::Export(statement) => visitor.visit_statement(statement),
+        Statement::Struct(name, fields) => {
+            visitor.visit_identifier(name);
+            for field in fields {
+                visitor.visit_identifier(field);
+            }
+        }
+        Statement::Enum(name, variants) => {
+            visitor.visit_identifier(name);
+            for variant in variants {
+                visitor.visit_identifier(&variant.name);
+                for field in variant.fields.iter().flatten() {
+                    visitor.visit_identifier(field);
+                }
+            }
+        }
+        Statement::Impl(name, methods) => {
+            visitor.visit_identifier(name);
+            for (method, function) in methods {
+                visitor.visit_identifier(method);
+                visitor.visit_expression(function);
+            }
+        }
+        Statement::Return(value)
+        | Statement::Throw(value)
+        | Statement::Yield(value)
+        | Statement::Expression(value) => visitor.visit_expression(value),
+        Statement::For(pattern, iterable, body) => {
+            visitor.visit_pattern(pattern);
+            visitor.visit_expression(iterable);
+            visitor.visit_statement(body);
+        }
+        Statement::Block(statements) => {
+            for statement in statements {
+                visitor.visit_statement(statement);
+            }
+        }
+    }
+}
+
+pub fn walk_expression<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    expression: &'ast Expression,
+) {
+    match expression {
+        Expression::Identifier(ident) => visitor.visit_identifier(ident),
+        Expression::Literal(literal) => visitor.visit_literal(literal),
+        Expression::Array(elements) | Expression::Interpolated(elements) => {
+            for element in elements {
+                visitor.visit_expression(element);
+            }
+        }
+        Expression::Hash(pairs) => {
+            for (key, value) in pairs {
+                visitor.visit_expression(key);
+                visitor.visit_expression(value);
+            }
+        }
+        Expression::If(condition, consequence, alternative) => {
+            visitor.visit_expression(condition);
+            visitor.visit_statement(consequence);
+            if let Some(alternative) = alternative {
+                visitor.visit_statement(alternative);
+            }
+        }
+        Expression::Match(value, arms) => {
+            visitor.visit_expression(value);
+            for arm in arms {
+                visitor.visit_match_arm(arm);
+            }
+        }
+        Expression::Try(body, catch, finally) => {
+            visitor.visit_statement(body);
+            if let Some(catch) = catch {
+                visitor.visit_catch_clause(catch);
+            }
+            if let Some(finally) = finally {
+                visitor.visit_statement(finally);
+            }
+        }
+        Expression::Function(params, body)
+        | Expression::Generator(params, body)
+        | Expression::Macro(params, body) => {
+            for param in params {
+                visitor.visit_pattern(param);
+            }
+            visitor.visit_statement(body);
+        }
+        Expression::Call(function, args) => {
+            visitor.visit_expression(function);
+            for arg in args {
+                visitor.visit_expression(arg);
+            }
+        }
+        Expression::StructLiteral(name, fields) => {
+            visitor.visit_identifier(name);
+            for (field, value) in fields {
+                visitor.visit_identifier(field);
+                visitor.visit_expression(value);
+            }
+        }
+        Expression::Prefix(_, right) => visitor.visit_expression(right),
+        Expression::Infix(left, _, right) => {
+            visitor.visit_expression(left);
+            visitor.visit_expression(right);
+        }
+        Expression::Field(left, name) => {
+            visitor.visit_expression(left);
+            visitor.visit_identifier(name);
+        }
+        Expression::Index(left, index)
+        | Expression::OptionalIndex(left, index)
+        | Expression::Assign(left, index) => {
+            visitor.visit_expression(left);
+            visitor.visit_expression(index);
+        }
+        Expression::Slice(left, start, end) => {
+            visitor.visit_expression(left);
+            if let Some(start) = start {
+                visitor.visit_expression(start);
+            }
+            if let Some(end) = end {
+                visitor.visit_expression(end);
+            }
+        }
+    }
+}
+
+pub fn walk_pattern<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, pattern: &'ast Pattern) {
+    match pattern {
+        Pattern::Wildcard => {}
+        Pattern::Identifier(ident) => visitor.visit_identifier(ident),
+        Pattern::Literal(literal) => visitor.visit_literal(literal),
+        Pattern::Array(elements, rest) => {
+            for element in elements {
+                visitor.visit_pattern(element);
+            }
+            if let Some(rest) = rest {
+                visitor.visit_identifier(rest);
+            }
+        }
+        Pattern::Hash(entries) => {
+            for (key, pattern) in entries {
+                visitor.visit_literal(key);
+                visitor.visit_pattern(pattern);
+            }
+        }
+        Pattern::Variant(enum_name, name, payload) => {
+            if let Some(enum_name) = enum_name {
+                visitor.visit_identifier(enum_name);
+            }
+            visitor.visit_identifier(name);
+            for pattern in payload.iter().flatten() {
+                visitor.visit_pattern(pattern);
+            }
+        }
+    }
+}
+
+pub fn walk_match_arm<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, arm: &'ast MatchArm) {
+    visitor.visit_pattern(&arm.pattern);
+    if let Some(guard) = &arm.guard {
+        visitor.visit_expression(guard);
+    }
+    visitor.visit_statement(&arm.body);
+}
+
+pub fn walk_catch_clause<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    catch: &'ast CatchClause,
+) {
+    if let Some(binding) = &catch.binding {
+        visitor.visit_pattern(binding);
+    }
+    visitor.visit_statement(&catch.body);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexer::Lexer, parser::Parser};
+
+    fn parse(input: &str) -> Program {
+        let mut parser = Parser::new(Lexer::new(input));
+        let program = parser.parse_program();
+        assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+        program
+    }
+
+    #[derive(Default)]
+    struct Collector<'ast> {
+        identifiers: Vec<&'ast str>,
+        literals: Vec<String>,
+        expressions: usize,
+    }
+
+    impl<'ast> Visitor<'ast> for Collector<'ast> {
+        fn visit_expression(&mut self, expression: &'ast Expression) {
+            self.expressions += 1;
+            walk_expression(self, expression)
+        }
+
+        fn visit_identifier(&mut self, identifier: &'ast Identifier) {
+            self.identifiers.push(&identifier.0);
+        }
+
+        fn visit_literal(&mut self, literal: &'ast Literal) {
+            self.literals.push(literal.to_string());
+        }
+    }
+
+    #[test]
+    fn test_visitor_reaches_every_node() {
+        let program = parse(
+            r#"
+let [a, {"k": b}, ...c] = d;
+const e = fn(f) { f + 1 };
+match (g) { 2 => h, i if i > 3 => { i } };
+try { throw j } catch (k) { k["m"] } finally { l[4:5] };
+"#,
+        );
+        let mut collector = Collector::default();
+        collector.visit_program(&program);
+
+        assert_eq!(
+            collector.identifiers,
+            vec!["a", "b", "c", "d", "e", "f", "f", "g", "h", "i", "i", "i", "j", "k", "k", "l"]
+        );
+        assert_eq!(collector.literals, vec!["k", "1", "2", "3", "m", "4", "5"]);
+        assert_eq!(collector.expressions, 21);
+    }
+
+    /// Counts function literals without looking inside them.
+    struct ShallowFunctionCounter(usize);
+
+    impl Visitor<'_> for ShallowFunctionCounter {
+        fn visit_expression(&mut self, expression: &Expression) {
+            match expression {
+                Expression::Function(_, _) => self.0 += 1,
+                expression => walk_expression(self, expression),
+            }
+        }
+    }
+
+    #[test]
+    fn test_visitor_can_skip_children() {
+        let program = parse("let f = fn() { fn() { 1 } }; [fn() {}, 1 + 2];");
+        let mut counter = ShallowFunctionCounter(0);
+        counter.visit_program(&program);
+        assert_eq!(counter.0, 2);
+    }
+}
@@ -0,0 +1,276 @@
+//! In-place traversal of the AST, the mutable counterpart of
+//! [`Visitor`](super::visit::Visitor). Nodes can be edited where they are,
+//! but not replaced by a different kind of node; use
+//! [`Fold`](super::fold::Fold) for that.
+
+use super::{CatchClause, Expression, Identifier, Literal, MatchArm, Pattern, Program, Statement};
+
+pub trait VisitorMut {
+    fn visit_program_mut(&mut self, program: &mut Program) {
+        walk_program_mut(self, program)
+    }
+
+    fn visit_statement_mut(&mut self, statement: &mut Statement) {
+        walk_statement_mut(self, statement)
+    }
+
+    fn visit_expression_mut(&mut self, expression: &mut Expression) {
+        walk_expression_mut(self, expression)
+    }
+
+    fn visit_pattern_mut(&mut self, pattern: &mut Pattern) {
+        walk_pattern_mut(self, pattern)
+    }
+
+    fn visit_match_arm_mut(&mut self, arm: &mut MatchArm) {
+        walk_match_arm_mut(self, arm)
+    }
+
+    fn visit_catch_clause_mut(&mut self, catch: &mut CatchClause) {
+        walk_catch_clause_mut(self, catch)
+    }
+
+    fn visit_identifier_mut(&mut self, _identifier: &mut Identifier) {}
+
+    fn visit_literal_mut(&mut self, _literal: &mut Literal) {}
+}
+
+pub fn walk_program_mut<V: VisitorMut + ?Sized>(visitor: &mut V, program: &mut Program) {
+    for statement in &mut program.statements {
+        visitor.visit_statement_mut(statement);
+    }
+}
+
+pub fn walk_statement_mut<V: VisitorMut + ?Sized>(visitor: &mut V, statement: &mut Statement) {
+    match statement {
+        Statement::Let(pattern, value) => {
+            visitor.visit_pattern_mut(pattern);
+            visitor.visit_expression_mut(value);
+        }
+        Statement::Const(name, value, _) => {
+            visitor.visit_identifier_mut(name);
+            visitor.visit_expression_mut(value);
+        }
+        Statement::Import(_, name) => visitor.visit_identifier_mut(name),
+        Statement::Export(statement) => visitor.visit_statement_mut(statement),
+        Statement::Struct(name, fields) => {
+            visitor.visit_identifier_mut(name);
+            for field in fields {
+                visitor.visit_identifier_mut(field);
+            }
+        }
+        Statement::Enum(name, variants) => {
+            visitor.visit_identifier_mut(name);
+            for variant in variants {
+                visitor.visit_identifier_mut(&mut variant.name);
+                for field in variant.fields.iter_mut().flatten() {
+                    visitor.visit_identifier_mut(field);
+                }
+            }
+        }
+        Statement::Impl(name, methods) => {
+            visitor.visit_identifier_mut(name);
+            for (method, function) in methods {
+                visitor.visit_identifier_mut(method);
+                visitor.visit_expression_mut(function);
+            }
+        }
+        Statement::Return(value)
+        | Statement::Throw(value)
+        | Statement::Yield(value)
+        | Statement::Expression(value) => visitor.visit_expression_mut(value),
+        Statement::For(pattern, iterable, body) => {
+            visitor.visit_pattern_mut(pattern);
+            visitor.visit_expression_mut(iterable);
+            visitor.visit_statement_mut(body);
+        }
+        Statement::Block(statements) => {
+            for statement in statements {
+                visitor.visit_statement_mut(statement);
+            }
+        }
+    }
+}
+
+pub fn walk_expression_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expression: &mut Expression) {
+    match expression {
+        Expression::Identifier(ident) => visitor.visit_identifier_mut(ident),
+        Expression::Literal(literal) => visitor.visit_literal_mut(literal),
+        Expression::Array(elements) | Expression::Interpolated(elements) => {
+            for element in elements {
+                visitor.visit_expression_mut(element);
+            }
+        }
+        Expression::Hash(pairs) => {
+            for (key, value) in pairs {
+                visitor.visit_expression_mut(key);
+                visitor.visit_expression_mut(value);
+            }
+        }
+        Expression::If(condition, consequence, alternative) => {
+            visitor.visit_expression_mut(condition);
+            visitor.visit_statement_mut(consequence);
+            if let Some(alternative) = alternative {
+                visitor.visit_statement_mut(alternative);
+            }
+        }
+        Expression::Match(value, arms) => {
+            visitor.visit_expression_mut(value);
+            for arm in arms {
+                visitor.visit_match_arm_mut(arm);
+            }
+        }
+        Expression::Try(body, catch, finally) => {
+            visitor.visit_statement_mut(body);
+            if let Some(catch) = catch {
+                visitor.visit_catch_clause_mut(catch);
+            }
+            if let Some(finally) = finally {
+                visitor.visit_statement_mut(finally);
+            }
+        }
+        Expression::Function(params, body)
+        | Expression::Generator(params, body)
+        | Expression::Macro(params, body) => {
+            for param in params {
+                visitor.visit_pattern_mut(param);
+            }
+            visitor.visit_statement_mut(body);
+        }
+        Expression::Call(function, args) => {
+            visitor.visit_expression_mut(function);
+            for arg in args {
+                visitor.visit_expression_mut(arg);
+            }
+        }
+        Expression::StructLiteral(name, fields) => {
+            visitor.visit_identifier_mut(name);
+            for (field, value) in fields {
+                visitor.visit_identifier_mut(field);
+                visitor.visit_expression_mut(value);
+            }
+        }
+        Expression::Prefix(_, right) => visitor.visit_expression_mut(right),
+        Expression::Infix(left, _, right) => {
+            visitor.visit_expression_mut(left);
+            visitor.visit_expression_mut(right);
+        }
+        Expression::Field(left, name) => {
+            visitor.visit_expression_mut(left);
+            visitor.visit_identifier_mut(name);
+        }
+        Expression::Index(left, index)
+        | Expression::OptionalIndex(left, index)
+        | Expression::Assign(left, index) => {
+            visitor.visit_expression_mut(left);
+            visitor.visit_expression_mut(index);
+        }
+        Expression::Slice(left, start, end) => {
+            visitor.visit_expression_mut(left);
+            if let Some(start) = start {
+                visitor.visit_expression_mut(start);
+            }
+            if let Some(end) = end {
+                visitor.visit_expression_mut(end);
+            }
+        }
+    }
+}
+
+pub fn walk_pattern_mut<V: VisitorMut + ?Sized>(visitor: &mut V, pattern: &mut Pattern) {
+    match pattern {
+        Pattern::Wildcard => {}
+        Pattern::Identifier(ident) => visitor.visit_identifier_mut(ident),
+        Pattern::Literal(literal) => visitor.visit_literal_mut(literal),
+        Pattern::Array(elements, rest) => {
+            for element in elements {
+                visitor.visit_pattern_mut(element);
+            }
+            if let Some(rest) = rest {
+                visitor.visit_identifier_mut(rest);
+            }
+        }
+        Pattern::Hash(entries) => {
+            for (key, pattern) in entries {
+                visitor.visit_literal_mut(key);
+                visitor.visit_pattern_mut(pattern);
+            }
+        }
+        Pattern::Variant(enum_name, name, payload) => {
+            if let Some(enum_name) = enum_name {
+                visitor.visit_identifier_mut(enum_name);
+            }
+            visitor.visit_identifier_mut(name);
+            for pattern in payload.iter_mut().flatten() {
+                visitor.visit_pattern_mut(pattern);
+            }
+        }
+    }
+}
+
+pub fn walk_match_arm_mut<V: VisitorMut + ?Sized>(visitor: &mut V, arm: &mut MatchArm) {
+    visitor.visit_pattern_mut(&mut arm.pattern);
+    if let Some(guard) = &mut arm.guard {
+        visitor.visit_expression_mut(guard);
+    }
+    visitor.visit_statement_mut(&mut arm.body);
+}
+
+pub fn walk_catch_clause_mut<V: VisitorMut + ?Sized>(visitor: &mut V, catch: &mut CatchClause) {
+    if let Some(binding) = &mut catch.binding {
+        visitor.visit_pattern_mut(binding);
+    }
+    visitor.visit_statement_mut(&mut catch.body);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexer::Lexer, parser::Parser};
+
+    fn parse(input: &str) -> Program {
+        let mut parser = Parser::new(Lexer::new(input));
+        let program = parser.parse_program();
+        assert!(parser.errors().is_empty(), "{:?}", parser.errors());
+        program
+    }
+
+    struct Prefixer;
+
+    impl VisitorMut for Prefixer {
+        fn visit_identifier_mut(&mut self, identifier: &mut Identifier) {
+            identifier.0.insert_str(0, "my_");
+        }
+
+        fn visit_literal_mut(&mut self, literal: &mut Literal) {
+            if let Literal::Integer(int) = literal {
+                *int *= 10;
+            }
+        }
+    }
+
+    #[test]
+    fn test_visitor_mut_edits_in_place() {
+        let mut program = parse(
+            "let [a, ...b] = [1, c]; const d = fn(e) { e * 2 }; match (f) { 3 => g, {\"k\": h} => h }",
+        );
+        Prefixer.visit_program_mut(&mut program);
+
+        assert_eq!(
+            program.to_string(),
+            "let [my_a, ...my_b] = [10, my_c];const my_d = fn(my_e) (my_e * 20);match (my_f) { 30 => my_g, {k: my_h} => my_h }"
+        );
+    }
+
+    struct Nothing;
+
+    impl VisitorMut for Nothing {}
+
+    #[test]
+    fn test_default_visitor_mut_changes_nothing() {
+        let input = r#"try { x?.y ?? z[1:] } catch ({message}) { "${message}" } finally { -a }"#;
+        let mut program = parse(input);
+        Nothing.visit_program_mut(&mut program);
+        assert_eq!(program.statements, parse(input).statements);
+    }
+}
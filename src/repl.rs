@@ -1,12 +1,26 @@
-use std::io::{self, BufRead, Write};
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+    path::Path,
+};
 
-use crate::{evaluator::object::Environment, evaluator::Eval, lexer::Lexer, parser::Parser};
+use crate::{
+    evaluator::{
+        macro_expansion::{define_macros, expand_macros},
+        module,
+        object::{Env, Environment, Object},
+        Eval,
+    },
+    lexer::Lexer,
+    parser::{ast::Program, Parser},
+};
 
 const PROMPT: &str = ">> ";
 
 pub fn start(mut input: impl BufRead) -> io::Result<()> {
     let mut buf = String::new();
     let env = Environment::new().into_env();
+    let macro_env = Environment::new().into_env();
 
     loop {
         print!("{PROMPT}");
@@ -25,8 +39,12 @@ pub fn start(mut input: impl BufRead) -> io::Result<()> {
             print_parser_errors(errors);
             continue;
         }
+        print_parser_warnings(parser.warnings());
 
-        let evaluated = program.eval(env.clone());
+        let evaluated = match expand(program, &macro_env) {
+            Ok(program) => program.eval(env.clone()),
+            Err(error) => Some(error),
+        };
         if let Some(value) = evaluated {
             println!("{}", value.inspect());
         }
@@ -35,6 +53,41 @@ pub fn start(mut input: impl BufRead) -> io::Result<()> {
     }
 }
 
+/// Runs a whole script, resolving its imports relative to where it lives.
+pub fn run_file(path: &Path) -> io::Result<()> {
+    let source = fs::read_to_string(path)?;
+
+    let mut parser = Parser::new(Lexer::new(&source));
+    let program = parser.parse_program();
+
+    let errors = parser.errors();
+    if !errors.is_empty() {
+        print_parser_errors(errors);
+        return Ok(());
+    }
+    print_parser_warnings(parser.warnings());
+
+    let path = fs::canonicalize(path)?;
+    let env = Environment::new_for_file(&path, Default::default()).into_env();
+    let evaluated = match expand(program, &Environment::new().into_env()) {
+        Ok(program) => module::eval_file(&path, &program, env),
+        Err(error) => Some(error),
+    };
+    if let Some(error) = evaluated.filter(|value| value.is_error()) {
+        println!("{}", error.inspect());
+    }
+    Ok(())
+}
+
+fn expand(mut program: Program, macro_env: &Env) -> Result<Program, Object> {
+    define_macros(&mut program, macro_env)?;
+    expand_macros(program, macro_env)
+}
+
 fn print_parser_errors(errors: &[String]) {
     errors.iter().for_each(|e| println!("\t{e}"))
 }
+
+fn print_parser_warnings(warnings: &[String]) {
+    warnings.iter().for_each(|w| println!("\twarning: {w}"))
+}